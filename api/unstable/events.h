@@ -122,6 +122,131 @@ typedef void (*s2n_event_on_timing_checkpoint_cb)(struct s2n_connection *conn, v
  */
 S2N_API extern int s2n_config_set_timing_checkpoint_cb(struct s2n_config *config, s2n_event_on_timing_checkpoint_cb callback);
 
+/**
+ * Emitted once per connection, when a connection that called s2n_negotiate is
+ * wiped with s2n_connection_wipe or freed with s2n_connection_free. This covers
+ * connections closed by s2n_shutdown, connections closed by an error, and
+ * connections that are discarded without being shut down. Renegotiation does
+ * not close the connection, so s2n_renegotiate_wipe does not emit the event.
+ *
+ * The pointer passed to the callback is valid only for the duration of the
+ * callback invocation. The connection is being torn down, so the callback
+ * should only read from it.
+ */
+struct s2n_event_connection_close {
+    /* The number of bytes read from the transport, including record framing */
+    uint64_t wire_bytes_in;
+    /* The number of bytes written to the transport, including record framing */
+    uint64_t wire_bytes_out;
+    /**
+     * The first call to s2n_negotiate. This is not an interpretable time, and
+     * only has meaning in reference to connection_end_ns. Both use the same
+     * clock as the timestamps in struct s2n_event_handshake.
+     */
+    uint64_t connection_start_ns;
+    uint64_t connection_end_ns;
+};
+
+typedef void (*s2n_event_on_connection_close_cb)(struct s2n_connection *conn, void *subscriber, struct s2n_event_connection_close *event);
+
+/**
+ * Set a callback to receive a connection close event.
+ *
+ * The same `subscriber` pointer set via s2n_config_set_subscriber is passed
+ * as the second argument to the callback. If no subscriber has been set,
+ * NULL is passed.
+ *
+ * Returns S2N_SUCCESS on success.
+ * Returns S2N_FAILURE with S2N_ERR_NULL if config or callback is NULL.
+ */
+S2N_API extern int s2n_config_set_connection_close_event(struct s2n_config *config, s2n_event_on_connection_close_cb callback);
+
+/**
+ * Emitted for each alert sent to or received from the peer, including
+ * close_notify alerts and warnings which s2n-tls ignores.
+ *
+ * The pointer passed to the callback is valid only for the duration of the
+ * callback invocation.
+ */
+struct s2n_event_alert {
+    /* The alert level: 1 = warning, 2 = fatal */
+    uint8_t level;
+    /* The alert description, e.g. 0 = close_notify, 40 = handshake_failure */
+    uint8_t description;
+    /* 1 if this endpoint sent the alert, 0 if it was received from the peer */
+    uint8_t sent;
+};
+
+typedef void (*s2n_event_on_alert_cb)(struct s2n_connection *conn, void *subscriber, struct s2n_event_alert *event);
+
+/**
+ * Set a callback to receive an event for each alert sent or received.
+ *
+ * The same `subscriber` pointer set via s2n_config_set_subscriber is passed
+ * as the second argument to the callback. If no subscriber has been set,
+ * NULL is passed.
+ *
+ * Returns S2N_SUCCESS on success.
+ * Returns S2N_FAILURE with S2N_ERR_NULL if config or callback is NULL.
+ */
+S2N_API extern int s2n_config_set_alert_event(struct s2n_config *config, s2n_event_on_alert_cb callback);
+
+/**
+ * Emitted for each TLS1.3 KeyUpdate message sent to or received from the peer.
+ *
+ * The pointer passed to the callback is valid only for the duration of the
+ * callback invocation.
+ */
+struct s2n_event_key_update {
+    /* 1 if this endpoint sent the KeyUpdate, 0 if it was received from the peer */
+    uint8_t sent;
+    /* 1 if the KeyUpdate requested that the receiver also update its sending keys */
+    uint8_t update_requested;
+};
+
+typedef void (*s2n_event_on_key_update_cb)(struct s2n_connection *conn, void *subscriber, struct s2n_event_key_update *event);
+
+/**
+ * Set a callback to receive an event for each KeyUpdate sent or received.
+ *
+ * The same `subscriber` pointer set via s2n_config_set_subscriber is passed
+ * as the second argument to the callback. If no subscriber has been set,
+ * NULL is passed.
+ *
+ * Returns S2N_SUCCESS on success.
+ * Returns S2N_FAILURE with S2N_ERR_NULL if config or callback is NULL.
+ */
+S2N_API extern int s2n_config_set_key_update_event(struct s2n_config *config, s2n_event_on_key_update_cb callback);
+
+/**
+ * Emitted for each session ticket issued by a server or received by a client,
+ * for both TLS1.2 and TLS1.3. Empty TLS1.2 tickets and TLS1.3 tickets with a
+ * lifetime of zero are not reported, because they can't be used to resume.
+ *
+ * The pointer passed to the callback is valid only for the duration of the
+ * callback invocation.
+ */
+struct s2n_event_session_ticket {
+    /* 1 if this endpoint issued the ticket, 0 if it was received from the peer */
+    uint8_t sent;
+    /* The ticket lifetime hint, in seconds */
+    uint32_t lifetime_secs;
+};
+
+typedef void (*s2n_event_on_session_ticket_cb)(struct s2n_connection *conn, void *subscriber, struct s2n_event_session_ticket *event);
+
+/**
+ * Set a callback to receive an event for each session ticket issued or received.
+ *
+ * The same `subscriber` pointer set via s2n_config_set_subscriber is passed
+ * as the second argument to the callback. If no subscriber has been set,
+ * NULL is passed.
+ *
+ * Returns S2N_SUCCESS on success.
+ * Returns S2N_FAILURE with S2N_ERR_NULL if config or callback is NULL.
+ */
+S2N_API extern int s2n_config_set_session_ticket_event(struct s2n_config *config, s2n_event_on_session_ticket_cb callback);
+
 #ifdef __cplusplus
 }
 #endif
@@ -0,0 +1,583 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The runtime-agnostic TLS stream shared by `s2n-tls-tokio` and
+//! `s2n-tls-futures`.
+//!
+//! [`TlsStream`] drives a [`Connection`] over any stream, using a [`Runtime`]
+//! to perform IO and to create timers. Each runtime crate implements
+//! [`Runtime`] for its IO traits, and wraps [`TlsStream`] to implement them.
+//!
+//! This crate is not intended for direct consumption.
+
+#[cfg(not(target_os = "windows"))]
+use errno::{set_errno, Errno};
+use s2n_tls::{
+    connection::Connection,
+    enums::{Blinding, CallbackResult},
+    error::Error,
+};
+use std::{
+    fmt,
+    future::Future,
+    io,
+    mem::MaybeUninit,
+    os::raw::{c_int, c_void},
+    pin::Pin,
+    task::{
+        Context, Poll,
+        Poll::{Pending, Ready},
+    },
+    time::Duration,
+};
+
+#[cfg(feature = "unstable-renegotiate")]
+mod renegotiate;
+
+// TODO use the version from s2n_quic_core
+mod task;
+use task::waker::debug_assert_contract as debug_assert_waker_contract;
+
+macro_rules! ready {
+    ($x:expr) => {
+        match $x {
+            Ready(r) => r,
+            Pending => return Pending,
+        }
+    };
+}
+
+/// The IO operations and timers of an async runtime, for streams of type `S`.
+pub trait Runtime<S>: Unpin {
+    /// A future that completes once the duration passed to [`Runtime::sleep`]
+    /// has elapsed.
+    type Sleep: Future<Output = ()>;
+
+    /// Returns a future that completes after `duration`.
+    fn sleep(&self, duration: Duration) -> Self::Sleep;
+
+    /// Reads from `stream` into `buf`, returning the number of bytes read.
+    fn poll_read(stream: &mut S, ctx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>>;
+
+    /// Writes `buf` to `stream`, returning the number of bytes written.
+    fn poll_write(stream: &mut S, ctx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>>;
+
+    fn poll_flush(stream: &mut S, ctx: &mut Context<'_>) -> Poll<io::Result<()>>;
+
+    /// Closes the write half of `stream`.
+    fn poll_shutdown(stream: &mut S, ctx: &mut Context<'_>) -> Poll<io::Result<()>>;
+}
+
+/// The error returned when a handshake or shutdown does not complete
+/// within the configured timeout.
+///
+/// The timeout is surfaced as an [`Error`] with
+/// [`ErrorSource::Application`](s2n_tls::error::ErrorSource::Application).
+/// Use [`TimeoutError::from_error`] to identify it.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeoutError {
+    /// The TLS handshake did not complete in time.
+    Handshake,
+    /// The TLS shutdown did not complete in time.
+    Shutdown,
+}
+
+impl TimeoutError {
+    /// Returns the `TimeoutError` wrapped by `error`, if there is one.
+    pub fn from_error(error: &Error) -> Option<Self> {
+        error
+            .application_error()
+            .and_then(|inner| inner.downcast_ref::<Self>())
+            .copied()
+    }
+}
+
+impl fmt::Display for TimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TimeoutError::Handshake => f.write_str("TLS handshake timed out"),
+            TimeoutError::Shutdown => f.write_str("TLS shutdown timed out"),
+        }
+    }
+}
+
+impl std::error::Error for TimeoutError {}
+
+impl From<TimeoutError> for Error {
+    fn from(input: TimeoutError) -> Self {
+        Error::application(Box::new(input))
+    }
+}
+
+/// The handshake timeout used unless another is configured.
+pub const DEFAULT_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The shutdown timeout used unless another is configured.
+pub const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Timeouts applied to a [`TlsStream`]. `None` disables a timeout.
+#[derive(Clone, Copy, Debug)]
+pub struct Timeouts {
+    pub handshake: Option<Duration>,
+    pub shutdown: Option<Duration>,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            handshake: Some(DEFAULT_HANDSHAKE_TIMEOUT),
+            shutdown: Some(DEFAULT_SHUTDOWN_TIMEOUT),
+        }
+    }
+}
+
+struct TlsHandshake<'a, S, C, R>
+where
+    C: AsRef<Connection> + AsMut<Connection> + Unpin,
+    R: Runtime<S>,
+{
+    tls: &'a mut TlsStream<S, C, R>,
+    error: Option<Error>,
+    timeout: Option<Pin<Box<R::Sleep>>>,
+}
+
+impl<S, C, R> Future for TlsHandshake<'_, S, C, R>
+where
+    C: AsRef<Connection> + AsMut<Connection> + Unpin,
+    R: Runtime<S>,
+{
+    type Output = Result<(), Error>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        debug_assert_waker_contract(ctx, |ctx| {
+            // Retrieve a result, either from the stored error
+            // or by polling Connection::poll_negotiate().
+            // Connection::poll_negotiate() only completes once,
+            // regardless of how often this method is polled.
+            //
+            // The handshake timeout only bounds Connection::poll_negotiate(),
+            // so it never cuts short the blinding applied by poll_shutdown().
+            let result = match self.error.take() {
+                Some(err) => Err(err),
+                None => {
+                    let handshake_poll = self.tls.with_io(ctx, |conn| {
+                        conn.as_mut().poll_negotiate().map(|r| r.map(|_| ()))
+                    });
+                    match (handshake_poll, self.timeout.as_mut()) {
+                        (Ready(result), _) => result,
+                        (Pending, Some(timer)) => {
+                            ready!(timer.as_mut().poll(ctx));
+                            Err(TimeoutError::Handshake.into())
+                        }
+                        (Pending, None) => return Pending,
+                    }
+                }
+            };
+            // If the result isn't a fatal error, return it immediately.
+            // Otherwise, poll Connection::poll_shutdown().
+            //
+            // Shutdown is only best-effort.
+            // When Connection::poll_shutdown() completes, even with an error,
+            // we return the original Connection::poll_negotiate() error.
+            match result {
+                Ok(r) => Ok(r).into(),
+                Err(e) if e.is_retryable() => Err(e).into(),
+                Err(e) => match self.tls.poll_shutdown(ctx) {
+                    Pending => {
+                        self.error = Some(e);
+                        Pending
+                    }
+                    Ready(_) => Err(e).into(),
+                },
+            }
+        })
+    }
+}
+
+pub struct TlsStream<S, C, R>
+where
+    C: AsRef<Connection> + AsMut<Connection> + Unpin,
+    R: Runtime<S>,
+{
+    conn: C,
+    stream: S,
+    runtime: R,
+    /// Bytes already read from `stream` that s2n-tls has not received yet.
+    replay: Vec<u8>,
+    /// Application data decrypted outside of `poll_read`, such as by another
+    /// process before the stream was migrated, or while renegotiating.
+    /// Returned before any data that s2n-tls has not decrypted yet.
+    unread: Vec<u8>,
+    blinding: Option<Pin<Box<R::Sleep>>>,
+    shutdown_error: Option<Error>,
+    shutdown_timeout: Option<Duration>,
+    shutdown_timer: Option<Pin<Box<R::Sleep>>>,
+}
+
+impl<S, C, R> TlsStream<S, C, R>
+where
+    C: AsRef<Connection> + AsMut<Connection> + Unpin,
+    R: Runtime<S>,
+{
+    /// Performs the handshake on `conn` over `stream`.
+    ///
+    /// `replay` is provided to s2n-tls before any data read from `stream`.
+    pub async fn open(
+        conn: C,
+        stream: S,
+        runtime: R,
+        replay: Vec<u8>,
+        timeouts: Timeouts,
+    ) -> Result<Self, Error> {
+        let handshake_timeout = timeouts
+            .handshake
+            .map(|timeout| Box::pin(runtime.sleep(timeout)));
+        let mut tls = TlsStream {
+            conn,
+            stream,
+            runtime,
+            replay,
+            unread: Vec::new(),
+            blinding: None,
+            shutdown_error: None,
+            shutdown_timeout: timeouts.shutdown,
+            shutdown_timer: None,
+        };
+        TlsHandshake {
+            tls: &mut tls,
+            error: None,
+            timeout: handshake_timeout,
+        }
+        .await?;
+        Ok(tls)
+    }
+
+    /// Assembles a `TlsStream` from an already negotiated connection.
+    ///
+    /// Like a negotiated stream, its shutdown times out after
+    /// [`DEFAULT_SHUTDOWN_TIMEOUT`].
+    pub fn from_parts(conn: C, stream: S, runtime: R) -> Self {
+        TlsStream {
+            conn,
+            stream,
+            runtime,
+            replay: Vec::new(),
+            unread: Vec::new(),
+            blinding: None,
+            shutdown_error: None,
+            shutdown_timeout: Some(DEFAULT_SHUTDOWN_TIMEOUT),
+            shutdown_timer: None,
+        }
+    }
+
+    pub fn into_parts(self) -> (C, S) {
+        (self.conn, self.stream)
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Whether bytes have been read from the stream that s2n-tls has not
+    /// received yet.
+    pub fn has_replay(&self) -> bool {
+        !self.replay.is_empty()
+    }
+
+    /// Takes the application data that has been decrypted but not read.
+    pub fn take_unread(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.unread)
+    }
+
+    /// Sets application data to return before any data that s2n-tls has
+    /// not decrypted yet.
+    pub fn set_unread(&mut self, unread: Vec<u8>) {
+        self.unread = unread;
+    }
+
+    /// Performs `action` on the connection, with the connection's IO
+    /// configured to use the stream.
+    pub fn with_io<F, T>(&mut self, ctx: &mut Context, action: F) -> Poll<Result<T, Error>>
+    where
+        F: FnOnce(&mut C) -> Poll<Result<T, Error>>,
+    {
+        // Setting contexts on a connection is considered unsafe
+        // because the raw pointers provide no lifetime or memory guarantees.
+        // We protect against this by holding the exclusive borrow of the
+        // stream during the action and clearing the context afterwards.
+        unsafe {
+            let context = self as *mut Self as *mut c_void;
+
+            let conn = self.conn.as_mut();
+            conn.set_receive_callback(Some(Self::recv_io_cb))?;
+            conn.set_send_callback(Some(Self::send_io_cb))?;
+            conn.set_receive_context(context)?;
+            conn.set_send_context(context)?;
+            conn.set_waker(Some(ctx.waker()))?;
+            conn.set_blinding(Blinding::SelfService)?;
+
+            let result = action(&mut self.conn);
+
+            let conn = self.conn.as_mut();
+            conn.set_receive_callback(None)?;
+            conn.set_send_callback(None)?;
+            conn.set_receive_context(std::ptr::null_mut())?;
+            conn.set_send_context(std::ptr::null_mut())?;
+            conn.set_waker(None)?;
+            result
+        }
+    }
+
+    fn poll_io<F>(ctx: *mut c_void, action: F) -> c_int
+    where
+        F: FnOnce(&mut S, &mut Context) -> Poll<Result<usize, std::io::Error>>,
+    {
+        debug_assert_ne!(ctx, std::ptr::null_mut());
+        let tls = unsafe { &mut *(ctx as *mut Self) };
+
+        let mut async_context = Context::from_waker(tls.conn.as_ref().waker().unwrap());
+
+        let res = debug_assert_waker_contract(&mut async_context, |async_context| {
+            action(&mut tls.stream, async_context)
+        });
+
+        match res {
+            Poll::Ready(Ok(len)) => len as c_int,
+            Poll::Pending => {
+                Self::set_io_would_block();
+                CallbackResult::Failure.into()
+            }
+            _ => CallbackResult::Failure.into(),
+        }
+    }
+
+    /// Signal a "would block" to s2n's C IO layer by setting the CRT `errno` to
+    /// EWOULDBLOCK. `s2n_io.c` reads `errno` to distinguish a retriable blocked
+    /// read/write from a fatal IO error.
+    fn set_io_would_block() {
+        #[cfg(not(target_os = "windows"))]
+        {
+            // The `errno` crate writes the CRT errno, which is what s2n reads.
+            set_errno(Errno(libc::EWOULDBLOCK));
+        }
+
+        // On Windows the `errno` crate writes the Win32 last-error, not the CRT
+        // `errno` that s2n reads, so set the CRT errno directly. s2n and this
+        // code share one statically linked CRT, so `_set_errno` and `errno` hit
+        // the same thread-local variable.
+        #[cfg(target_os = "windows")]
+        {
+            extern "C" {
+                fn _set_errno(value: core::ffi::c_int) -> core::ffi::c_int;
+            }
+            // SAFETY: `_set_errno` only writes the thread-local CRT errno.
+            unsafe {
+                let _ = _set_errno(libc::EWOULDBLOCK);
+            }
+        }
+    }
+
+    unsafe extern "C" fn recv_io_cb(ctx: *mut c_void, buf: *mut u8, len: u32) -> c_int {
+        debug_assert_ne!(ctx, std::ptr::null_mut());
+        let tls = &mut *(ctx as *mut Self);
+        if !tls.replay.is_empty() {
+            let len = tls.replay.len().min(len.try_into().unwrap());
+            std::slice::from_raw_parts_mut(buf, len).copy_from_slice(&tls.replay[..len]);
+            tls.replay.drain(..len);
+            return len as c_int;
+        }
+
+        Self::poll_io(ctx, |stream, async_context| {
+            let len: usize = len.try_into().unwrap();
+            let dest = std::slice::from_raw_parts_mut(buf, len);
+            R::poll_read(stream, async_context, dest)
+        })
+    }
+
+    unsafe extern "C" fn send_io_cb(ctx: *mut c_void, buf: *const u8, len: u32) -> c_int {
+        Self::poll_io(ctx, |stream, async_context| {
+            let len: usize = len.try_into().unwrap();
+            let src = std::slice::from_raw_parts(buf, len);
+            R::poll_write(stream, async_context, src)
+        })
+    }
+
+    /// Polls the blinding timer, if there is any.
+    ///
+    /// s2n has a "blinding" functionality - when a bad behavior from the peer
+    /// is detected, sleeps for 10-30 seconds before answering the client
+    /// and closing the connection. This mitigates some timing side channels
+    /// that could leak information about encrypted data. See the
+    /// `s2n_connection_set_blinding` docs for more details.
+    ///
+    /// For security reasons, to allow for blinding to correctly function,
+    /// before dropping an s2n connection, you should wait until either
+    /// `poll_blinding` or `poll_shutdown` (which calls `poll_blinding`
+    /// internally) returns ready.
+    pub fn poll_blinding(&mut self, ctx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        debug_assert_waker_contract(ctx, |ctx| {
+            if self.blinding.is_none() {
+                let delay = self.conn.as_ref().remaining_blinding_delay()?;
+                if !delay.is_zero() {
+                    // Timers may operate at the milisecond resolution, so add an extra
+                    // millisecond to account for any stray nanoseconds.
+                    let safety = Duration::from_millis(1);
+                    let timer = self.runtime.sleep(delay.saturating_add(safety));
+                    self.blinding = Some(Box::pin(timer));
+                }
+            };
+
+            if let Some(timer) = self.blinding.as_mut() {
+                ready!(timer.as_mut().poll(ctx));
+                self.blinding = None;
+            }
+
+            Poll::Ready(Ok(()))
+        })
+    }
+
+    pub async fn apply_blinding(&mut self) -> Result<(), Error> {
+        std::future::poll_fn(|ctx| self.poll_blinding(ctx)).await
+    }
+
+    /// Reads decrypted application data into `buf`, returning the number of
+    /// bytes read.
+    ///
+    /// Never de-initializes any part of `buf`. The first bytes of `buf`, up to
+    /// the returned length, are initialized.
+    pub fn poll_read(
+        &mut self,
+        ctx: &mut Context<'_>,
+        buf: &mut [MaybeUninit<u8>],
+    ) -> Poll<io::Result<usize>> {
+        if !self.unread.is_empty() {
+            let len = self.unread.len().min(buf.len());
+            for (dest, byte) in buf.iter_mut().zip(self.unread.drain(..len)) {
+                dest.write(byte);
+            }
+            return Ready(Ok(len));
+        }
+        self.with_io(ctx, |conn| conn.as_mut().poll_recv_uninitialized(buf))
+            .map_err(io::Error::from)
+    }
+
+    pub fn poll_write(&mut self, ctx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        #[cfg(feature = "unstable-renegotiate")]
+        ready!(self.poll_renegotiate(ctx)).map_err(io::Error::from)?;
+        self.with_io(ctx, |conn| conn.as_mut().poll_send(buf))
+            .map_err(io::Error::from)
+    }
+
+    pub fn poll_flush(&mut self, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.with_io(ctx, |conn| {
+            conn.as_mut().poll_flush().map(|r| r.map(|_| ()))
+        }))
+        .map_err(io::Error::from)?;
+
+        R::poll_flush(&mut self.stream, ctx)
+    }
+
+    /// Applies any blinding delay, then sends the TLS close_notify and shuts
+    /// down the underlying stream.
+    pub fn poll_shutdown(&mut self, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        debug_assert_waker_contract(ctx, |ctx| {
+            ready!(self.poll_blinding(ctx))?;
+
+            // Only start the shutdown timer once blinding is complete,
+            // so that the timeout never shortens the blinding delay.
+            if self.shutdown_timer.is_none() {
+                if let Some(timeout) = self.shutdown_timeout {
+                    self.shutdown_timer = Some(Box::pin(self.runtime.sleep(timeout)));
+                }
+            }
+
+            match self.poll_shutdown_io(ctx) {
+                Ready(result) => {
+                    self.shutdown_timer = None;
+                    Ready(result)
+                }
+                Pending => {
+                    let timer = match self.shutdown_timer.as_mut() {
+                        Some(timer) => timer,
+                        None => return Pending,
+                    };
+                    ready!(timer.as_mut().poll(ctx));
+                    self.shutdown_timer = None;
+                    // The shutdown was abandoned part way through, so it
+                    // can't be safely resumed.
+                    let next_error = Error::application("Shutdown called again after error".into());
+                    self.shutdown_error = Some(next_error);
+                    Ready(Err(io::Error::from(Error::from(TimeoutError::Shutdown))))
+                }
+            }
+        })
+    }
+
+    /// Sends the TLS close_notify and shuts down the underlying stream.
+    fn poll_shutdown_io(&mut self, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // s2n_shutdown_send must not be called again if it errors
+        if self.shutdown_error.is_none() {
+            let result = ready!(self.with_io(ctx, |conn| {
+                conn.as_mut().poll_shutdown_send().map(|r| r.map(|_| ()))
+            }));
+            if let Err(error) = result {
+                self.shutdown_error = Some(error);
+                // s2n_shutdown_send only writes, so will never trigger blinding again.
+                // So we do not need to poll_blinding again after this error.
+            }
+        };
+
+        let io_result = ready!(R::poll_shutdown(&mut self.stream, ctx));
+
+        if let Some(err) = self.shutdown_error.take() {
+            // poll methods shouldn't be called again after returning Ready, but
+            // nothing actually prevents it so poll_shutdown should handle it.
+            // s2n_shutdown can be polled indefinitely after succeeding, but not after failing.
+            // s2n_tls::error::Error isn't cloneable, so we can't just return the same error
+            // if poll_shutdown is called again. Instead, save a different error.
+            let next_error = Error::application("Shutdown called again after error".into());
+            self.shutdown_error = Some(next_error);
+
+            Ready(Err(io::Error::from(err)))
+        } else {
+            Ready(io_result)
+        }
+    }
+}
+
+impl<S, C, R> AsRef<Connection> for TlsStream<S, C, R>
+where
+    C: AsRef<Connection> + AsMut<Connection> + Unpin,
+    R: Runtime<S>,
+{
+    fn as_ref(&self) -> &Connection {
+        self.conn.as_ref()
+    }
+}
+
+impl<S, C, R> AsMut<Connection> for TlsStream<S, C, R>
+where
+    C: AsRef<Connection> + AsMut<Connection> + Unpin,
+    R: Runtime<S>,
+{
+    fn as_mut(&mut self) -> &mut Connection {
+        self.conn.as_mut()
+    }
+}
+
+impl<S, C, R> fmt::Debug for TlsStream<S, C, R>
+where
+    C: AsRef<Connection> + AsMut<Connection> + Unpin,
+    R: Runtime<S>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TlsStream")
+            .field("connection", self.as_ref())
+            .finish()
+    }
+}
@@ -0,0 +1,54 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Scheduled renegotiation for [`TlsStream`].
+//!
+//! When the connection's [`RenegotiateCallback`](s2n_tls::renegotiate::RenegotiateCallback)
+//! returns [`RenegotiateResponse::Schedule`](s2n_tls::renegotiate::RenegotiateResponse::Schedule),
+//! reads from a [`TlsStream`] perform the new handshake, as described in
+//! [`s2n_tls::renegotiate`]. Writes also complete any renegotiation in progress
+//! before sending, so unlike [`Connection::poll_send`] they do not fail if
+//! the renegotiation request arrived before the write.
+//!
+//! Async callbacks, like the
+//! [`PrivateKeyCallback`](s2n_tls::callbacks::PrivateKeyCallback),
+//! are polled during the new handshake with the stream's task context.
+//!
+//! Reads and writes still must not be polled concurrently from
+//! different tasks while renegotiating.
+
+use crate::{Runtime, TlsStream};
+use s2n_tls::{connection::Connection, error::Error};
+use std::task::{
+    Context, Poll,
+    Poll::{Pending, Ready},
+};
+
+/// Size of the buffer used to receive application data while renegotiating.
+const RECV_BUFFER_LEN: usize = 4096;
+
+impl<S, C, R> TlsStream<S, C, R>
+where
+    C: AsRef<Connection> + AsMut<Connection> + Unpin,
+    R: Runtime<S>,
+{
+    /// Completes a scheduled renegotiation, if one is in progress.
+    ///
+    /// Only `poll_recv` drives a scheduled renegotiation, so any application
+    /// data received during the new handshake is saved for the next read.
+    pub(crate) fn poll_renegotiate(&mut self, ctx: &mut Context) -> Poll<Result<(), Error>> {
+        while self.conn.as_ref().is_renegotiating() {
+            let mut buf = [0; RECV_BUFFER_LEN];
+            match self.with_io(ctx, |conn| conn.as_mut().poll_recv(&mut buf)) {
+                Ready(Ok(0)) => break,
+                Ready(Ok(len)) => self.unread.extend_from_slice(&buf[..len]),
+                Ready(Err(e)) => return Ready(Err(e)),
+                // Once the new handshake completes, poll_recv waits for
+                // application data. That doesn't block sending.
+                Pending if self.conn.as_ref().is_renegotiating() => return Pending,
+                Pending => break,
+            }
+        }
+        Ready(Ok(()))
+    }
+}
@@ -0,0 +1,323 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use futures_io::{AsyncRead, AsyncWrite};
+use s2n_tls::{
+    config::Config,
+    connection::{Builder, Connection},
+    enums::Mode,
+    error::Error,
+};
+use s2n_tls_async_core::{Runtime, Timeouts};
+use std::{
+    fmt, io,
+    mem::MaybeUninit,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+mod time;
+pub use time::{Sleep, Timer};
+
+pub use s2n_tls_async_core::{TimeoutError, DEFAULT_HANDSHAKE_TIMEOUT, DEFAULT_SHUTDOWN_TIMEOUT};
+
+/// Performs the IO of a [`TlsStream`] with the futures-io traits,
+/// and creates timers with the application's [`Timer`].
+#[derive(Clone)]
+struct SharedTimer(Arc<dyn Timer>);
+
+impl<S> Runtime<S> for SharedTimer
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    type Sleep = Sleep;
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        self.0.sleep(duration)
+    }
+
+    fn poll_read(stream: &mut S, ctx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        Pin::new(stream).poll_read(ctx, buf)
+    }
+
+    fn poll_write(stream: &mut S, ctx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(stream).poll_write(ctx, buf)
+    }
+
+    fn poll_flush(stream: &mut S, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(stream).poll_flush(ctx)
+    }
+
+    fn poll_shutdown(stream: &mut S, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(stream).poll_close(ctx)
+    }
+}
+
+#[derive(Clone)]
+pub struct TlsAcceptor<B: Builder = Config>
+where
+    <B as Builder>::Output: Unpin,
+{
+    builder: B,
+    timer: Arc<dyn Timer>,
+    timeouts: Timeouts,
+}
+
+impl<B: Builder> TlsAcceptor<B>
+where
+    <B as Builder>::Output: Unpin,
+{
+    pub fn new<T: Timer>(builder: B, timer: T) -> Self {
+        TlsAcceptor {
+            builder,
+            timer: Arc::new(timer),
+            timeouts: Timeouts::default(),
+        }
+    }
+
+    /// Sets the maximum time [`TlsAcceptor::accept`] waits for the handshake.
+    ///
+    /// By default, the handshake times out after [`DEFAULT_HANDSHAKE_TIMEOUT`].
+    /// Pass `None` to disable the timeout. If the handshake does not complete
+    /// in time, `accept` fails with [`TimeoutError::Handshake`].
+    ///
+    /// The timeout only bounds negotiation. If the handshake fails with an
+    /// error that requires blinding, the full blinding delay is still applied
+    /// before `accept` returns.
+    pub fn set_handshake_timeout(&mut self, timeout: impl Into<Option<Duration>>) -> &mut Self {
+        self.timeouts.handshake = timeout.into();
+        self
+    }
+
+    /// Sets the maximum time a [`TlsStream`] returned by this acceptor waits
+    /// for `poll_close` to complete.
+    ///
+    /// By default, the shutdown times out after [`DEFAULT_SHUTDOWN_TIMEOUT`].
+    /// Pass `None` to disable the timeout. If the shutdown does not complete
+    /// in time, it fails with [`TimeoutError::Shutdown`].
+    ///
+    /// The timeout starts after any blinding delay has elapsed, so it never
+    /// shortens blinding.
+    pub fn set_shutdown_timeout(&mut self, timeout: impl Into<Option<Duration>>) -> &mut Self {
+        self.timeouts.shutdown = timeout.into();
+        self
+    }
+
+    pub async fn accept<S>(&self, stream: S) -> Result<TlsStream<S, B::Output>, Error>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let conn = self.builder.build_connection(Mode::Server)?;
+        TlsStream::open(conn, stream, self.timer.clone(), self.timeouts).await
+    }
+}
+
+#[derive(Clone)]
+pub struct TlsConnector<B: Builder = Config>
+where
+    <B as Builder>::Output: Unpin,
+{
+    builder: B,
+    timer: Arc<dyn Timer>,
+    timeouts: Timeouts,
+}
+
+impl<B: Builder> TlsConnector<B>
+where
+    <B as Builder>::Output: Unpin,
+{
+    pub fn new<T: Timer>(builder: B, timer: T) -> Self {
+        TlsConnector {
+            builder,
+            timer: Arc::new(timer),
+            timeouts: Timeouts::default(),
+        }
+    }
+
+    /// Sets the maximum time [`TlsConnector::connect`] waits for the handshake.
+    ///
+    /// See [`TlsAcceptor::set_handshake_timeout`].
+    pub fn set_handshake_timeout(&mut self, timeout: impl Into<Option<Duration>>) -> &mut Self {
+        self.timeouts.handshake = timeout.into();
+        self
+    }
+
+    /// Sets the maximum time a [`TlsStream`] returned by this connector waits
+    /// for `poll_close` to complete.
+    ///
+    /// See [`TlsAcceptor::set_shutdown_timeout`].
+    pub fn set_shutdown_timeout(&mut self, timeout: impl Into<Option<Duration>>) -> &mut Self {
+        self.timeouts.shutdown = timeout.into();
+        self
+    }
+
+    pub async fn connect<S>(
+        &self,
+        domain: &str,
+        stream: S,
+    ) -> Result<TlsStream<S, B::Output>, Error>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let mut conn = self.builder.build_connection(Mode::Client)?;
+        conn.as_mut().set_server_name(domain)?;
+        TlsStream::open(conn, stream, self.timer.clone(), self.timeouts).await
+    }
+}
+
+pub struct TlsStream<S, C = Connection>
+where
+    C: AsRef<Connection> + AsMut<Connection> + Unpin,
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    inner: s2n_tls_async_core::TlsStream<S, C, SharedTimer>,
+}
+
+impl<S, C> TlsStream<S, C>
+where
+    C: AsRef<Connection> + AsMut<Connection> + Unpin,
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    ///Access a shared reference to the underlaying io stream
+    pub fn get_ref(&self) -> &S {
+        self.inner.get_ref()
+    }
+
+    ///Access the mutable reference to the underlaying io stream
+    pub fn get_mut(&mut self) -> &mut S {
+        self.inner.get_mut()
+    }
+
+    /// Consumes the `TlsStream`, returning its [`Connection`]
+    /// (or [`Builder::Output`](Builder)) and IO stream.
+    ///
+    /// Inverse of [`TlsStream::from_parts`]. All TLS state, including any data
+    /// s2n-tls has already buffered, stays with the returned connection.
+    ///
+    /// Only call this at a quiescent point: after the handshake and while no
+    /// read, write, or close future is in flight. Any in-flight blinding
+    /// timer is dropped, but the connection retains its remaining blinding
+    /// delay and re-applies it on the next poll.
+    pub fn into_parts(self) -> (C, S) {
+        self.inner.into_parts()
+    }
+
+    /// Reassembles a `TlsStream` from a [`Connection`] (or
+    /// [`Builder::Output`](Builder)) and IO stream from [`TlsStream::into_parts`].
+    ///
+    /// Inverse of [`TlsStream::into_parts`]. The connection must already be
+    /// negotiated; this does not perform a handshake.
+    ///
+    /// As for negotiated streams, the shutdown times out after
+    /// [`DEFAULT_SHUTDOWN_TIMEOUT`].
+    pub fn from_parts<T: Timer>(conn: C, stream: S, timer: T) -> Self {
+        let runtime = SharedTimer(Arc::new(timer));
+        TlsStream {
+            inner: s2n_tls_async_core::TlsStream::from_parts(conn, stream, runtime),
+        }
+    }
+
+    async fn open(
+        conn: C,
+        stream: S,
+        timer: Arc<dyn Timer>,
+        timeouts: Timeouts,
+    ) -> Result<Self, Error> {
+        let runtime = SharedTimer(timer);
+        let inner =
+            s2n_tls_async_core::TlsStream::open(conn, stream, runtime, Vec::new(), timeouts)
+                .await?;
+        Ok(TlsStream { inner })
+    }
+
+    /// Polls the blinding timer, if there is any.
+    ///
+    /// s2n has a "blinding" functionality - when a bad behavior from the peer
+    /// is detected, sleeps for 10-30 seconds before answering the client
+    /// and closing the connection. This mitigates some timing side channels
+    /// that could leak information about encrypted data. See the
+    /// `s2n_connection_set_blinding` docs for more details.
+    ///
+    /// For security reasons, to allow for blinding to correctly function,
+    /// before dropping an s2n connection, you should wait until either
+    /// `poll_blinding` or `poll_close` (which calls `poll_blinding`
+    /// internally) returns ready.
+    pub fn poll_blinding(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        self.get_mut().inner.poll_blinding(ctx)
+    }
+
+    pub async fn apply_blinding(&mut self) -> Result<(), Error> {
+        self.inner.apply_blinding().await
+    }
+}
+
+impl<S, C> AsRef<Connection> for TlsStream<S, C>
+where
+    C: AsRef<Connection> + AsMut<Connection> + Unpin,
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    fn as_ref(&self) -> &Connection {
+        self.inner.as_ref()
+    }
+}
+
+impl<S, C> AsMut<Connection> for TlsStream<S, C>
+where
+    C: AsRef<Connection> + AsMut<Connection> + Unpin,
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    fn as_mut(&mut self) -> &mut Connection {
+        self.inner.as_mut()
+    }
+}
+
+impl<S, C> AsyncRead for TlsStream<S, C>
+where
+    C: AsRef<Connection> + AsMut<Connection> + Unpin,
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        // Safe since `MaybeUninit<u8>` has the same layout as `u8`,
+        // and the core TlsStream never deinitializes any bytes.
+        let buf = unsafe { &mut *(buf as *mut [u8] as *mut [MaybeUninit<u8>]) };
+        self.get_mut().inner.poll_read(ctx, buf)
+    }
+}
+
+impl<S, C> AsyncWrite for TlsStream<S, C>
+where
+    C: AsRef<Connection> + AsMut<Connection> + Unpin,
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().inner.poll_write(ctx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().inner.poll_flush(ctx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().inner.poll_shutdown(ctx)
+    }
+}
+
+impl<S, C> fmt::Debug for TlsStream<S, C>
+where
+    C: AsRef<Connection> + AsMut<Connection> + Unpin,
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
@@ -0,0 +1,42 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{future::Future, pin::Pin, time::Duration};
+
+/// A future that completes once a [`Timer`] duration has elapsed.
+pub type Sleep = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Provides timers from the async runtime.
+///
+/// s2n-tls-futures does not depend on any particular runtime, so the
+/// application must supply timers. Timers are used for blinding delays and
+/// for the handshake and shutdown timeouts.
+///
+/// Any `Fn(Duration) -> Sleep` closure can be used as a `Timer`.
+/// For example, with smol:
+/// ```
+/// use s2n_tls_futures::{Sleep, TlsConnector};
+/// use std::time::Duration;
+///
+/// fn smol_timer(duration: Duration) -> Sleep {
+///     Box::pin(async move {
+///         smol::Timer::after(duration).await;
+///     })
+/// }
+///
+/// let config = s2n_tls::config::Config::default();
+/// let connector = TlsConnector::new(config, smol_timer);
+/// ```
+pub trait Timer: 'static + Send + Sync {
+    /// Returns a future that completes after `duration`.
+    fn sleep(&self, duration: Duration) -> Sleep;
+}
+
+impl<F> Timer for F
+where
+    F: Fn(Duration) -> Sleep + 'static + Send + Sync,
+{
+    fn sleep(&self, duration: Duration) -> Sleep {
+        (self)(duration)
+    }
+}
@@ -0,0 +1,121 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use futures::io::{AsyncRead, AsyncWrite};
+use s2n_tls::{
+    callbacks::MonotonicClock,
+    config,
+    connection::Builder,
+    error::Error,
+    security::{DEFAULT_TLS13, TESTING_TLS12},
+};
+use s2n_tls_futures::{Sleep, Timer, TlsAcceptor, TlsConnector, TlsStream};
+use smol::net::{TcpListener, TcpStream};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// NOTE: this certificate and key are used for testing purposes only!
+pub static CERT_PEM: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/../certs/cert.pem"));
+pub static KEY_PEM: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/../certs/key.pem"));
+pub static RSA_CERT_PEM: &[u8] = include_bytes!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../certs/cert_rsa.pem"
+));
+pub static RSA_KEY_PEM: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/../certs/key_rsa.pem"));
+
+pub const MIN_BLINDING_SECS: Duration = Duration::from_secs(10);
+
+pub static TEST_STR: &str = "hello world";
+
+/// A timer backed by the smol runtime.
+pub fn smol_timer(duration: Duration) -> Sleep {
+    Box::pin(async move {
+        smol::Timer::after(duration).await;
+    })
+}
+
+/// A timer that completes immediately, recording every requested duration.
+///
+/// Also acts as a monotonic clock that advances by each requested duration,
+/// so that the s2n-tls C library agrees that the sleep has elapsed.
+#[derive(Clone, Default)]
+pub struct InstantTimer(Arc<Mutex<Vec<Duration>>>);
+
+impl InstantTimer {
+    pub fn requested(&self) -> Vec<Duration> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+impl Timer for InstantTimer {
+    fn sleep(&self, duration: Duration) -> Sleep {
+        self.0.lock().unwrap().push(duration);
+        Box::pin(std::future::ready(()))
+    }
+}
+
+impl MonotonicClock for InstantTimer {
+    fn get_time(&self) -> Duration {
+        self.0.lock().unwrap().iter().sum()
+    }
+}
+
+pub async fn get_streams() -> Result<(TcpStream, TcpStream), std::io::Error> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let client_stream = TcpStream::connect(addr).await?;
+    let (server_stream, _) = listener.accept().await?;
+    Ok((server_stream, client_stream))
+}
+
+pub fn client_config() -> Result<config::Builder, Error> {
+    let mut builder = config::Config::builder();
+    builder.set_security_policy(&DEFAULT_TLS13)?;
+    builder.trust_pem(CERT_PEM)?;
+    Ok(builder)
+}
+
+pub fn server_config() -> Result<config::Builder, Error> {
+    let mut builder = config::Config::builder();
+    builder.set_security_policy(&DEFAULT_TLS13)?;
+    builder.load_pem(CERT_PEM, KEY_PEM)?;
+    Ok(builder)
+}
+
+pub fn client_config_tls12() -> Result<config::Builder, Error> {
+    let mut builder = config::Config::builder();
+    builder.set_security_policy(&TESTING_TLS12)?;
+    builder.trust_pem(RSA_CERT_PEM)?;
+    Ok(builder)
+}
+
+pub fn server_config_tls12() -> Result<config::Builder, Error> {
+    let mut builder = config::Config::builder();
+    builder.set_security_policy(&TESTING_TLS12)?;
+    builder.load_pem(RSA_CERT_PEM, RSA_KEY_PEM)?;
+    Ok(builder)
+}
+
+pub async fn run_negotiate<A: Builder, B: Builder, C, D>(
+    client: &TlsConnector<A>,
+    client_stream: C,
+    server: &TlsAcceptor<B>,
+    server_stream: D,
+) -> Result<(TlsStream<C, A::Output>, TlsStream<D, B::Output>), Error>
+where
+    <A as Builder>::Output: Unpin,
+    <B as Builder>::Output: Unpin,
+    C: AsyncRead + AsyncWrite + Unpin,
+    D: AsyncRead + AsyncWrite + Unpin,
+{
+    let (client, server) = futures::join!(
+        client.connect("localhost", client_stream),
+        server.accept(server_stream)
+    );
+    Ok((client?, server?))
+}
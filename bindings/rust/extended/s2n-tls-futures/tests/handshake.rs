@@ -0,0 +1,140 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use s2n_tls::{
+    config::Config,
+    enums::Version,
+    error::ErrorType,
+    security::{DEFAULT_TLS13, TESTING_TLS12},
+};
+use s2n_tls_futures::{TimeoutError, TlsAcceptor, TlsConnector};
+use std::time::Duration;
+
+pub mod common;
+
+#[test]
+fn handshake_basic() -> Result<(), Box<dyn std::error::Error>> {
+    smol::block_on(async {
+        let (server_stream, client_stream) = common::get_streams().await?;
+
+        let client = TlsConnector::new(common::client_config()?.build()?, common::smol_timer);
+        let server = TlsAcceptor::new(common::server_config()?.build()?, common::smol_timer);
+
+        let (client_result, server_result) =
+            common::run_negotiate(&client, client_stream, &server, server_stream).await?;
+
+        for tls in [client_result, server_result] {
+            // Security policy ensures TLS1.3.
+            assert_eq!(tls.as_ref().actual_protocol_version()?, Version::TLS13);
+            assert!(tls.as_ref().handshake_type()?.contains("NEGOTIATED"));
+        }
+
+        Ok(())
+    })
+}
+
+#[test]
+fn handshake_tls12() -> Result<(), Box<dyn std::error::Error>> {
+    smol::block_on(async {
+        let (server_stream, client_stream) = common::get_streams().await?;
+
+        let client = TlsConnector::new(common::client_config_tls12()?.build()?, common::smol_timer);
+        let server = TlsAcceptor::new(common::server_config_tls12()?.build()?, common::smol_timer);
+
+        let (client_result, server_result) =
+            common::run_negotiate(&client, client_stream, &server, server_stream).await?;
+
+        for tls in [client_result, server_result] {
+            assert_eq!(tls.as_ref().actual_protocol_version()?, Version::TLS12);
+        }
+
+        Ok(())
+    })
+}
+
+#[test]
+fn handshake_error_without_blinding() -> Result<(), Box<dyn std::error::Error>> {
+    smol::block_on(async {
+        // Server config only supports TLS1.2 cipher suites that require RSA auth.
+        // Additionally, no RSA certs are loaded into the config.
+        // Therefore the server will fail to select a cipher suite, and the error that
+        // gets triggered (S2N_ERR_CIPHER_NOT_SUPPORTED) is specifically excluded from blinding.
+        let mut builder = Config::builder();
+        builder.set_security_policy(&TESTING_TLS12)?;
+
+        // The InstantTimer completes immediately, so it would trigger any timeout.
+        let timer = common::InstantTimer::default();
+        let mut client = TlsConnector::new(common::client_config()?.build()?, timer.clone());
+        client
+            .set_handshake_timeout(None)
+            .set_shutdown_timeout(None);
+        let mut server = TlsAcceptor::new(builder.build()?, timer.clone());
+        server
+            .set_handshake_timeout(None)
+            .set_shutdown_timeout(None);
+
+        let (server_stream, client_stream) = common::get_streams().await?;
+        let result = common::run_negotiate(&client, client_stream, &server, server_stream).await;
+        assert!(matches!(result, Err(e) if !e.is_retryable()));
+        assert!(timer.requested().is_empty());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn handshake_error_with_blinding() -> Result<(), Box<dyn std::error::Error>> {
+    smol::block_on(async {
+        // Config::builder() does not include a trust store.
+        // The client will reject the server certificate as untrusted.
+        let timer = common::InstantTimer::default();
+        let mut bad_config = Config::builder();
+        bad_config.set_security_policy(&DEFAULT_TLS13)?;
+        bad_config.set_monotonic_clock(timer.clone())?;
+
+        // The InstantTimer completes immediately, so it would trigger any timeout.
+        let mut client = TlsConnector::new(bad_config.build()?, timer.clone());
+        client
+            .set_handshake_timeout(None)
+            .set_shutdown_timeout(None);
+        let server = TlsAcceptor::new(common::server_config()?.build()?, common::smol_timer);
+
+        let (server_stream, client_stream) = common::get_streams().await?;
+        let result = common::run_negotiate(&client, client_stream, &server, server_stream).await;
+
+        // The blinding delay MUST be requested from the timer.
+        let requested = timer.requested();
+        assert_eq!(requested.len(), 1);
+        assert!(requested[0] > common::MIN_BLINDING_SECS);
+
+        let error = result.unwrap_err();
+        assert_eq!(error.kind(), ErrorType::ProtocolError);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn handshake_timeout() -> Result<(), Box<dyn std::error::Error>> {
+    smol::block_on(async {
+        const TIMEOUT: Duration = Duration::from_secs(5);
+
+        let timer = common::InstantTimer::default();
+        let mut server = TlsAcceptor::new(common::server_config()?.build()?, timer.clone());
+        server
+            .set_handshake_timeout(TIMEOUT)
+            .set_shutdown_timeout(None);
+
+        // The client never sends a ClientHello, so the server handshake stalls.
+        let (server_stream, _client_stream) = common::get_streams().await?;
+        let error = server.accept(server_stream).await.unwrap_err();
+
+        assert_eq!(timer.requested(), vec![TIMEOUT]);
+        assert_eq!(
+            TimeoutError::from_error(&error),
+            Some(TimeoutError::Handshake)
+        );
+
+        Ok(())
+    })
+}
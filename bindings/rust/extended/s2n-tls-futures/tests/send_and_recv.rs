@@ -0,0 +1,52 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use futures::io::{AsyncReadExt, AsyncWriteExt};
+use s2n_tls_futures::{TlsAcceptor, TlsConnector};
+
+pub mod common;
+
+#[test]
+fn send_and_recv_basic() -> Result<(), Box<dyn std::error::Error>> {
+    smol::block_on(async {
+        let (server_stream, client_stream) = common::get_streams().await?;
+
+        let client = TlsConnector::new(common::client_config()?.build()?, common::smol_timer);
+        let server = TlsAcceptor::new(common::server_config()?.build()?, common::smol_timer);
+
+        let (mut client, mut server) =
+            common::run_negotiate(&client, client_stream, &server, server_stream).await?;
+
+        client.write_all(common::TEST_STR.as_bytes()).await?;
+        client.flush().await?;
+
+        let mut received = [0; common::TEST_STR.len()];
+        server.read_exact(&mut received).await?;
+        assert_eq!(&received, common::TEST_STR.as_bytes());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn close_sends_close_notify() -> Result<(), Box<dyn std::error::Error>> {
+    smol::block_on(async {
+        let (server_stream, client_stream) = common::get_streams().await?;
+
+        let client = TlsConnector::new(common::client_config()?.build()?, common::smol_timer);
+        let server = TlsAcceptor::new(common::server_config()?.build()?, common::smol_timer);
+
+        let (mut client, mut server) =
+            common::run_negotiate(&client, client_stream, &server, server_stream).await?;
+
+        server.write_all(common::TEST_STR.as_bytes()).await?;
+        server.close().await?;
+
+        // Zero bytes read indicates EOF
+        let mut received = String::new();
+        client.read_to_string(&mut received).await?;
+        assert_eq!(received, common::TEST_STR);
+
+        Ok(())
+    })
+}
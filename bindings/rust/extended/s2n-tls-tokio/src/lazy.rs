@@ -0,0 +1,238 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{TimeoutError, TlsStream};
+use s2n_tls::{client_hello::ClientHello, connection::Builder, enums::Mode, error::Error};
+use s2n_tls_async_core::Timeouts;
+use std::fmt;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite},
+    time::{timeout, Duration},
+};
+
+const RECORD_HEADER_LEN: usize = 5;
+const HANDSHAKE_HEADER_LEN: usize = 4;
+const CONTENT_TYPE_HANDSHAKE: u8 = 22;
+const HANDSHAKE_TYPE_CLIENT_HELLO: u8 = 1;
+
+/// Upper bound on the size of a ClientHello message that will be buffered.
+///
+/// This prevents a peer from forcing an unbounded allocation by advertising
+/// a very large message.
+const MAX_CLIENT_HELLO_LEN: usize = u16::MAX as usize;
+
+/// Upper bound on the number of records a ClientHello may be fragmented into.
+const MAX_CLIENT_HELLO_RECORDS: usize = 256;
+
+/// Upper bound on the raw bytes buffered while reading the ClientHello: the
+/// largest allowed message, plus a header for each allowed record.
+const MAX_CLIENT_HELLO_RECORDS_LEN: usize =
+    MAX_CLIENT_HELLO_LEN + HANDSHAKE_HEADER_LEN + MAX_CLIENT_HELLO_RECORDS * RECORD_HEADER_LEN;
+
+/// An acceptor that reads the ClientHello before a [`Config`](s2n_tls::config::Config)
+/// is chosen for the connection.
+///
+/// [`LazyTlsAcceptor::accept`] reads the ClientHello from the stream and returns
+/// a [`StartHandshake`]. The application can inspect the ClientHello and then
+/// either continue the handshake with a [`Builder`] of its choice, or reject the
+/// connection.
+///
+/// This is an alternative to selecting a config from a
+/// [`ClientHelloCallback`](s2n_tls::callbacks::ClientHelloCallback) with
+/// [`Connection::set_config`](s2n_tls::connection::Connection::set_config).
+#[derive(Clone, Default)]
+pub struct LazyTlsAcceptor {
+    timeouts: Timeouts,
+}
+
+impl LazyTlsAcceptor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum time to wait for the handshake.
+    ///
+    /// The timeout is applied separately to reading the ClientHello in
+    /// [`LazyTlsAcceptor::accept`] and to the rest of the handshake in
+    /// [`StartHandshake::into_stream`].
+    ///
+    /// See [`TlsAcceptor::set_handshake_timeout`](crate::TlsAcceptor::set_handshake_timeout).
+    pub fn set_handshake_timeout(&mut self, timeout: impl Into<Option<Duration>>) -> &mut Self {
+        self.timeouts.handshake = timeout.into();
+        self
+    }
+
+    /// Sets the maximum time a [`TlsStream`] returned by this acceptor waits
+    /// for `poll_shutdown` to complete.
+    ///
+    /// See [`TlsAcceptor::set_shutdown_timeout`](crate::TlsAcceptor::set_shutdown_timeout).
+    pub fn set_shutdown_timeout(&mut self, timeout: impl Into<Option<Duration>>) -> &mut Self {
+        self.timeouts.shutdown = timeout.into();
+        self
+    }
+
+    /// Reads the ClientHello from `stream`.
+    ///
+    /// Fails if the peer does not begin the connection with a TLS ClientHello.
+    /// SSLv2-formatted ClientHellos are not supported.
+    pub async fn accept<S>(&self, mut stream: S) -> Result<StartHandshake<S>, Error>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let received = match self.timeouts.handshake {
+            Some(duration) => timeout(duration, read_client_hello(&mut stream))
+                .await
+                .map_err(|_| Error::from(TimeoutError::Handshake))??,
+            None => read_client_hello(&mut stream).await?,
+        };
+        let client_hello = ClientHello::parse_client_hello(&received.message)?;
+        Ok(StartHandshake {
+            client_hello,
+            records: received.records,
+            stream,
+            timeouts: self.timeouts,
+        })
+    }
+}
+
+/// A connection whose ClientHello has been read, but whose handshake has
+/// not yet started.
+///
+/// Returned by [`LazyTlsAcceptor::accept`].
+pub struct StartHandshake<S> {
+    client_hello: Box<ClientHello>,
+    /// The raw records read from `stream`, replayed to s2n-tls when the
+    /// handshake starts.
+    records: Vec<u8>,
+    stream: S,
+    timeouts: Timeouts,
+}
+
+// SAFETY: the ClientHello was created by `s2n_client_hello_parse_message`,
+// so it owns its memory and has no references to any other s2n-tls structure.
+// It is only ever accessed through `&self`, which requires exclusive ownership
+// of the `StartHandshake` to move between threads.
+unsafe impl<S: Send> Send for StartHandshake<S> {}
+
+impl<S> StartHandshake<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    /// The ClientHello sent by the peer.
+    pub fn client_hello(&self) -> &ClientHello {
+        &self.client_hello
+    }
+
+    /// Access a shared reference to the underlying io stream
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Continues the handshake using a connection created by `builder`.
+    ///
+    /// The ClientHello already read from the stream is provided to the new
+    /// connection, so the handshake proceeds as if the connection had been
+    /// created before any data was read.
+    pub async fn into_stream<B>(self, builder: B) -> Result<TlsStream<S, B::Output>, Error>
+    where
+        B: Builder,
+        <B as Builder>::Output: Unpin,
+    {
+        let StartHandshake {
+            client_hello,
+            records,
+            stream,
+            timeouts,
+        } = self;
+        drop(client_hello);
+        let conn = builder.build_connection(Mode::Server)?;
+        TlsStream::open(conn, stream, records, timeouts).await
+    }
+
+    /// Rejects the connection, returning the underlying io stream.
+    ///
+    /// No handshake is performed, so no alert is sent to the peer.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S> fmt::Debug for StartHandshake<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StartHandshake")
+            .field("client_hello", &self.client_hello)
+            .finish_non_exhaustive()
+    }
+}
+
+struct ReceivedClientHello {
+    /// The ClientHello handshake message, including the message header.
+    message: Vec<u8>,
+    /// Every byte read from the stream.
+    records: Vec<u8>,
+}
+
+/// Reads handshake records until a complete ClientHello message is available.
+///
+/// Only whole records are read, so no data sent after the ClientHello is consumed.
+async fn read_client_hello<S>(stream: &mut S) -> Result<ReceivedClientHello, Error>
+where
+    S: AsyncRead + Unpin,
+{
+    let mut records = Vec::new();
+    let mut message = Vec::new();
+
+    loop {
+        let mut header = [0; RECORD_HEADER_LEN];
+        stream
+            .read_exact(&mut header)
+            .await
+            .map_err(Error::io_error)?;
+        if header[0] != CONTENT_TYPE_HANDSHAKE {
+            return Err(Error::application(
+                "Expected a TLS handshake record containing a ClientHello".into(),
+            ));
+        }
+
+        let record_len = u16::from_be_bytes([header[3], header[4]]) as usize;
+        // Empty handshake records are forbidden by RFC 8446, Section 5.1, and
+        // would otherwise let a peer grow `records` without sending a message.
+        if record_len == 0 {
+            return Err(Error::application(
+                "Received an empty TLS handshake record".into(),
+            ));
+        }
+        if message.len() + record_len > MAX_CLIENT_HELLO_LEN + HANDSHAKE_HEADER_LEN
+            || records.len() + RECORD_HEADER_LEN + record_len > MAX_CLIENT_HELLO_RECORDS_LEN
+        {
+            return Err(Error::application("ClientHello is too large".into()));
+        }
+
+        let start = message.len();
+        message.resize(start + record_len, 0);
+        stream
+            .read_exact(&mut message[start..])
+            .await
+            .map_err(Error::io_error)?;
+        records.extend_from_slice(&header);
+        records.extend_from_slice(&message[start..]);
+
+        if message.len() < HANDSHAKE_HEADER_LEN {
+            continue;
+        }
+        if message[0] != HANDSHAKE_TYPE_CLIENT_HELLO {
+            return Err(Error::application(
+                "Expected the first handshake message to be a ClientHello".into(),
+            ));
+        }
+        let message_len = HANDSHAKE_HEADER_LEN
+            + u32::from_be_bytes([0, message[1], message[2], message[3]]) as usize;
+        if message_len > MAX_CLIENT_HELLO_LEN + HANDSHAKE_HEADER_LEN {
+            return Err(Error::application("ClientHello is too large".into()));
+        }
+        if message.len() >= message_len {
+            message.truncate(message_len);
+            return Ok(ReceivedClientHello { message, records });
+        }
+    }
+}
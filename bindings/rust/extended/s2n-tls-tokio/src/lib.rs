@@ -1,41 +1,63 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-#[cfg(not(target_os = "windows"))]
-use errno::{set_errno, Errno};
 use s2n_tls::{
     config::Config,
     connection::{Builder, Connection},
-    enums::{Blinding, CallbackResult, Mode},
+    enums::Mode,
     error::Error,
 };
+use s2n_tls_async_core::{Runtime, Timeouts};
 use std::{
-    fmt,
-    future::Future,
-    io,
-    os::raw::{c_int, c_void},
+    fmt, io,
+    mem::MaybeUninit,
     pin::Pin,
-    task::{
-        Context, Poll,
-        Poll::{Pending, Ready},
-    },
+    task::{Context, Poll},
 };
 use tokio::{
     io::{AsyncRead, AsyncWrite, ReadBuf},
     time::{sleep, Duration, Sleep},
 };
 
-// TODO use the version from s2n_quic_core
-mod task;
-use task::waker::debug_assert_contract as debug_assert_waker_contract;
+mod lazy;
+pub use lazy::{LazyTlsAcceptor, StartHandshake};
+#[cfg(unix)]
+pub mod migrate;
 
-macro_rules! ready {
-    ($x:expr) => {
-        match $x {
-            Ready(r) => r,
-            Pending => return Pending,
-        }
-    };
+pub use s2n_tls_async_core::{TimeoutError, DEFAULT_HANDSHAKE_TIMEOUT, DEFAULT_SHUTDOWN_TIMEOUT};
+
+/// Performs the IO of a [`TlsStream`] with tokio.
+#[derive(Clone, Copy, Debug, Default)]
+struct TokioRuntime;
+
+impl<S> Runtime<S> for TokioRuntime
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    type Sleep = Sleep;
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        sleep(duration)
+    }
+
+    fn poll_read(stream: &mut S, ctx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let mut dest = ReadBuf::new(buf);
+        Pin::new(stream)
+            .poll_read(ctx, &mut dest)
+            .map_ok(|_| dest.filled().len())
+    }
+
+    fn poll_write(stream: &mut S, ctx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(stream).poll_write(ctx, buf)
+    }
+
+    fn poll_flush(stream: &mut S, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(stream).poll_flush(ctx)
+    }
+
+    fn poll_shutdown(stream: &mut S, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(stream).poll_shutdown(ctx)
+    }
 }
 
 #[derive(Clone)]
@@ -44,6 +66,7 @@ where
     <B as Builder>::Output: Unpin,
 {
     builder: B,
+    timeouts: Timeouts,
 }
 
 impl<B: Builder> TlsAcceptor<B>
@@ -51,7 +74,38 @@ where
     <B as Builder>::Output: Unpin,
 {
     pub fn new(builder: B) -> Self {
-        TlsAcceptor { builder }
+        TlsAcceptor {
+            builder,
+            timeouts: Timeouts::default(),
+        }
+    }
+
+    /// Sets the maximum time [`TlsAcceptor::accept`] waits for the handshake.
+    ///
+    /// By default, the handshake times out after [`DEFAULT_HANDSHAKE_TIMEOUT`].
+    /// Pass `None` to disable the timeout. If the handshake does not complete
+    /// in time, `accept` fails with [`TimeoutError::Handshake`].
+    ///
+    /// The timeout only bounds negotiation. If the handshake fails with an
+    /// error that requires blinding, the full blinding delay is still applied
+    /// before `accept` returns.
+    pub fn set_handshake_timeout(&mut self, timeout: impl Into<Option<Duration>>) -> &mut Self {
+        self.timeouts.handshake = timeout.into();
+        self
+    }
+
+    /// Sets the maximum time a [`TlsStream`] returned by this acceptor waits
+    /// for `poll_shutdown` to complete.
+    ///
+    /// By default, the shutdown times out after [`DEFAULT_SHUTDOWN_TIMEOUT`].
+    /// Pass `None` to disable the timeout. If the shutdown does not complete
+    /// in time, it fails with [`TimeoutError::Shutdown`].
+    ///
+    /// The timeout starts after any blinding delay has elapsed, so it never
+    /// shortens blinding.
+    pub fn set_shutdown_timeout(&mut self, timeout: impl Into<Option<Duration>>) -> &mut Self {
+        self.timeouts.shutdown = timeout.into();
+        self
     }
 
     pub async fn accept<S>(&self, stream: S) -> Result<TlsStream<S, B::Output>, Error>
@@ -59,7 +113,7 @@ where
         S: AsyncRead + AsyncWrite + Unpin,
     {
         let conn = self.builder.build_connection(Mode::Server)?;
-        TlsStream::open(conn, stream).await
+        TlsStream::open(conn, stream, Vec::new(), self.timeouts).await
     }
 }
 
@@ -69,6 +123,7 @@ where
     <B as Builder>::Output: Unpin,
 {
     builder: B,
+    timeouts: Timeouts,
 }
 
 impl<B: Builder> TlsConnector<B>
@@ -76,7 +131,27 @@ where
     <B as Builder>::Output: Unpin,
 {
     pub fn new(builder: B) -> Self {
-        TlsConnector { builder }
+        TlsConnector {
+            builder,
+            timeouts: Timeouts::default(),
+        }
+    }
+
+    /// Sets the maximum time [`TlsConnector::connect`] waits for the handshake.
+    ///
+    /// See [`TlsAcceptor::set_handshake_timeout`].
+    pub fn set_handshake_timeout(&mut self, timeout: impl Into<Option<Duration>>) -> &mut Self {
+        self.timeouts.handshake = timeout.into();
+        self
+    }
+
+    /// Sets the maximum time a [`TlsStream`] returned by this connector waits
+    /// for `poll_shutdown` to complete.
+    ///
+    /// See [`TlsAcceptor::set_shutdown_timeout`].
+    pub fn set_shutdown_timeout(&mut self, timeout: impl Into<Option<Duration>>) -> &mut Self {
+        self.timeouts.shutdown = timeout.into();
+        self
     }
 
     pub async fn connect<S>(
@@ -89,60 +164,7 @@ where
     {
         let mut conn = self.builder.build_connection(Mode::Client)?;
         conn.as_mut().set_server_name(domain)?;
-        TlsStream::open(conn, stream).await
-    }
-}
-
-struct TlsHandshake<'a, S, C>
-where
-    C: AsRef<Connection> + AsMut<Connection> + Unpin,
-    S: AsyncRead + AsyncWrite + Unpin,
-{
-    tls: &'a mut TlsStream<S, C>,
-    error: Option<Error>,
-}
-
-impl<S, C> Future for TlsHandshake<'_, S, C>
-where
-    C: AsRef<Connection> + AsMut<Connection> + Unpin,
-    S: AsyncRead + AsyncWrite + Unpin,
-{
-    type Output = Result<(), Error>;
-
-    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
-        debug_assert_waker_contract(ctx, |ctx| {
-            // Retrieve a result, either from the stored error
-            // or by polling Connection::poll_negotiate().
-            // Connection::poll_negotiate() only completes once,
-            // regardless of how often this method is polled.
-            let result = match self.error.take() {
-                Some(err) => Err(err),
-                None => {
-                    let handshake_poll = self.tls.with_io(ctx, |context| {
-                        let conn = context.get_mut().as_mut();
-                        conn.poll_negotiate().map(|r| r.map(|_| ()))
-                    });
-                    ready!(handshake_poll)
-                }
-            };
-            // If the result isn't a fatal error, return it immediately.
-            // Otherwise, poll Connection::poll_shutdown().
-            //
-            // Shutdown is only best-effort.
-            // When Connection::poll_shutdown() completes, even with an error,
-            // we return the original Connection::poll_negotiate() error.
-            match result {
-                Ok(r) => Ok(r).into(),
-                Err(e) if e.is_retryable() => Err(e).into(),
-                Err(e) => match Pin::new(&mut self.tls).poll_shutdown(ctx) {
-                    Pending => {
-                        self.error = Some(e);
-                        Pending
-                    }
-                    Ready(_) => Err(e).into(),
-                },
-            }
-        })
+        TlsStream::open(conn, stream, Vec::new(), self.timeouts).await
     }
 }
 
@@ -151,10 +173,7 @@ where
     C: AsRef<Connection> + AsMut<Connection> + Unpin,
     S: AsyncRead + AsyncWrite + Unpin,
 {
-    conn: C,
-    stream: S,
-    blinding: Option<Pin<Box<Sleep>>>,
-    shutdown_error: Option<Error>,
+    inner: s2n_tls_async_core::TlsStream<S, C, TokioRuntime>,
 }
 
 impl<S, C> TlsStream<S, C>
@@ -164,12 +183,12 @@ where
 {
     ///Access a shared reference to the underlaying io stream
     pub fn get_ref(&self) -> &S {
-        &self.stream
+        self.inner.get_ref()
     }
 
     ///Access the mutable reference to the underlaying io stream
     pub fn get_mut(&mut self) -> &mut S {
-        &mut self.stream
+        self.inner.get_mut()
     }
 
     /// Consumes the `TlsStream`, returning its [`Connection`]
@@ -182,8 +201,11 @@ where
     /// read, write, or shutdown future is in flight. Any in-flight blinding
     /// timer is dropped, but the connection retains its remaining blinding
     /// delay and re-applies it on the next poll.
+    ///
+    /// Application data restored by [`TlsStream::from_serialized`] that
+    /// has not been read yet is not returned.
     pub fn into_parts(self) -> (C, S) {
-        (self.conn, self.stream)
+        self.inner.into_parts()
     }
 
     /// Reassembles a `TlsStream` from a [`Connection`] (or
@@ -191,125 +213,20 @@ where
     ///
     /// Inverse of [`TlsStream::into_parts`]. The connection must already be
     /// negotiated; this does not perform a handshake.
+    ///
+    /// As for negotiated streams, the shutdown times out after
+    /// [`DEFAULT_SHUTDOWN_TIMEOUT`].
     pub fn from_parts(conn: C, stream: S) -> Self {
         TlsStream {
-            conn,
-            stream,
-            blinding: None,
-            shutdown_error: None,
-        }
-    }
-
-    async fn open(conn: C, stream: S) -> Result<Self, Error> {
-        let mut tls = TlsStream {
-            conn,
-            stream,
-            blinding: None,
-            shutdown_error: None,
-        };
-        TlsHandshake {
-            tls: &mut tls,
-            error: None,
-        }
-        .await?;
-        Ok(tls)
-    }
-
-    fn with_io<F, R>(&mut self, ctx: &mut Context, action: F) -> Poll<Result<R, Error>>
-    where
-        F: FnOnce(Pin<&mut Self>) -> Poll<Result<R, Error>>,
-    {
-        // Setting contexts on a connection is considered unsafe
-        // because the raw pointers provide no lifetime or memory guarantees.
-        // We protect against this by pinning the stream during the action
-        // and clearing the context afterwards.
-        unsafe {
-            let context = self as *mut Self as *mut c_void;
-
-            self.as_mut().set_receive_callback(Some(Self::recv_io_cb))?;
-            self.as_mut().set_send_callback(Some(Self::send_io_cb))?;
-            self.as_mut().set_receive_context(context)?;
-            self.as_mut().set_send_context(context)?;
-            self.as_mut().set_waker(Some(ctx.waker()))?;
-            self.as_mut().set_blinding(Blinding::SelfService)?;
-
-            let result = action(Pin::new(self));
-
-            self.as_mut().set_receive_callback(None)?;
-            self.as_mut().set_send_callback(None)?;
-            self.as_mut().set_receive_context(std::ptr::null_mut())?;
-            self.as_mut().set_send_context(std::ptr::null_mut())?;
-            self.as_mut().set_waker(None)?;
-            result
+            inner: s2n_tls_async_core::TlsStream::from_parts(conn, stream, TokioRuntime),
         }
     }
 
-    fn poll_io<F>(ctx: *mut c_void, action: F) -> c_int
-    where
-        F: FnOnce(Pin<&mut S>, &mut Context) -> Poll<Result<usize, std::io::Error>>,
-    {
-        debug_assert_ne!(ctx, std::ptr::null_mut());
-        let tls = unsafe { &mut *(ctx as *mut Self) };
-
-        let mut async_context = Context::from_waker(tls.conn.as_ref().waker().unwrap());
-        let stream = Pin::new(&mut tls.stream);
-
-        let res = debug_assert_waker_contract(&mut async_context, |async_context| {
-            action(stream, async_context)
-        });
-
-        match res {
-            Poll::Ready(Ok(len)) => len as c_int,
-            Poll::Pending => {
-                Self::set_io_would_block();
-                CallbackResult::Failure.into()
-            }
-            _ => CallbackResult::Failure.into(),
-        }
-    }
-
-    /// Signal a "would block" to s2n's C IO layer by setting the CRT `errno` to
-    /// EWOULDBLOCK. `s2n_io.c` reads `errno` to distinguish a retriable blocked
-    /// read/write from a fatal IO error.
-    fn set_io_would_block() {
-        #[cfg(not(target_os = "windows"))]
-        {
-            // The `errno` crate writes the CRT errno, which is what s2n reads.
-            set_errno(Errno(libc::EWOULDBLOCK));
-        }
-
-        // On Windows the `errno` crate writes the Win32 last-error, not the CRT
-        // `errno` that s2n reads, so set the CRT errno directly. s2n and this
-        // code share one statically linked CRT, so `_set_errno` and `errno` hit
-        // the same thread-local variable.
-        #[cfg(target_os = "windows")]
-        {
-            extern "C" {
-                fn _set_errno(value: core::ffi::c_int) -> core::ffi::c_int;
-            }
-            // SAFETY: `_set_errno` only writes the thread-local CRT errno.
-            unsafe {
-                let _ = _set_errno(libc::EWOULDBLOCK);
-            }
-        }
-    }
-
-    unsafe extern "C" fn recv_io_cb(ctx: *mut c_void, buf: *mut u8, len: u32) -> c_int {
-        Self::poll_io(ctx, |stream, async_context| {
-            let len: usize = len.try_into().unwrap();
-            let mut dest = ReadBuf::new(std::slice::from_raw_parts_mut(buf, len));
-            stream
-                .poll_read(async_context, &mut dest)
-                .map_ok(|_| dest.filled().len())
-        })
-    }
-
-    unsafe extern "C" fn send_io_cb(ctx: *mut c_void, buf: *const u8, len: u32) -> c_int {
-        Self::poll_io(ctx, |stream, async_context| {
-            let len: usize = len.try_into().unwrap();
-            let src = std::slice::from_raw_parts(buf, len);
-            stream.poll_write(async_context, src)
-        })
+    async fn open(conn: C, stream: S, replay: Vec<u8>, timeouts: Timeouts) -> Result<Self, Error> {
+        let inner =
+            s2n_tls_async_core::TlsStream::open(conn, stream, TokioRuntime, replay, timeouts)
+                .await?;
+        Ok(TlsStream { inner })
     }
 
     /// Polls the blinding timer, if there is any.
@@ -325,30 +242,11 @@ where
     /// `poll_blinding` or `poll_shutdown` (which calls `poll_blinding`
     /// internally) returns ready.
     pub fn poll_blinding(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Result<(), Error>> {
-        debug_assert_waker_contract(ctx, |ctx| {
-            let tls = self.get_mut();
-
-            if tls.blinding.is_none() {
-                let delay = tls.as_ref().remaining_blinding_delay()?;
-                if !delay.is_zero() {
-                    // Sleep operates at the milisecond resolution, so add an extra
-                    // millisecond to account for any stray nanoseconds.
-                    let safety = Duration::from_millis(1);
-                    tls.blinding = Some(Box::pin(sleep(delay.saturating_add(safety))));
-                }
-            };
-
-            if let Some(timer) = tls.blinding.as_mut() {
-                ready!(timer.as_mut().poll(ctx));
-                tls.blinding = None;
-            }
-
-            Poll::Ready(Ok(()))
-        })
+        self.get_mut().inner.poll_blinding(ctx)
     }
 
     pub async fn apply_blinding(&mut self) -> Result<(), Error> {
-        ApplyBlinding { stream: self }.await
+        self.inner.apply_blinding().await
     }
 }
 
@@ -358,7 +256,7 @@ where
     S: AsyncRead + AsyncWrite + Unpin,
 {
     fn as_ref(&self) -> &Connection {
-        self.conn.as_ref()
+        self.inner.as_ref()
     }
 }
 
@@ -368,7 +266,7 @@ where
     S: AsyncRead + AsyncWrite + Unpin,
 {
     fn as_mut(&mut self) -> &mut Connection {
-        self.conn.as_mut()
+        self.inner.as_mut()
     }
 }
 
@@ -382,25 +280,20 @@ where
         ctx: &mut Context<'_>,
         buf: &mut ReadBuf<'_>,
     ) -> Poll<io::Result<()>> {
-        let tls = self.get_mut();
-        tls.with_io(ctx, |mut context| {
-            context
-                .conn
-                .as_mut()
-                // Safe since poll_recv_uninitialized does not
-                // deinitialize any bytes.
-                .poll_recv_uninitialized(unsafe { buf.unfilled_mut() })
-                .map_ok(|size| {
-                    unsafe {
-                        // Safe since poll_recv_uninitialized guaranteed
-                        // us that the first `size` bytes have been
-                        // initialized.
-                        buf.assume_init(size);
-                    }
-                    buf.advance(size);
-                })
-        })
-        .map_err(io::Error::from)
+        // Safe since the core TlsStream never deinitializes any bytes.
+        let unfilled: &mut [MaybeUninit<u8>] = unsafe { buf.unfilled_mut() };
+        self.get_mut()
+            .inner
+            .poll_read(ctx, unfilled)
+            .map_ok(|size| {
+                unsafe {
+                    // Safe since the core TlsStream guaranteed
+                    // us that the first `size` bytes have been
+                    // initialized.
+                    buf.assume_init(size);
+                }
+                buf.advance(size);
+            })
     }
 }
 
@@ -414,58 +307,15 @@ where
         ctx: &mut Context<'_>,
         buf: &[u8],
     ) -> Poll<io::Result<usize>> {
-        let tls = self.get_mut();
-        tls.with_io(ctx, |mut context| context.conn.as_mut().poll_send(buf))
-            .map_err(io::Error::from)
+        self.get_mut().inner.poll_write(ctx, buf)
     }
 
     fn poll_flush(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
-        let tls = self.get_mut();
-
-        ready!(tls.with_io(ctx, |mut context| {
-            context.conn.as_mut().poll_flush().map(|r| r.map(|_| ()))
-        }))
-        .map_err(io::Error::from)?;
-
-        Pin::new(&mut tls.stream).poll_flush(ctx)
+        self.get_mut().inner.poll_flush(ctx)
     }
 
-    fn poll_shutdown(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
-        debug_assert_waker_contract(ctx, |ctx| {
-            ready!(self.as_mut().poll_blinding(ctx))?;
-
-            // s2n_shutdown_send must not be called again if it errors
-            if self.shutdown_error.is_none() {
-                let result = ready!(self.as_mut().with_io(ctx, |mut context| {
-                    context
-                        .conn
-                        .as_mut()
-                        .poll_shutdown_send()
-                        .map(|r| r.map(|_| ()))
-                }));
-                if let Err(error) = result {
-                    self.shutdown_error = Some(error);
-                    // s2n_shutdown_send only writes, so will never trigger blinding again.
-                    // So we do not need to poll_blinding again after this error.
-                }
-            };
-
-            let tcp_result = ready!(Pin::new(&mut self.as_mut().stream).poll_shutdown(ctx));
-
-            if let Some(err) = self.shutdown_error.take() {
-                // poll methods shouldn't be called again after returning Ready, but
-                // nothing actually prevents it so poll_shutdown should handle it.
-                // s2n_shutdown can be polled indefinitely after succeeding, but not after failing.
-                // s2n_tls::error::Error isn't cloneable, so we can't just return the same error
-                // if poll_shutdown is called again. Instead, save a different error.
-                let next_error = Error::application("Shutdown called again after error".into());
-                self.shutdown_error = Some(next_error);
-
-                Ready(Err(io::Error::from(err)))
-            } else {
-                Ready(tcp_result)
-            }
-        })
+    fn poll_shutdown(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().inner.poll_shutdown(ctx)
     }
 }
 
@@ -475,28 +325,6 @@ where
     S: AsyncRead + AsyncWrite + Unpin,
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.debug_struct("TlsStream")
-            .field("connection", self.as_ref())
-            .finish()
-    }
-}
-
-struct ApplyBlinding<'a, S, C>
-where
-    C: AsRef<Connection> + AsMut<Connection> + Unpin,
-    S: AsyncRead + AsyncWrite + Unpin,
-{
-    stream: &'a mut TlsStream<S, C>,
-}
-
-impl<S, C> Future for ApplyBlinding<'_, S, C>
-where
-    C: AsRef<Connection> + AsMut<Connection> + Unpin,
-    S: AsyncRead + AsyncWrite + Unpin,
-{
-    type Output = Result<(), Error>;
-
-    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
-        Pin::new(&mut *self.as_mut().stream).poll_blinding(ctx)
+        self.inner.fmt(f)
     }
 }
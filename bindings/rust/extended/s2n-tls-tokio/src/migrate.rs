@@ -0,0 +1,392 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Migrate established TLS streams between processes.
+//!
+//! [`TlsStream::into_serialized`] turns a negotiated stream into its socket
+//! and an opaque serialized state, and [`TlsStream::from_serialized`] restores
+//! the stream in another process. [`send_serialized`] and [`recv_serialized`]
+//! pass both over a Unix domain socket, transferring the socket with `SCM_RIGHTS`.
+//!
+//! For example, an old process hands a stream off:
+//! ```no_run
+//! # async fn example(
+//! #     tls: s2n_tls_tokio::TlsStream<tokio::net::TcpStream>,
+//! #     handoff: tokio::net::UnixStream,
+//! # ) -> Result<(), Box<dyn std::error::Error>> {
+//! use std::os::fd::AsFd;
+//!
+//! let (fd, state) = tls.into_serialized().await?;
+//! s2n_tls_tokio::migrate::send_serialized(&handoff, fd.as_fd(), &state).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! and the new process picks it up:
+//! ```no_run
+//! # async fn example(
+//! #     config: s2n_tls::config::Config,
+//! #     handoff: tokio::net::UnixStream,
+//! # ) -> Result<(), Box<dyn std::error::Error>> {
+//! use s2n_tls_tokio::TlsStream;
+//!
+//! let (fd, state) = s2n_tls_tokio::migrate::recv_serialized(&handoff).await?;
+//! let tls = TlsStream::from_serialized(fd, &state, config)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Connection serialization must be enabled on both configs with
+//! [`set_serialization_version`](s2n_tls::config::Builder::set_serialization_version).
+//! See the limitations documented on
+//! [`Connection::serialize`](s2n_tls::connection::Connection::serialize).
+
+use crate::TlsStream;
+use s2n_tls::{config::Config, connection::Connection, enums::Mode, error::Error};
+use std::{
+    future::poll_fn,
+    io, mem,
+    os::{
+        fd::{AsRawFd, BorrowedFd, FromRawFd, OwnedFd, RawFd},
+        raw::{c_int, c_void},
+    },
+    ptr,
+};
+use tokio::{
+    io::Interest,
+    net::{TcpStream, UnixStream},
+};
+
+/// Version of the format produced by [`TlsStream::into_serialized`].
+const FORMAT_VERSION: u8 = 1;
+const MODE_SERVER: u8 = 0;
+const MODE_CLIENT: u8 = 1;
+/// Version, mode, and the length of the unread application data.
+const HEADER_LEN: usize = 1 + 1 + 4;
+
+/// Length prefix for the state sent by [`send_serialized`].
+const LENGTH_PREFIX_LEN: usize = 4;
+/// Upper bound on the size of a state accepted by [`recv_serialized`].
+///
+/// The serialized state holds at most one record of unread application data,
+/// so this is far larger than any valid state.
+const MAX_SERIALIZED_LEN: usize = 1 << 20;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const RECV_FLAGS: c_int = libc::MSG_CMSG_CLOEXEC;
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+const RECV_FLAGS: c_int = 0;
+
+impl<C> TlsStream<TcpStream, C>
+where
+    C: AsRef<Connection> + AsMut<Connection> + Unpin,
+{
+    /// Consumes a negotiated `TlsStream`, returning its socket and serialized state.
+    ///
+    /// Any data s2n-tls has buffered for sending is flushed first. Application
+    /// data that s2n-tls has already decrypted but that has not been read is
+    /// included in the serialized state, and is returned by the first reads
+    /// from the stream restored by [`TlsStream::from_serialized`].
+    ///
+    /// Only call this at a quiescent point: after the handshake and while no
+    /// read, write, or shutdown future is in flight. Fails if a record has
+    /// only been partially received.
+    ///
+    /// The socket is deregistered from the current runtime.
+    /// The returned fd is left in non-blocking mode.
+    pub async fn into_serialized(mut self) -> Result<(OwnedFd, Vec<u8>), Error> {
+        if self.inner.has_replay() {
+            return Err(Error::application(
+                "Stream has received data that s2n-tls has not processed".into(),
+            ));
+        }
+
+        poll_fn(|ctx| {
+            self.inner.with_io(ctx, |conn| {
+                conn.as_mut().poll_flush().map(|r| r.map(|_| ()))
+            })
+        })
+        .await?;
+
+        let mut unread = self.inner.take_unread();
+        loop {
+            let len = self.inner.as_ref().peek_len();
+            if len == 0 {
+                break;
+            }
+            let start = unread.len();
+            unread.resize(start + len, 0);
+            let read = poll_fn(|ctx| {
+                self.inner
+                    .with_io(ctx, |conn| conn.as_mut().poll_recv(&mut unread[start..]))
+            })
+            .await?;
+            unread.truncate(start + read);
+            if read == 0 {
+                break;
+            }
+        }
+
+        let conn = self.inner.as_ref();
+        let mut state = vec![0; conn.serialization_length()?];
+        conn.serialize(&mut state)?;
+
+        let unread_len = u32::try_from(unread.len())
+            .map_err(|_| Error::application("Too much unread application data".into()))?;
+        let mode = match conn.mode() {
+            Mode::Server => MODE_SERVER,
+            Mode::Client => MODE_CLIENT,
+        };
+        let mut serialized = Vec::with_capacity(HEADER_LEN + unread.len() + state.len());
+        serialized.push(FORMAT_VERSION);
+        serialized.push(mode);
+        serialized.extend_from_slice(&unread_len.to_be_bytes());
+        serialized.extend_from_slice(&unread);
+        serialized.extend_from_slice(&state);
+
+        let (_conn, stream) = self.into_parts();
+        let stream = stream.into_std().map_err(Error::io_error)?;
+        Ok((OwnedFd::from(stream), serialized))
+    }
+}
+
+impl TlsStream<TcpStream, Connection> {
+    /// Restores a `TlsStream` from the socket and state returned by
+    /// [`TlsStream::into_serialized`].
+    ///
+    /// `config` must enable connection serialization with the same version
+    /// as the config used by the original connection. No handshake is
+    /// performed: the stream continues the original session with the peer.
+    ///
+    /// Must be called from within a tokio runtime, so that the socket
+    /// can be registered with it.
+    pub fn from_serialized(fd: OwnedFd, serialized: &[u8], config: Config) -> Result<Self, Error> {
+        let invalid = || Error::application("Invalid serialized TlsStream".into());
+
+        let (header, rest) = serialized
+            .split_at_checked(HEADER_LEN)
+            .ok_or_else(invalid)?;
+        if header[0] != FORMAT_VERSION {
+            return Err(invalid());
+        }
+        let mode = match header[1] {
+            MODE_SERVER => Mode::Server,
+            MODE_CLIENT => Mode::Client,
+            _ => return Err(invalid()),
+        };
+        let unread_len = u32::from_be_bytes([header[2], header[3], header[4], header[5]]);
+        let (unread, state) = rest
+            .split_at_checked(unread_len as usize)
+            .ok_or_else(invalid)?;
+
+        let mut conn = Connection::new(mode);
+        conn.set_config(config)?;
+        conn.deserialize(state)?;
+
+        let stream = std::net::TcpStream::from(fd);
+        stream.set_nonblocking(true).map_err(Error::io_error)?;
+        let stream = TcpStream::from_std(stream).map_err(Error::io_error)?;
+
+        let mut tls = TlsStream::from_parts(conn, stream);
+        tls.inner.set_unread(unread.to_vec());
+        Ok(tls)
+    }
+}
+
+/// Sends a socket and serialized state from [`TlsStream::into_serialized`]
+/// over a Unix domain socket.
+///
+/// The socket is duplicated into the receiving process, so `fd` can be
+/// closed once this completes.
+pub async fn send_serialized(
+    socket: &UnixStream,
+    fd: BorrowedFd<'_>,
+    serialized: &[u8],
+) -> io::Result<()> {
+    if serialized.len() > MAX_SERIALIZED_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "serialized state is too large",
+        ));
+    }
+    let mut message = Vec::with_capacity(LENGTH_PREFIX_LEN + serialized.len());
+    message.extend_from_slice(&(serialized.len() as u32).to_be_bytes());
+    message.extend_from_slice(serialized);
+
+    // The fd is attached to the first bytes of the message,
+    // so it is only sent once.
+    let mut sent = socket
+        .async_io(Interest::WRITABLE, || {
+            send_with_fd(socket.as_raw_fd(), fd.as_raw_fd(), &message)
+        })
+        .await?;
+    while sent < message.len() {
+        sent += socket
+            .async_io(Interest::WRITABLE, || {
+                // SAFETY: the socket and the message are both valid for the call.
+                let result = unsafe {
+                    libc::send(
+                        socket.as_raw_fd(),
+                        message[sent..].as_ptr() as *const c_void,
+                        message.len() - sent,
+                        0,
+                    )
+                };
+                cvt(result)
+            })
+            .await?;
+    }
+    Ok(())
+}
+
+/// Receives a socket and serialized state sent by [`send_serialized`].
+///
+/// The result can be passed to [`TlsStream::from_serialized`].
+pub async fn recv_serialized(socket: &UnixStream) -> io::Result<(OwnedFd, Vec<u8>)> {
+    let mut prefix = [0; LENGTH_PREFIX_LEN];
+    let (received, fd) = socket
+        .async_io(Interest::READABLE, || {
+            recv_with_fd(socket.as_raw_fd(), &mut prefix)
+        })
+        .await?;
+    let fd = fd.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "no file descriptor was received",
+        )
+    })?;
+    recv_exact(socket, &mut prefix[received..]).await?;
+
+    let len = u32::from_be_bytes(prefix) as usize;
+    if len > MAX_SERIALIZED_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "serialized state is too large",
+        ));
+    }
+    let mut serialized = vec![0; len];
+    recv_exact(socket, &mut serialized).await?;
+    Ok((fd, serialized))
+}
+
+/// Fills `buf` from `socket`.
+async fn recv_exact(socket: &UnixStream, buf: &mut [u8]) -> io::Result<()> {
+    let mut received = 0;
+    while received < buf.len() {
+        let len = socket
+            .async_io(Interest::READABLE, || {
+                // SAFETY: the socket and the buffer are both valid for the call.
+                let result = unsafe {
+                    libc::recv(
+                        socket.as_raw_fd(),
+                        buf[received..].as_mut_ptr() as *mut c_void,
+                        buf.len() - received,
+                        0,
+                    )
+                };
+                cvt(result)
+            })
+            .await?;
+        if len == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        received += len;
+    }
+    Ok(())
+}
+
+fn cvt(result: isize) -> io::Result<usize> {
+    if result < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(result as usize)
+    }
+}
+
+/// Control message buffer, aligned for `cmsghdr`.
+#[repr(C)]
+#[derive(Default)]
+struct ControlBuffer([u64; 4]);
+
+fn control_len() -> usize {
+    // SAFETY: CMSG_SPACE only performs arithmetic.
+    let len = unsafe { libc::CMSG_SPACE(mem::size_of::<RawFd>() as u32) } as usize;
+    debug_assert!(len <= mem::size_of::<ControlBuffer>());
+    len
+}
+
+fn send_with_fd(socket: RawFd, fd: RawFd, buf: &[u8]) -> io::Result<usize> {
+    let mut control = ControlBuffer::default();
+    let mut iov = libc::iovec {
+        iov_base: buf.as_ptr() as *mut c_void,
+        iov_len: buf.len(),
+    };
+
+    // SAFETY: msghdr is plain data, so zeroed is a valid empty value.
+    // The control buffer is large enough and suitably aligned for a single
+    // cmsghdr carrying one fd, and every pointer outlives the sendmsg call.
+    unsafe {
+        let mut msg: libc::msghdr = mem::zeroed();
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = control.0.as_mut_ptr() as *mut c_void;
+        msg.msg_controllen = control_len() as _;
+
+        let cmsg = libc::CMSG_FIRSTHDR(&msg);
+        (*cmsg).cmsg_level = libc::SOL_SOCKET;
+        (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+        (*cmsg).cmsg_len = libc::CMSG_LEN(mem::size_of::<RawFd>() as u32) as _;
+        ptr::write_unaligned(libc::CMSG_DATA(cmsg) as *mut RawFd, fd);
+
+        cvt(libc::sendmsg(socket, &msg, 0))
+    }
+}
+
+fn recv_with_fd(socket: RawFd, buf: &mut [u8]) -> io::Result<(usize, Option<OwnedFd>)> {
+    let mut control = ControlBuffer::default();
+    let mut iov = libc::iovec {
+        iov_base: buf.as_mut_ptr() as *mut c_void,
+        iov_len: buf.len(),
+    };
+
+    // SAFETY: see `send_with_fd`. Every fd received is immediately
+    // wrapped in an OwnedFd, so none are leaked.
+    unsafe {
+        let mut msg: libc::msghdr = mem::zeroed();
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = control.0.as_mut_ptr() as *mut c_void;
+        msg.msg_controllen = control_len() as _;
+
+        let len = cvt(libc::recvmsg(socket, &mut msg, RECV_FLAGS))?;
+
+        let mut fd = None;
+        let mut cmsg = libc::CMSG_FIRSTHDR(&msg);
+        while !cmsg.is_null() {
+            if (*cmsg).cmsg_level == libc::SOL_SOCKET && (*cmsg).cmsg_type == libc::SCM_RIGHTS {
+                let data = libc::CMSG_DATA(cmsg);
+                let data_len = (*cmsg).cmsg_len as usize - (data as usize - cmsg as usize);
+                for i in 0..data_len / mem::size_of::<RawFd>() {
+                    let raw = ptr::read_unaligned((data as *const RawFd).add(i));
+                    let owned = OwnedFd::from_raw_fd(raw);
+                    // Only one fd is expected. Any others are dropped, closing them.
+                    if fd.is_none() {
+                        fd = Some(owned);
+                    }
+                }
+            }
+            cmsg = libc::CMSG_NXTHDR(&msg, cmsg);
+        }
+
+        if msg.msg_flags & libc::MSG_CTRUNC != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "control message was truncated",
+            ));
+        }
+        if len == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok((len, fd))
+    }
+}
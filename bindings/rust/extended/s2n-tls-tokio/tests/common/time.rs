@@ -1,7 +1,8 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-use s2n_tls::callbacks::MonotonicClock;
+use s2n_tls::{callbacks::MonotonicClock, connection::Builder};
+use s2n_tls_tokio::{TlsAcceptor, TlsConnector};
 use std::time::Duration;
 use tokio::time::Instant;
 
@@ -20,3 +21,23 @@ impl MonotonicClock for TokioTime {
         self.0.elapsed()
     }
 }
+
+/// Creates a connector and acceptor for tests which pause the tokio clock.
+///
+/// The paused clock auto-advances whenever the runtime waits on the TCP
+/// streams, which would trigger the default handshake timeout, so the
+/// handshake timeout is disabled.
+pub fn paused_clock_endpoints<A: Builder, B: Builder>(
+    client_builder: A,
+    server_builder: B,
+) -> (TlsConnector<A>, TlsAcceptor<B>)
+where
+    <A as Builder>::Output: Unpin,
+    <B as Builder>::Output: Unpin,
+{
+    let mut client = TlsConnector::new(client_builder);
+    client.set_handshake_timeout(None);
+    let mut server = TlsAcceptor::new(server_builder);
+    server.set_handshake_timeout(None);
+    (client, server)
+}
@@ -10,7 +10,7 @@ use s2n_tls::{
     pool::ConfigPoolBuilder,
     security::{DEFAULT_TLS13, TESTING_TLS12},
 };
-use s2n_tls_tokio::{TlsAcceptor, TlsConnector};
+use s2n_tls_tokio::{TimeoutError, TlsAcceptor, TlsConnector, DEFAULT_HANDSHAKE_TIMEOUT};
 use std::{collections::VecDeque, time::Duration};
 use tokio::time;
 
@@ -171,8 +171,8 @@ async fn handshake_error_with_blinding() -> Result<(), Box<dyn std::error::Error
     let client_config = bad_config.build()?;
     let server_config = common::server_config()?.build()?;
 
-    let client = TlsConnector::new(client_config.clone());
-    let server = TlsAcceptor::new(server_config.clone());
+    let (client, server) =
+        common::paused_clock_endpoints(client_config.clone(), server_config.clone());
     let (server_stream, client_stream) = common::get_streams().await?;
 
     let time_start = time::Instant::now();
@@ -189,6 +189,95 @@ async fn handshake_error_with_blinding() -> Result<(), Box<dyn std::error::Error
     Ok(())
 }
 
+#[tokio::test(start_paused = true)]
+async fn handshake_timeout() -> Result<(), Box<dyn std::error::Error>> {
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    let mut server = TlsAcceptor::new(common::server_config()?.build()?);
+    server.set_handshake_timeout(TIMEOUT);
+
+    // The client never sends a ClientHello, so the server handshake stalls.
+    let (server_stream, _client_stream) = common::get_streams().await?;
+
+    let time_start = time::Instant::now();
+    let error = server.accept(server_stream).await.unwrap_err();
+    let time_elapsed = time_start.elapsed();
+
+    assert!(time_elapsed >= TIMEOUT);
+    assert!(time_elapsed < common::MIN_BLINDING_SECS);
+    assert_eq!(
+        TimeoutError::from_error(&error),
+        Some(TimeoutError::Handshake)
+    );
+    assert_eq!(error.kind(), ErrorType::Application);
+
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn handshake_timeout_with_blinding() -> Result<(), Box<dyn std::error::Error>> {
+    let clock = common::TokioTime::default();
+
+    // Config::builder() does not include a trust store.
+    // The client will reject the server certificate as untrusted.
+    let mut bad_config = Config::builder();
+    bad_config.set_security_policy(&DEFAULT_TLS13)?;
+    bad_config.set_monotonic_clock(clock)?;
+
+    // The handshake fails well before the timeout, so the timeout
+    // must not cut the blinding delay short.
+    let mut client = TlsConnector::new(bad_config.build()?);
+    client.set_handshake_timeout(Duration::from_secs(1));
+    let server = TlsAcceptor::new(common::server_config()?.build()?);
+    let (server_stream, client_stream) = common::get_streams().await?;
+
+    let time_start = time::Instant::now();
+    let result = common::run_negotiate(&client, client_stream, &server, server_stream).await;
+    let time_elapsed = time_start.elapsed();
+
+    assert!(time_elapsed > common::MIN_BLINDING_SECS);
+
+    let error = result.unwrap_err();
+    assert_eq!(TimeoutError::from_error(&error), None);
+    assert_eq!(error.kind(), ErrorType::ProtocolError);
+
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn handshake_timeout_default() -> Result<(), Box<dyn std::error::Error>> {
+    let server = TlsAcceptor::new(common::server_config()?.build()?);
+
+    // The client never sends a ClientHello, so the server handshake stalls.
+    let (server_stream, _client_stream) = common::get_streams().await?;
+
+    let time_start = time::Instant::now();
+    let error = server.accept(server_stream).await.unwrap_err();
+    let time_elapsed = time_start.elapsed();
+
+    assert!(time_elapsed >= DEFAULT_HANDSHAKE_TIMEOUT);
+    assert_eq!(
+        TimeoutError::from_error(&error),
+        Some(TimeoutError::Handshake)
+    );
+
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn handshake_timeout_disabled() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = TlsAcceptor::new(common::server_config()?.build()?);
+    server.set_handshake_timeout(None);
+
+    // The client never sends a ClientHello, so the server handshake stalls.
+    let (server_stream, _client_stream) = common::get_streams().await?;
+
+    let result = time::timeout(DEFAULT_HANDSHAKE_TIMEOUT * 2, server.accept(server_stream)).await;
+    assert!(result.is_err(), "the handshake should still be pending");
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn io_stream_access() -> Result<(), Box<dyn std::error::Error>> {
     let (server_stream, client_stream) = common::get_streams().await?;
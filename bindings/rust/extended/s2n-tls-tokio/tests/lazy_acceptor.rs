@@ -0,0 +1,166 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use s2n_tls::enums::Version;
+use s2n_tls_tokio::{LazyTlsAcceptor, TimeoutError, TlsConnector};
+use std::time::Duration;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    time,
+};
+
+pub mod common;
+
+#[tokio::test]
+async fn select_config_from_client_hello() -> Result<(), Box<dyn std::error::Error>> {
+    let (server_stream, client_stream) = common::get_streams().await?;
+
+    let client = TlsConnector::new(common::client_config_tls12()?.build()?);
+    let tls12_config = common::server_config_tls12()?.build()?;
+    let fallback_config = common::server_config()?.build()?;
+
+    let server = tokio::spawn(async move {
+        let start = LazyTlsAcceptor::new().accept(server_stream).await?;
+        let config = match start.client_hello().server_name()?.as_slice() {
+            b"localhost" => tls12_config,
+            _ => fallback_config,
+        };
+        let mut tls = start.into_stream(config).await?;
+        tls.write_all(common::TEST_STR.as_bytes()).await?;
+        tls.shutdown().await?;
+        Ok::<_, Box<dyn std::error::Error + Send + Sync>>(tls)
+    });
+
+    let mut client = client.connect("localhost", client_stream).await?;
+    let mut received = String::new();
+    client.read_to_string(&mut received).await?;
+    assert_eq!(received, common::TEST_STR);
+
+    let server = server.await?.unwrap();
+    assert_eq!(server.as_ref().actual_protocol_version()?, Version::TLS12);
+    assert_eq!(server.as_ref().server_name(), Some("localhost"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn client_hello_matches_connection() -> Result<(), Box<dyn std::error::Error>> {
+    let (server_stream, client_stream) = common::get_streams().await?;
+
+    let client = TlsConnector::new(common::client_config()?.build()?);
+    let server_config = common::server_config()?.build()?;
+
+    let (client_result, server_result) =
+        tokio::join!(client.connect("localhost", client_stream), async {
+            let start = LazyTlsAcceptor::new().accept(server_stream).await?;
+            let lazy_message = start.client_hello().raw_message()?;
+            let tls = start.into_stream(server_config).await?;
+            Ok::<_, s2n_tls::error::Error>((lazy_message, tls))
+        });
+    client_result?;
+    let (lazy_message, server) = server_result?;
+
+    let connection_message = server.as_ref().client_hello()?.raw_message()?;
+    assert_eq!(lazy_message, connection_message);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn reject_client_hello() -> Result<(), Box<dyn std::error::Error>> {
+    let (server_stream, client_stream) = common::get_streams().await?;
+
+    let client = TlsConnector::new(common::client_config()?.build()?);
+
+    let (client_result, server_result) =
+        tokio::join!(client.connect("localhost", client_stream), async {
+            let start = LazyTlsAcceptor::new().accept(server_stream).await?;
+            // Dropping the stream closes the connection without a handshake
+            drop(start.into_inner());
+            Ok::<_, s2n_tls::error::Error>(())
+        });
+    server_result?;
+    assert!(client_result.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn not_a_client_hello() -> Result<(), Box<dyn std::error::Error>> {
+    let (server_stream, mut client_stream) = common::get_streams().await?;
+
+    client_stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .await?;
+
+    let error = LazyTlsAcceptor::new()
+        .accept(server_stream)
+        .await
+        .unwrap_err();
+    assert!(error.application_error().is_some());
+
+    Ok(())
+}
+
+/// Writes `record` to `stream` until the peer stops reading.
+fn write_records_forever(mut stream: tokio::net::TcpStream, record: Vec<u8>) {
+    tokio::spawn(async move { while stream.write_all(&record).await.is_ok() {} });
+}
+
+#[tokio::test]
+async fn empty_handshake_records() -> Result<(), Box<dyn std::error::Error>> {
+    let (server_stream, client_stream) = common::get_streams().await?;
+    write_records_forever(client_stream, vec![22, 3, 1, 0, 0]);
+
+    let error = LazyTlsAcceptor::new()
+        .accept(server_stream)
+        .await
+        .unwrap_err();
+    let error = error.application_error().unwrap().to_string();
+    assert!(error.contains("empty"), "{error}");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn too_many_handshake_records() -> Result<(), Box<dyn std::error::Error>> {
+    let (server_stream, mut client_stream) = common::get_streams().await?;
+
+    // Start a ClientHello of the maximum size, then fragment the rest of it
+    // into one byte records.
+    client_stream
+        .write_all(&[22, 3, 1, 0, 4, 1, 0, 0xFF, 0xFF])
+        .await?;
+    write_records_forever(client_stream, vec![22, 3, 1, 0, 1, 0]);
+
+    let error = LazyTlsAcceptor::new()
+        .accept(server_stream)
+        .await
+        .unwrap_err();
+    let error = error.application_error().unwrap().to_string();
+    assert!(error.contains("too large"), "{error}");
+
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn client_hello_timeout() -> Result<(), Box<dyn std::error::Error>> {
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    let mut acceptor = LazyTlsAcceptor::new();
+    acceptor.set_handshake_timeout(TIMEOUT);
+
+    // The client sends a partial record header and then stalls.
+    let (server_stream, mut client_stream) = common::get_streams().await?;
+    client_stream.write_all(&[22, 3, 1]).await?;
+
+    let time_start = time::Instant::now();
+    let error = acceptor.accept(server_stream).await.unwrap_err();
+    assert!(time_start.elapsed() >= TIMEOUT);
+    assert_eq!(
+        TimeoutError::from_error(&error),
+        Some(TimeoutError::Handshake)
+    );
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(unix)]
+
+use s2n_tls::{config, enums::SerializationVersion};
+use s2n_tls_tokio::{migrate, TlsAcceptor, TlsConnector, TlsStream};
+use std::os::fd::AsFd;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::UnixStream,
+};
+
+pub mod common;
+
+fn server_config() -> Result<config::Config, s2n_tls::error::Error> {
+    let mut builder = common::server_config()?;
+    builder.set_serialization_version(SerializationVersion::V1)?;
+    builder.build()
+}
+
+#[tokio::test]
+async fn migrate_server_stream() -> Result<(), Box<dyn std::error::Error>> {
+    let (server_stream, client_stream) = common::get_streams().await?;
+    let connector = TlsConnector::new(common::client_config()?.build()?);
+    let acceptor = TlsAcceptor::new(server_config()?);
+    let (mut client, mut server) =
+        common::run_negotiate(&connector, client_stream, &acceptor, server_stream).await?;
+
+    // Leave most of a record decrypted but unread when the stream is migrated.
+    client.write_all(common::TEST_STR.as_bytes()).await?;
+    let mut first = [0; 1];
+    server.read_exact(&mut first).await?;
+
+    let (fd, state) = server.into_serialized().await?;
+    let (old_process, new_process) = UnixStream::pair()?;
+    migrate::send_serialized(&old_process, fd.as_fd(), &state).await?;
+    drop(fd);
+    let (fd, state) = migrate::recv_serialized(&new_process).await?;
+    let mut server = TlsStream::from_serialized(fd, &state, server_config()?)?;
+
+    let mut rest = [0; common::TEST_STR.len() - 1];
+    server.read_exact(&mut rest).await?;
+    assert_eq!(&first, &common::TEST_STR.as_bytes()[..1]);
+    assert_eq!(&rest, &common::TEST_STR.as_bytes()[1..]);
+
+    // The restored stream continues the session in both directions.
+    server.write_all(common::TEST_STR.as_bytes()).await?;
+    let mut received = [0; common::TEST_STR.len()];
+    client.read_exact(&mut received).await?;
+    assert_eq!(&received, common::TEST_STR.as_bytes());
+
+    client.write_all(common::TEST_STR.as_bytes()).await?;
+    server.read_exact(&mut received).await?;
+    assert_eq!(&received, common::TEST_STR.as_bytes());
+
+    server.shutdown().await?;
+    assert_eq!(client.read(&mut received).await?, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn migrate_requires_serialization_version() -> Result<(), Box<dyn std::error::Error>> {
+    let (server_stream, client_stream) = common::get_streams().await?;
+    let connector = TlsConnector::new(common::client_config()?.build()?);
+    let acceptor = TlsAcceptor::new(common::server_config()?.build()?);
+    let (_client, server) =
+        common::run_negotiate(&connector, client_stream, &acceptor, server_stream).await?;
+
+    assert!(server.into_serialized().await.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn invalid_serialized_state() -> Result<(), Box<dyn std::error::Error>> {
+    let (server_stream, _client_stream) = common::get_streams().await?;
+    let fd = server_stream.into_std()?.into();
+
+    let error = TlsStream::from_serialized(fd, &[0; 3], server_config()?).unwrap_err();
+    assert!(error.application_error().is_some());
+
+    Ok(())
+}
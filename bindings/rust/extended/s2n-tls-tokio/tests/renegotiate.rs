@@ -0,0 +1,206 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "unstable-renegotiate")]
+
+use foreign_types::ForeignTypeRef;
+use openssl::{
+    ecdsa::EcdsaSig,
+    pkey::PKey,
+    ssl::{Ssl, SslContext, SslFiletype, SslMethod, SslStream, SslVerifyMode, SslVersion},
+};
+use s2n_tls::{
+    callbacks::{ConnectionFuture, PrivateKeyCallback, PrivateKeyOperation},
+    config,
+    connection::Connection,
+    error::Error,
+    renegotiate::RenegotiateResponse,
+    testing::{CertKeyPair, InsecureAcceptAllCertificatesHandler},
+};
+use s2n_tls_tokio::TlsConnector;
+use std::{
+    io::{Read, Write},
+    net::TcpListener,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    thread,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+// Currently renegotiation is not available from the openssl-sys bindings
+extern "C" {
+    fn SSL_renegotiate(s: *mut openssl_sys::SSL) -> libc::size_t;
+    fn SSL_renegotiate_pending(s: *mut openssl_sys::SSL) -> libc::size_t;
+}
+
+const BEFORE: &[u8] = b"before renegotiation";
+const REQUEST: &[u8] = b"request";
+const RESPONSE: &[u8] = b"after renegotiation";
+
+fn certs() -> CertKeyPair {
+    CertKeyPair::from_path(
+        "permutations/ec_ecdsa_p256_sha256/",
+        "server-chain",
+        "server-key",
+        "ca-cert",
+    )
+}
+
+fn client_config() -> Result<config::Builder, Error> {
+    let certs = certs();
+    let mut builder = config::Builder::new();
+    builder.load_pem(certs.cert(), certs.key())?;
+    builder.trust_pem(certs.cert())?;
+    builder.set_verify_host_callback(InsecureAcceptAllCertificatesHandler {})?;
+    builder.set_renegotiate_callback(RenegotiateResponse::Schedule)?;
+    Ok(builder)
+}
+
+/// s2n-tls doesn't support sending renegotiation requests,
+/// so the server is always openssl.
+///
+/// The server sends some data, requests renegotiation, and then responds
+/// to a request from the client after the renegotiation completes.
+fn spawn_openssl_server() -> Result<
+    (
+        u16,
+        thread::JoinHandle<Result<(), openssl::error::ErrorStack>>,
+    ),
+    std::io::Error,
+> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let port = listener.local_addr()?.port();
+
+    let handle = thread::spawn(move || {
+        let certs = certs();
+        let mut ctx = SslContext::builder(SslMethod::tls_server())?;
+        ctx.set_max_proto_version(Some(SslVersion::TLS1_2))?;
+        ctx.set_min_proto_version(Some(SslVersion::TLS1_2))?;
+        ctx.set_certificate_chain_file(certs.cert_path())?;
+        ctx.set_private_key_file(certs.key_path(), SslFiletype::PEM)?;
+        ctx.set_ca_file(certs.ca_path())?;
+        ctx.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+        let ctx = ctx.build();
+
+        let (stream, _) = listener.accept().expect("accept");
+        let mut server = SslStream::new(Ssl::new(&ctx)?, stream)?;
+        server.accept().expect("initial handshake");
+        server.write_all(BEFORE).expect("write before");
+
+        // The HelloRequest is sent with the next write.
+        let ssl = server.ssl().as_ptr();
+        unsafe { SSL_renegotiate(ssl) };
+        server.write_all(&[0]).expect("write hello request");
+
+        // Reading performs the new handshake.
+        let mut request = [0; REQUEST.len()];
+        server.read_exact(&mut request).expect("read request");
+        assert_eq!(request, REQUEST);
+        assert_eq!(unsafe { SSL_renegotiate_pending(ssl) }, 0);
+
+        server.write_all(RESPONSE).expect("write response");
+        Ok(())
+    });
+    Ok((port, handle))
+}
+
+async fn renegotiate(builder: config::Builder) -> Result<(), Box<dyn std::error::Error>> {
+    let (port, server) = spawn_openssl_server()?;
+    let stream = TcpStream::connect(("127.0.0.1", port)).await?;
+    let connector = TlsConnector::new(builder.build()?);
+    let mut client = connector.connect("localhost", stream).await?;
+
+    let mut received = [0; BEFORE.len() + 1];
+    client.read_exact(&mut received).await?;
+    assert_eq!(&received[..BEFORE.len()], BEFORE);
+
+    // The renegotiation request arrived with the last record read,
+    // so the write needs to complete the new handshake first.
+    client.write_all(REQUEST).await?;
+    assert!(!client.as_ref().is_renegotiating());
+
+    let mut received = [0; RESPONSE.len()];
+    client.read_exact(&mut received).await?;
+    assert_eq!(received, RESPONSE);
+
+    server.join().unwrap()?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn scheduled_renegotiate() -> Result<(), Box<dyn std::error::Error>> {
+    renegotiate(client_config()?).await
+}
+
+#[tokio::test]
+async fn scheduled_renegotiate_with_async_pkey_callback() -> Result<(), Box<dyn std::error::Error>>
+{
+    /// Signs with the client key after yielding to the runtime a few times.
+    struct AsyncSigner {
+        count: Arc<AtomicUsize>,
+    }
+
+    impl PrivateKeyCallback for AsyncSigner {
+        fn handle_operation(
+            &self,
+            _: &mut Connection,
+            operation: PrivateKeyOperation,
+        ) -> Result<Option<Pin<Box<dyn ConnectionFuture>>>, Error> {
+            self.count.fetch_add(1, Ordering::Relaxed);
+            Ok(Some(Box::pin(AsyncSign {
+                pending: 3,
+                op: Some(operation),
+            })))
+        }
+    }
+
+    struct AsyncSign {
+        pending: usize,
+        op: Option<PrivateKeyOperation>,
+    }
+
+    impl ConnectionFuture for AsyncSign {
+        fn poll(
+            self: Pin<&mut Self>,
+            conn: &mut Connection,
+            ctx: &mut Context,
+        ) -> Poll<Result<(), Error>> {
+            let this = self.get_mut();
+            if this.pending > 0 {
+                this.pending -= 1;
+                ctx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+
+            let op = this.op.take().unwrap();
+            let mut digest = vec![0; op.input_size()?];
+            op.input(&mut digest)?;
+            let key = PKey::private_key_from_pem(certs().key())
+                .and_then(|key| key.ec_key())
+                .map_err(|e| Error::application(e.into()))?;
+            let signature = EcdsaSig::sign(&digest, &key)
+                .and_then(|signature| signature.to_der())
+                .map_err(|e| Error::application(e.into()))?;
+            op.set_output(conn, &signature)?;
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut builder = client_config()?;
+    builder.set_private_key_callback(AsyncSigner {
+        count: count.clone(),
+    })?;
+
+    renegotiate(builder).await?;
+    // The key is used once for each handshake.
+    assert_eq!(count.load(Ordering::Relaxed), 2);
+    Ok(())
+}
@@ -2,12 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use s2n_tls::error;
-use s2n_tls_tokio::{TlsAcceptor, TlsConnector, TlsStream};
+use s2n_tls_tokio::{TimeoutError, TlsAcceptor, TlsConnector, TlsStream};
 use std::{
     convert::TryFrom,
     io,
     sync::Arc,
     task::Poll::{Pending, Ready},
+    time::Duration,
 };
 use tokio::{
     io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
@@ -153,8 +154,8 @@ async fn shutdown_with_blinding() -> Result<(), Box<dyn std::error::Error>> {
     let mut server_config = common::server_config()?;
     server_config.set_monotonic_clock(clock)?;
 
-    let client = TlsConnector::new(common::client_config()?.build()?);
-    let server = TlsAcceptor::new(server_config.build()?);
+    let (client, server) =
+        common::paused_clock_endpoints(common::client_config()?.build()?, server_config.build()?);
 
     let (server_stream, client_stream) = common::get_streams().await?;
     let server_stream = common::TestStream::new(server_stream);
@@ -199,8 +200,8 @@ async fn shutdown_with_poll_blinding() -> Result<(), Box<dyn std::error::Error>>
     let mut server_config = common::server_config()?;
     server_config.set_monotonic_clock(clock)?;
 
-    let client = TlsConnector::new(common::client_config()?.build()?);
-    let server = TlsAcceptor::new(server_config.build()?);
+    let (client, server) =
+        common::paused_clock_endpoints(common::client_config()?.build()?, server_config.build()?);
 
     let (server_stream, client_stream) = common::get_streams().await?;
     let server_stream = common::TestStream::new(server_stream);
@@ -339,3 +340,47 @@ async fn shutdown_with_tls_error_and_tcp_delay() -> Result<(), Box<dyn std::erro
 
     Ok(())
 }
+
+#[tokio::test(start_paused = true)]
+async fn shutdown_with_timeout() -> Result<(), Box<dyn std::error::Error>> {
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    let (client, mut server) = common::paused_clock_endpoints(
+        common::client_config()?.build()?,
+        common::server_config()?.build()?,
+    );
+    server.set_shutdown_timeout(TIMEOUT);
+
+    let (server_stream, client_stream) = common::get_streams().await?;
+    let server_stream = common::TestStream::new(server_stream);
+    let overrides = server_stream.overrides();
+
+    let (_, mut server) =
+        common::run_negotiate(&client, client_stream, &server, server_stream).await?;
+
+    // The underlying stream never completes its shutdown
+    fn stall_shutdown(overrides: Arc<common::Overrides>) {
+        let next = overrides.clone();
+        overrides.next_shutdown(Some(Box::new(move |_, _| {
+            stall_shutdown(next.clone());
+            Pending
+        })));
+    }
+    stall_shutdown(overrides);
+
+    let time_start = time::Instant::now();
+    let result = server.shutdown().await;
+    let time_elapsed = time_start.elapsed();
+    assert!(time_elapsed >= TIMEOUT);
+
+    let error: error::Error = result.unwrap_err().try_into()?;
+    assert_eq!(
+        TimeoutError::from_error(&error),
+        Some(TimeoutError::Shutdown)
+    );
+
+    // The abandoned shutdown can't be resumed
+    assert!(server.shutdown().await.is_err());
+
+    Ok(())
+}
@@ -38,6 +38,13 @@ pub use session_ticket::*;
 mod pkey;
 pub use pkey::*;
 
+mod key_log;
+pub use key_log::*;
+
+mod cert_tiebreak;
+pub(crate) use cert_tiebreak::{cert_tiebreak_cb, with_cert_tiebreak_callback};
+pub use cert_tiebreak::{CertPreference, CertTiebreakCallback};
+
 #[cfg(feature = "unstable-crl")]
 mod cert_validation;
 #[cfg(feature = "unstable-crl")]
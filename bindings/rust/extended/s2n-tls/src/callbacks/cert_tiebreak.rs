@@ -0,0 +1,231 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::cert_chain::CertificateChain;
+use s2n_tls_sys::*;
+use std::{cell::Cell, ptr::NonNull};
+
+/// The certificate chain selected by a [`CertTiebreakCallback`].
+#[non_exhaustive]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum CertPreference {
+    /// Keep the certificate chain that was loaded first.
+    Existing,
+    /// Replace it with the certificate chain being loaded.
+    New,
+}
+
+/// A trait for the callback used to choose between two certificate chains
+/// of the same type (RSA, ECDSA, etc.) that match the same domain name.
+///
+/// The callback is invoked while certificate chains are loaded with
+/// [config::Builder::load_chain()](`crate::config::Builder::load_chain()`) or
+/// [config::Builder::load_pem()](`crate::config::Builder::load_pem()`),
+/// not during the handshake. It is invoked once for each conflicting name.
+/// The chain kept for a name is the one that a server selects when the client's
+/// SNI matches that name.
+///
+/// Application contexts set with
+/// [cert_chain::Builder::set_application_context()](`crate::cert_chain::Builder::set_application_context()`)
+/// can be used to attach properties like expiry or priority to each chain.
+pub trait CertTiebreakCallback: 'static + Send + Sync {
+    /// `name` is the domain name from the certificates, in lowercase.
+    fn choose_certificate(
+        &self,
+        existing: &CertificateChain,
+        new: &CertificateChain,
+        name: &str,
+    ) -> CertPreference;
+}
+
+thread_local! {
+    // s2n_cert_tiebreak_callback doesn't receive a context or the config,
+    // so the callback of the config being modified is stored here for the
+    // duration of the call which may invoke it.
+    static CERT_TIEBREAK_CALLBACK: Cell<Option<NonNull<dyn CertTiebreakCallback>>> =
+        const { Cell::new(None) };
+}
+
+/// Makes `callback` available to [`cert_tiebreak_cb`] while `action` runs.
+///
+/// # Safety
+///
+/// `callback` must remain valid until `action` returns.
+pub(crate) unsafe fn with_cert_tiebreak_callback<F, T>(
+    callback: Option<NonNull<dyn CertTiebreakCallback>>,
+    action: F,
+) -> T
+where
+    F: FnOnce() -> T,
+{
+    struct Reset(Option<NonNull<dyn CertTiebreakCallback>>);
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            CERT_TIEBREAK_CALLBACK.with(|current| current.set(self.0));
+        }
+    }
+
+    let _reset = Reset(CERT_TIEBREAK_CALLBACK.with(|current| current.replace(callback)));
+    action()
+}
+
+pub(crate) unsafe extern "C" fn cert_tiebreak_cb(
+    existing: *mut s2n_cert_chain_and_key,
+    new: *mut s2n_cert_chain_and_key,
+    name: *mut u8,
+    name_len: u32,
+) -> *mut s2n_cert_chain_and_key {
+    // Returning null keeps the existing certificate chain.
+    let Some(callback) = CERT_TIEBREAK_CALLBACK.with(|current| current.get()) else {
+        return core::ptr::null_mut();
+    };
+    let (Some(existing_ptr), Some(new_ptr)) = (NonNull::new(existing), NonNull::new(new)) else {
+        return core::ptr::null_mut();
+    };
+    let name = core::slice::from_raw_parts(name, name_len as usize);
+    let Ok(name) = core::str::from_utf8(name) else {
+        return core::ptr::null_mut();
+    };
+
+    let existing_chain = CertificateChain::from_ptr_reference(existing_ptr);
+    let new_chain = CertificateChain::from_ptr_reference(new_ptr);
+    match callback
+        .as_ref()
+        .choose_certificate(&existing_chain, &new_chain, name)
+    {
+        CertPreference::Existing => existing,
+        CertPreference::New => new,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cert_chain, config, error::Error, security, testing::*};
+    use std::sync::{Arc, Mutex};
+
+    /// Prefers the chain with the highest priority, stored as its application context.
+    #[derive(Default)]
+    struct HighestPriority(Arc<Mutex<Vec<String>>>);
+    impl CertTiebreakCallback for HighestPriority {
+        fn choose_certificate(
+            &self,
+            existing: &CertificateChain,
+            new: &CertificateChain,
+            name: &str,
+        ) -> CertPreference {
+            self.0.lock().unwrap().push(name.to_owned());
+            let priority = |chain: &CertificateChain| *chain.application_context::<u8>().unwrap();
+            if priority(new) > priority(existing) {
+                CertPreference::New
+            } else {
+                CertPreference::Existing
+            }
+        }
+    }
+
+    fn chain(priority: u8) -> Result<CertificateChain<'static>, Error> {
+        let cert = SniTestCerts::AlligatorRsa.get();
+        let mut chain = cert_chain::Builder::new()?;
+        chain.load_pem(cert.cert(), cert.key())?;
+        chain.set_application_context(priority)?;
+        chain.build()
+    }
+
+    fn selected_priority(server_config: &config::Config) -> Result<u8, Error> {
+        let client_config = {
+            let cert = SniTestCerts::AlligatorRsa.get();
+            let mut config = config::Builder::new();
+            config.set_security_policy(&security::DEFAULT_TLS13)?;
+            config.trust_pem(cert.cert())?;
+            config.build()?
+        };
+
+        let mut pair = TestPair::from_configs(&client_config, server_config);
+        pair.client.set_server_name("www.alligator.com")?;
+        pair.handshake()?;
+
+        let selected = pair.server.selected_cert().unwrap();
+        Ok(*selected.application_context::<u8>().unwrap())
+    }
+
+    #[test]
+    fn cert_tiebreak() -> Result<(), Error> {
+        for priorities in [[1, 3, 2], [3, 2, 1], [1, 2, 3]] {
+            let mut server_config = config::Builder::new();
+            server_config.set_cert_tiebreak_callback(HighestPriority::default())?;
+            for priority in priorities {
+                server_config.load_chain(chain(priority)?)?;
+            }
+            let server_config = server_config.build()?;
+            assert_eq!(selected_priority(&server_config)?, 3, "{priorities:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn no_cert_tiebreak() -> Result<(), Error> {
+        // Without a callback, the first chain loaded is used.
+        let mut server_config = config::Builder::new();
+        for priority in [1, 3, 2] {
+            server_config.load_chain(chain(priority)?)?;
+        }
+        let server_config = server_config.build()?;
+        assert_eq!(selected_priority(&server_config)?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn cert_tiebreak_names() -> Result<(), Error> {
+        let callback = HighestPriority::default();
+        let names = Arc::clone(&callback.0);
+
+        let mut server_config = config::Builder::new();
+        server_config.set_cert_tiebreak_callback(callback)?;
+        for priority in [1, 2, 3] {
+            server_config.load_chain(chain(priority)?)?;
+        }
+
+        // The callback is invoked for each conflict, with the name from the SAN.
+        let names = names.lock().unwrap();
+        assert_eq!(*names, ["www.alligator.com", "www.alligator.com"]);
+
+        // The callback is only available while chains are loaded.
+        assert!(CERT_TIEBREAK_CALLBACK
+            .with(|current| current.get())
+            .is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn cert_tiebreak_public_chains() -> Result<(), Error> {
+        /// Records each conflict, and prefers the new chain.
+        #[derive(Default)]
+        struct PreferNew(Arc<Mutex<Vec<String>>>);
+        impl CertTiebreakCallback for PreferNew {
+            fn choose_certificate(
+                &self,
+                _existing: &CertificateChain,
+                _new: &CertificateChain,
+                name: &str,
+            ) -> CertPreference {
+                self.0.lock().unwrap().push(name.to_owned());
+                CertPreference::New
+            }
+        }
+
+        let callback = PreferNew::default();
+        let names = Arc::clone(&callback.0);
+
+        // load_public_pem can only be called once per config, so the conflict
+        // comes from a chain which lists the same name twice in its SAN.
+        let cert = CertKeyPair::from_path("sni/duplicate_san_rsa_", "cert", "key", "cert");
+        let mut server_config = config::Builder::new();
+        server_config.set_cert_tiebreak_callback(callback)?;
+        server_config.load_public_pem(cert.cert())?;
+
+        // Chains without a private key are also resolved by the callback.
+        assert_eq!(*names.lock().unwrap(), ["www.duplicate.com"]);
+        Ok(())
+    }
+}
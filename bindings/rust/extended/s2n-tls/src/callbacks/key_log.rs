@@ -0,0 +1,181 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::connection::Connection;
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::Path,
+    sync::Mutex,
+};
+
+/// The environment variable conventionally used to name the key log file.
+pub const SSLKEYLOGFILE: &str = "SSLKEYLOGFILE";
+
+/// A trait for the callback used to receive the secrets negotiated by a connection.
+///
+/// THIS SHOULD BE USED FOR DEBUGGING PURPOSES ONLY!
+///
+/// Each line is formatted with the
+/// [NSS Key Log Format](https://developer.mozilla.org/en-US/docs/Mozilla/Projects/NSS/Key_Log_Format)
+/// and does not include a trailing newline. Anyone with access to the lines
+/// can decrypt the traffic of the connection.
+pub trait KeyLogCallback: 'static + Send + Sync {
+    fn on_key_log(&self, connection: &Connection, line: &[u8]);
+}
+
+/// A [`KeyLogCallback`] which appends each line to a file in the
+/// NSS Key Log Format, which tools like Wireshark can use to decrypt traffic.
+///
+/// THIS SHOULD BE USED FOR DEBUGGING PURPOSES ONLY!
+///
+/// The file is flushed after every line, so it can be read while connections
+/// are still in progress.
+#[derive(Debug)]
+pub struct KeyLogFile(Mutex<File>);
+
+impl KeyLogFile {
+    /// Opens the file at `path` for appending, creating it if necessary.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = OpenOptions::new().append(true).create(true).open(path)?;
+        Ok(Self(Mutex::new(file)))
+    }
+
+    /// Opens the file named by the `SSLKEYLOGFILE` environment variable.
+    ///
+    /// Returns `Ok(None)` if the variable is not set or is empty.
+    pub fn from_env() -> io::Result<Option<Self>> {
+        match std::env::var_os(SSLKEYLOGFILE) {
+            Some(path) if !path.is_empty() => Self::open(path).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl KeyLogCallback for KeyLogFile {
+    fn on_key_log(&self, _connection: &Connection, line: &[u8]) {
+        // A poisoned lock only means another thread panicked mid-write;
+        // the file itself is still usable.
+        let mut file = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        // The line and its newline are written together so that concurrent
+        // connections don't interleave partial lines.
+        let mut entry = Vec::with_capacity(line.len() + 1);
+        entry.extend_from_slice(line);
+        entry.push(b'\n');
+        // Failing to log a secret must not fail the handshake.
+        let _ = file.write_all(&entry).and_then(|_| file.flush());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{security, testing::*};
+    use std::{path::PathBuf, sync::Arc};
+
+    #[derive(Clone, Default)]
+    struct Lines(Arc<Mutex<Vec<String>>>);
+    impl KeyLogCallback for Lines {
+        fn on_key_log(&self, _connection: &Connection, line: &[u8]) {
+            let line = String::from_utf8(line.to_vec()).unwrap();
+            self.0.lock().unwrap().push(line);
+        }
+    }
+
+    fn labels(lines: &[String]) -> Vec<&str> {
+        lines
+            .iter()
+            .map(|line| line.split(' ').next().unwrap())
+            .collect()
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("s2n-tls-{name}-{}", std::process::id()))
+    }
+
+    #[test]
+    fn key_log_callback() -> Result<(), Box<dyn std::error::Error>> {
+        let lines = Lines::default();
+        let config = {
+            let mut config = config_builder(&security::DEFAULT_TLS13)?;
+            config.set_key_logger(lines.clone())?;
+            config.build()?
+        };
+
+        let mut pair = TestPair::from_config(&config);
+        pair.handshake()?;
+
+        let lines = lines.0.lock().unwrap();
+        // Both the client and the server log every secret.
+        for label in [
+            "CLIENT_HANDSHAKE_TRAFFIC_SECRET",
+            "SERVER_HANDSHAKE_TRAFFIC_SECRET",
+            "CLIENT_TRAFFIC_SECRET_0",
+            "SERVER_TRAFFIC_SECRET_0",
+        ] {
+            let count = labels(&lines).iter().filter(|l| **l == label).count();
+            assert_eq!(count, 2, "{label}");
+        }
+
+        // Each line is "<label> <client random> <secret>", all hex encoded.
+        for line in lines.iter() {
+            let fields: Vec<&str> = line.split(' ').collect();
+            assert_eq!(fields.len(), 3, "{line}");
+            assert_eq!(fields[1].len(), 64, "{line}");
+            assert!(hex::decode(fields[2]).is_ok(), "{line}");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn key_log_file() -> Result<(), Box<dyn std::error::Error>> {
+        let path = temp_path("key_log_file");
+        let _ = std::fs::remove_file(&path);
+
+        let config = {
+            let mut config = config_builder(&security::DEFAULT_TLS13)?;
+            config.set_key_logger(KeyLogFile::open(&path)?)?;
+            config.build()?
+        };
+
+        // The file is appended to across connections.
+        for _ in 0..2 {
+            let mut pair = TestPair::from_config(&config);
+            pair.handshake()?;
+        }
+
+        let contents = std::fs::read_to_string(&path)?;
+        std::fs::remove_file(&path)?;
+
+        let lines: Vec<String> = contents.lines().map(String::from).collect();
+        assert!(contents.ends_with('\n'));
+        let count = labels(&lines)
+            .iter()
+            .filter(|l| **l == "CLIENT_TRAFFIC_SECRET_0")
+            .count();
+        assert_eq!(count, 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn key_log_file_from_env() -> Result<(), Box<dyn std::error::Error>> {
+        let path = temp_path("key_log_file_from_env");
+        let _ = std::fs::remove_file(&path);
+
+        temp_env::with_var_unset(SSLKEYLOGFILE, || {
+            assert!(KeyLogFile::from_env().unwrap().is_none());
+        });
+        temp_env::with_var(SSLKEYLOGFILE, Some(""), || {
+            assert!(KeyLogFile::from_env().unwrap().is_none());
+        });
+        temp_env::with_var(SSLKEYLOGFILE, Some(&path), || {
+            assert!(KeyLogFile::from_env().unwrap().is_some());
+        });
+
+        assert!(path.exists());
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+}
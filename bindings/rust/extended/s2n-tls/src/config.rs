@@ -327,13 +327,16 @@ impl Builder {
     pub fn load_pem(&mut self, certificate: &[u8], private_key: &[u8]) -> Result<&mut Self, Error> {
         let certificate = CString::new(certificate).map_err(|_| Error::INVALID_INPUT)?;
         let private_key = CString::new(private_key).map_err(|_| Error::INVALID_INPUT)?;
+        let config = self.as_mut_ptr();
         unsafe {
-            s2n_config_add_cert_chain_and_key(
-                self.as_mut_ptr(),
-                certificate.as_ptr(),
-                private_key.as_ptr(),
-            )
-            .into_result()
+            with_cert_tiebreak_callback(self.cert_tiebreak_callback(), || {
+                s2n_config_add_cert_chain_and_key(
+                    config,
+                    certificate.as_ptr(),
+                    private_key.as_ptr(),
+                )
+                .into_result()
+            })
         }?;
         Ok(self)
     }
@@ -345,14 +348,17 @@ impl Builder {
         // visibility into the failure modes, so this behavior ensures that _if_
         // the C library held the reference despite the failure, it would continue
         // to be valid memory.
+        let config = self.as_mut_ptr();
         let result = unsafe {
-            s2n_config_add_cert_chain_and_key_to_store(
-                self.as_mut_ptr(),
-                // SAFETY: audit of add_to_store shows that the certificate chain
-                // is not mutated. https://github.com/aws/s2n-tls/issues/4140
-                chain.as_ptr() as *mut _,
-            )
-            .into_result()
+            with_cert_tiebreak_callback(self.cert_tiebreak_callback(), || {
+                s2n_config_add_cert_chain_and_key_to_store(
+                    config,
+                    // SAFETY: audit of add_to_store shows that the certificate chain
+                    // is not mutated. https://github.com/aws/s2n-tls/issues/4140
+                    chain.as_ptr() as *mut _,
+                )
+                .into_result()
+            })
         };
         let context = unsafe {
             // SAFETY: usage of context_mut is safe in the builder, because the
@@ -365,6 +371,43 @@ impl Builder {
         Ok(self)
     }
 
+    /// Sets a callback to choose between certificate chains of the same type
+    /// which match the same domain name.
+    ///
+    /// The callback is only invoked for chains loaded after it is set.
+    /// If no callback is set, the chain loaded first is used.
+    ///
+    /// Corresponds to [`s2n_config_set_cert_tiebreak_callback`].
+    pub fn set_cert_tiebreak_callback<T: 'static + CertTiebreakCallback>(
+        &mut self,
+        handler: T,
+    ) -> Result<&mut Self, Error> {
+        let handler = Box::new(handler);
+        let context = unsafe {
+            // SAFETY: usage of context_mut is safe in the builder, because while
+            // it is being built, the Builder is the only reference to the config.
+            self.config.context_mut()
+        };
+        context.cert_tiebreak_callback = Some(handler);
+
+        unsafe {
+            s2n_config_set_cert_tiebreak_callback(self.as_mut_ptr(), Some(cert_tiebreak_cb))
+                .into_result()
+        }?;
+        Ok(self)
+    }
+
+    /// The callback passed to [`with_cert_tiebreak_callback`] when loading certificates.
+    ///
+    /// The pointer remains valid until the callback is replaced or the config is dropped.
+    fn cert_tiebreak_callback(&self) -> Option<NonNull<dyn CertTiebreakCallback>> {
+        self.config
+            .context()
+            .cert_tiebreak_callback
+            .as_deref()
+            .map(NonNull::from)
+    }
+
     /// Corresponds to [`s2n_config_set_cert_chain_and_key_defaults`].
     pub fn set_default_chains<T: IntoIterator<Item = CertificateChain<'static>>>(
         &mut self,
@@ -424,7 +467,12 @@ impl Builder {
             .try_into()
             .map_err(|_| Error::INVALID_INPUT)?;
         let certificate = certificate.as_ptr() as *mut u8;
-        unsafe { s2n_config_add_cert_chain(self.as_mut_ptr(), certificate, size) }.into_result()?;
+        let config = self.as_mut_ptr();
+        unsafe {
+            with_cert_tiebreak_callback(self.cert_tiebreak_callback(), || {
+                s2n_config_add_cert_chain(config, certificate, size).into_result()
+            })
+        }?;
         Ok(self)
     }
 
@@ -596,6 +644,8 @@ impl Builder {
     /// THIS SHOULD BE USED FOR DEBUGGING PURPOSES ONLY!
     /// The `context` pointer must live at least as long as the config
     ///
+    /// Prefer [`Builder::set_key_logger`], which doesn't require unsafe code.
+    ///
     /// Corresponds to [`s2n_config_set_key_log_cb`].
     pub unsafe fn set_key_log_callback(
         &mut self,
@@ -606,6 +656,57 @@ impl Builder {
         Ok(self)
     }
 
+    /// Sets a callback to receive the secrets negotiated by each connection,
+    /// formatted with the NSS Key Log Format.
+    ///
+    /// THIS SHOULD BE USED FOR DEBUGGING PURPOSES ONLY!
+    ///
+    /// [`KeyLogFile`] can be used to write the secrets to the file
+    /// named by the `SSLKEYLOGFILE` environment variable:
+    ///
+    /// ```no_run
+    /// use s2n_tls::{callbacks::KeyLogFile, config::Config};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut builder = Config::builder();
+    /// if let Some(key_log) = KeyLogFile::from_env()? {
+    ///     builder.set_key_logger(key_log)?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Corresponds to [`s2n_config_set_key_log_cb`].
+    pub fn set_key_logger<T: 'static + KeyLogCallback>(
+        &mut self,
+        handler: T,
+    ) -> Result<&mut Self, Error> {
+        unsafe extern "C" fn key_log_cb(
+            _context: *mut ::libc::c_void,
+            conn_ptr: *mut s2n_connection,
+            logline: *mut u8,
+            len: usize,
+        ) -> libc::c_int {
+            let line = core::slice::from_raw_parts(logline, len);
+            with_context(conn_ptr, |conn, context| {
+                let callback = context.key_log_callback.as_ref();
+                callback.map(|c| c.on_key_log(conn, line))
+            });
+            CallbackResult::Success.into()
+        }
+
+        let handler = Box::new(handler);
+        let context = unsafe {
+            // SAFETY: usage of context_mut is safe in the builder, because while
+            // it is being built, the Builder is the only reference to the config.
+            self.config.context_mut()
+        };
+        context.key_log_callback = Some(handler);
+
+        let context = self.config.context() as *const Context as *mut c_void;
+        unsafe { self.set_key_log_callback(Some(key_log_cb), context) }
+    }
+
     /// Corresponds to [`s2n_config_set_max_cert_chain_depth`].
     pub fn set_max_cert_chain_depth(&mut self, depth: u16) -> Result<&mut Self, Error> {
         unsafe { s2n_config_set_max_cert_chain_depth(self.as_mut_ptr(), depth).into_result() }?;
@@ -637,6 +738,19 @@ impl Builder {
         Ok(self)
     }
 
+    /// Enables the Next Protocol Negotiation extension, a legacy alternative to ALPN.
+    ///
+    /// The protocols are configured with [`Builder::set_application_protocol_preference`].
+    /// If the peer also supports ALPN, ALPN is preferred. NPN is only negotiated
+    /// in TLS1.2 and earlier.
+    ///
+    /// Corresponds to [`s2n_config_set_npn`].
+    #[cfg(feature = "unstable-npn")]
+    pub fn set_npn(&mut self, enable: bool) -> Result<&mut Self, Error> {
+        unsafe { s2n_config_set_npn(self.as_mut_ptr(), enable).into_result() }?;
+        Ok(self)
+    }
+
     /// Set a callback function to perform custom cert validation synchronously.
     ///
     /// Corresponds to [`s2n_config_set_cert_validation_cb`], but the rust callback
@@ -727,7 +841,9 @@ impl Builder {
     }
 
     /// Corresponds to [`s2n_config_set_subscriber`], [`s2n_config_set_handshake_event`],
-    /// and [`s2n_config_set_timing_checkpoint_cb`].
+    /// [`s2n_config_set_timing_checkpoint_cb`], [`s2n_config_set_connection_close_event`],
+    /// [`s2n_config_set_alert_event`], [`s2n_config_set_key_update_event`],
+    /// and [`s2n_config_set_session_ticket_event`].
     #[cfg(feature = "unstable-events")]
     pub fn set_event_subscriber<T: 'static + EventSubscriber>(
         &mut self,
@@ -762,6 +878,62 @@ impl Builder {
             });
         }
 
+        unsafe extern "C" fn on_connection_close(
+            conn_ptr: *mut s2n_tls_sys::s2n_connection,
+            _subscriber: *mut c_void,
+            event: *mut s2n_tls_sys::s2n_event_connection_close,
+        ) {
+            with_context(conn_ptr, |conn, context| {
+                let callback = context.event_subscriber.as_ref();
+                if let Some(callback) = callback {
+                    callback.on_connection_close(
+                        conn,
+                        &crate::events::ConnectionCloseEvent::new(&*event),
+                    );
+                }
+            });
+        }
+
+        unsafe extern "C" fn on_alert(
+            conn_ptr: *mut s2n_tls_sys::s2n_connection,
+            _subscriber: *mut c_void,
+            event: *mut s2n_tls_sys::s2n_event_alert,
+        ) {
+            with_context(conn_ptr, |conn, context| {
+                let callback = context.event_subscriber.as_ref();
+                if let Some(callback) = callback {
+                    callback.on_alert(conn, &crate::events::AlertEvent::new(&*event));
+                }
+            });
+        }
+
+        unsafe extern "C" fn on_key_update(
+            conn_ptr: *mut s2n_tls_sys::s2n_connection,
+            _subscriber: *mut c_void,
+            event: *mut s2n_tls_sys::s2n_event_key_update,
+        ) {
+            with_context(conn_ptr, |conn, context| {
+                let callback = context.event_subscriber.as_ref();
+                if let Some(callback) = callback {
+                    callback.on_key_update(conn, &crate::events::KeyUpdateEvent::new(&*event));
+                }
+            });
+        }
+
+        unsafe extern "C" fn on_session_ticket(
+            conn_ptr: *mut s2n_tls_sys::s2n_connection,
+            _subscriber: *mut c_void,
+            event: *mut s2n_tls_sys::s2n_event_session_ticket,
+        ) {
+            with_context(conn_ptr, |conn, context| {
+                let callback = context.event_subscriber.as_ref();
+                if let Some(callback) = callback {
+                    callback
+                        .on_session_ticket(conn, &crate::events::SessionTicketEvent::new(&*event));
+                }
+            });
+        }
+
         let handler = Box::new(subscriber);
         let context = unsafe {
             // SAFETY: usage of context_mut is safe in the builder, because while
@@ -791,6 +963,32 @@ impl Builder {
             .into_result()
         }?;
 
+        // Register the post-handshake connection lifecycle callbacks.
+        unsafe {
+            s2n_tls_sys::s2n_config_set_connection_close_event(
+                self.as_mut_ptr(),
+                Some(on_connection_close),
+            )
+            .into_result()
+        }?;
+
+        unsafe {
+            s2n_tls_sys::s2n_config_set_alert_event(self.as_mut_ptr(), Some(on_alert)).into_result()
+        }?;
+
+        unsafe {
+            s2n_tls_sys::s2n_config_set_key_update_event(self.as_mut_ptr(), Some(on_key_update))
+                .into_result()
+        }?;
+
+        unsafe {
+            s2n_tls_sys::s2n_config_set_session_ticket_event(
+                self.as_mut_ptr(),
+                Some(on_session_ticket),
+            )
+            .into_result()
+        }?;
+
         Ok(self)
     }
 
@@ -1169,6 +1367,8 @@ pub(crate) struct Context {
     pub(crate) private_key_callback: Option<Box<dyn PrivateKeyCallback>>,
     pub(crate) verify_host_callback: Option<Box<dyn VerifyHostNameCallback>>,
     pub(crate) session_ticket_callback: Option<Box<dyn SessionTicketCallback>>,
+    pub(crate) key_log_callback: Option<Box<dyn KeyLogCallback>>,
+    pub(crate) cert_tiebreak_callback: Option<Box<dyn CertTiebreakCallback>>,
     pub(crate) connection_initializer: Option<Box<dyn ConnectionInitializer>>,
     pub(crate) wall_clock: Option<Box<dyn WallClock>>,
     pub(crate) monotonic_clock: Option<Box<dyn MonotonicClock>>,
@@ -1195,6 +1395,8 @@ impl Default for Context {
             private_key_callback: None,
             verify_host_callback: None,
             session_ticket_callback: None,
+            key_log_callback: None,
+            cert_tiebreak_callback: None,
             connection_initializer: None,
             wall_clock: None,
             monotonic_clock: None,
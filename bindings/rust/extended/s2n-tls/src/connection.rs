@@ -181,6 +181,18 @@ impl Connection {
         self.as_ptr()
     }
 
+    /// Returns an identifier for the connection, which is unique among
+    /// connections that have not been freed.
+    ///
+    /// An [`EventSubscriber`](crate::events::EventSubscriber) can use the
+    /// identifier to correlate the events of a connection. The identifier is
+    /// the address of the underlying `s2n_connection`, so it does not change
+    /// when the connection is wiped, and may be reused after it is freed.
+    #[cfg(feature = "unstable-events")]
+    pub fn id(&self) -> usize {
+        self.connection.as_ptr() as usize
+    }
+
     /// # Safety
     ///
     /// Caller must ensure s2n_connection is a valid reference to a [`s2n_connection`] object
@@ -239,30 +251,6 @@ impl Connection {
         Ok(self)
     }
 
-    /// Attempts to drop the config on the connection.
-    ///
-    /// # Safety
-    ///
-    /// The caller must ensure the config associated with the connection was created
-    /// with a [`config::Builder`].
-    unsafe fn drop_config(&mut self) -> Result<(), Error> {
-        let mut prev_config = core::ptr::null_mut();
-
-        // A valid non-null pointer is returned only if the application previously called
-        // [`Self::set_config()`].
-        if s2n_connection_get_config(self.connection.as_ptr(), &mut prev_config)
-            .into_result()
-            .is_ok()
-        {
-            let prev_config = NonNull::new(prev_config).expect(
-                "config should exist since the call to s2n_connection_get_config was successful",
-            );
-            drop(Config::from_raw(prev_config));
-        }
-
-        Ok(())
-    }
-
     /// Associates a configuration object with a connection.
     ///
     /// Corresponds to [`s2n_connection_set_config`].
@@ -562,11 +550,23 @@ impl Connection {
     where
         F: FnOnce(&mut Self) -> Result<T, Error>,
     {
-        // Safety:
-        // We re-init the context after the wipe
-        unsafe { self.drop_context()? };
+        // The wipe may invoke callbacks, like the connection close event, so
+        // the old context is only dropped after the wipe completes.
+        let prev_ctx = unsafe { s2n_connection_get_ctx(self.connection.as_ptr()) }.into_result();
 
         let result = wipe(self);
+        // Safety:
+        // We clear the connection's pointer to the old context and re-init
+        // the context immediately after dropping it. A successful wipe already
+        // clears the pointer, but a failed wipe leaves it in place.
+        if let Ok(prev_ctx) = prev_ctx {
+            drop(unsafe { Box::from_raw(prev_ctx.as_ptr() as *mut Context) });
+            unsafe {
+                s2n_connection_set_ctx(self.connection.as_ptr(), core::ptr::null_mut())
+                    .into_result()
+                    .unwrap();
+            }
+        }
         // We must initialize the context again whether or not wipe succeeds.
         // A connection without a context is invalid and has undefined behavior.
         self.init_context();
@@ -972,23 +972,6 @@ impl Connection {
         }
     }
 
-    /// Drop the context
-    ///
-    /// SAFETY:
-    /// A connection without a context is invalid. After calling this method
-    /// from anywhere other than Drop, you must reinitialize the context.
-    unsafe fn drop_context(&mut self) -> Result<(), Error> {
-        let ctx = s2n_connection_get_ctx(self.connection.as_ptr()).into_result();
-        if let Ok(ctx) = ctx {
-            drop(Box::from_raw(ctx.as_ptr() as *mut Context));
-        }
-        // Setting a NULL context is important: if we don't also remove the context
-        // from the connection, then the invalid memory is still accessible and
-        // may even be double-freed.
-        s2n_connection_set_ctx(self.connection.as_ptr(), core::ptr::null_mut()).into_result()?;
-        Ok(())
-    }
-
     /// Mark that the server_name extension was used to configure the connection.
     ///
     /// Corresponds to [`s2n_connection_server_name_extension_used`].
@@ -1121,6 +1104,24 @@ impl Connection {
         }
     }
 
+    /// The name of the last TLS message processed, e.g. "SERVER_HELLO".
+    ///
+    /// If the handshake failed, this is the message being processed when it failed.
+    ///
+    /// Corresponds to [`s2n_connection_get_last_message_name`].
+    pub fn last_message_name(&self) -> Result<&str, Error> {
+        let message = unsafe {
+            s2n_connection_get_last_message_name(self.connection.as_ptr()).into_result()?
+        };
+        unsafe {
+            // SAFETY: Constructed strings have a null byte appended to them.
+            // SAFETY: The data has a 'static lifetime, because it resides in a
+            //         static char array, and is never modified after its initial
+            //         creation.
+            const_str!(message)
+        }
+    }
+
     /// Corresponds to [`s2n_connection_get_cipher`].
     pub fn cipher_suite(&self) -> Result<&str, Error> {
         let cipher = unsafe { s2n_connection_get_cipher(self.connection.as_ptr()).into_result()? };
@@ -1322,6 +1323,22 @@ impl Connection {
         Some(unsafe { CStr::from_ptr(protocol).to_bytes() })
     }
 
+    /// Returns the extension used to negotiate the [application protocol](Self::application_protocol),
+    /// or `None` if no application protocol was negotiated.
+    #[cfg(feature = "unstable-npn")]
+    pub fn application_protocol_negotiation(&self) -> Result<Option<ProtocolNegotiation>, Error> {
+        if self.application_protocol().is_none() {
+            return Ok(None);
+        }
+        // s2n-tls records the use of NPN in the handshake type.
+        let negotiation = if self.handshake_type()?.contains("WITH_NPN") {
+            ProtocolNegotiation::Npn
+        } else {
+            ProtocolNegotiation::Alpn
+        };
+        Ok(Some(negotiation))
+    }
+
     /// Provides access to the TLS-Exporter functionality.
     ///
     /// See https://datatracker.ietf.org/doc/html/rfc5705 and https://www.rfc-editor.org/rfc/rfc8446.
@@ -1505,6 +1522,16 @@ impl Connection {
         Ok(())
     }
 
+    /// Corresponds to [`s2n_connection_get_early_data_status`].
+    pub fn early_data_status(&self) -> Result<EarlyDataStatus, Error> {
+        let mut status = s2n_early_data_status_t::NOT_REQUESTED;
+        unsafe {
+            s2n_connection_get_early_data_status(self.connection.as_ptr(), &mut status)
+                .into_result()?;
+        }
+        status.try_into()
+    }
+
     /// Associates arbitrary application contexts with the Connection to be later retrieved via
     /// the [`Self::application_context()`] and [`Self::application_context_mut()`] APIs.
     ///
@@ -1708,14 +1735,28 @@ impl Drop for Connection {
     fn drop(&mut self) {
         // ignore failures since there's not much we can do about it
         unsafe {
-            // clean up context
-            let _ = self.drop_context();
-
-            // cleanup config
-            let _ = self.drop_config();
+            // Freeing the connection may invoke callbacks, like the connection
+            // close event, which require the context and config. So retrieve
+            // them first and only drop them once the connection is freed.
+            let ctx = s2n_connection_get_ctx(self.connection.as_ptr()).into_result();
+            let mut config = core::ptr::null_mut();
+            let config = s2n_connection_get_config(self.connection.as_ptr(), &mut config)
+                .into_result()
+                .ok()
+                .and_then(|_| NonNull::new(config));
 
             // cleanup connection
             let _ = s2n_connection_free(self.connection.as_ptr()).into_result();
+
+            // clean up context
+            if let Ok(ctx) = ctx {
+                drop(Box::from_raw(ctx.as_ptr() as *mut Context));
+            }
+
+            // cleanup config
+            if let Some(config) = config {
+                drop(Config::from_raw(config));
+            }
         }
     }
 }
@@ -1726,6 +1767,7 @@ mod tests {
 
     use super::*;
     use crate::{
+        error::ErrorType,
         security::Policy,
         testing::{build_config, config_builder, LIFOSessionResumption, SniTestCerts, TestPair},
     };
@@ -2080,7 +2122,7 @@ mod tests {
         assert_eq!(err.name(), "S2N_ERR_TOO_MANY_CERTIFICATES");
 
         // The connection should still be usable (no dangling pointer).
-        // Dropping the connection exercises drop_config which would crash
+        // Dropping the connection drops the connection's config, which would crash
         // if the old config had been freed.
         drop(conn);
     }
@@ -2131,6 +2173,32 @@ mod tests {
         Ok(())
     }
 
+    /// A connection remains usable after a failed wipe.
+    #[test]
+    fn failed_wipe_leaves_connection_usable() -> Result<(), Box<dyn std::error::Error>> {
+        let config = build_config(&security::DEFAULT_TLS13)?;
+        let mut pair = TestPair::from_config(&config);
+
+        pair.server.set_application_context(1142_u32);
+        let result = pair.server.wipe_method(|_| {
+            Err::<(), _>(Error::bindings(
+                ErrorType::UsageError,
+                "TestError",
+                "wipe failed",
+            ))
+        });
+        assert_eq!(result.unwrap_err().name(), "TestError");
+
+        // The old context was replaced with a new one.
+        assert!(pair.server.application_context::<u32>().is_none());
+        pair.server.set_application_context(1142_u32);
+        assert_eq!(*pair.server.application_context::<u32>().unwrap(), 1142);
+
+        pair.handshake()?;
+        assert_eq!(pair.server.actual_protocol_version()?, Version::TLS13);
+        Ok(())
+    }
+
     /// A wiped connection can be reused for a subsequent handshake.
     #[test]
     fn wipe_allows_connection_reuse() -> Result<(), Box<dyn std::error::Error>> {
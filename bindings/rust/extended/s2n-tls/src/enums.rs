@@ -300,6 +300,35 @@ impl From<PskHmac> for s2n_psk_hmac::Type {
     }
 }
 
+/// Corresponds to [`s2n_early_data_status_t`].
+#[non_exhaustive]
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum EarlyDataStatus {
+    /// Early data is in progress.
+    InProgress,
+    /// The client did not request early data.
+    NotRequested,
+    /// The client requested early data, but the server rejected the request.
+    Rejected,
+    /// All early data was successfully sent and received.
+    End,
+}
+
+impl TryFrom<s2n_early_data_status_t::Type> for EarlyDataStatus {
+    type Error = Error;
+
+    fn try_from(input: s2n_early_data_status_t::Type) -> Result<Self, Self::Error> {
+        let status = match input {
+            s2n_early_data_status_t::OK => Self::InProgress,
+            s2n_early_data_status_t::NOT_REQUESTED => Self::NotRequested,
+            s2n_early_data_status_t::REJECTED => Self::Rejected,
+            s2n_early_data_status_t::END => Self::End,
+            _ => return Err(Error::INVALID_INPUT),
+        };
+        Ok(status)
+    }
+}
+
 /// Corresponds to [`s2n_serialization_version`].
 #[non_exhaustive]
 #[derive(Debug, PartialEq, Copy, Clone)]
@@ -316,3 +345,12 @@ impl From<SerializationVersion> for s2n_serialization_version::Type {
         }
     }
 }
+
+/// The extension used to negotiate the application protocol of a connection.
+#[cfg(feature = "unstable-npn")]
+#[non_exhaustive]
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum ProtocolNegotiation {
+    Alpn,
+    Npn,
+}
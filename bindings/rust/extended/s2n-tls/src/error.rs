@@ -192,6 +192,12 @@ impl Error {
         Self(Context::Bindings(kind, name, message))
     }
 
+    /// An error reported by s2n-tls as an error code, rather than through s2n_errno.
+    #[cfg(feature = "unstable-events")]
+    pub(crate) fn from_code(code: s2n_status_code::Type) -> Self {
+        Self(Context::Code(code, Errno(0)))
+    }
+
     fn capture() -> Self {
         unsafe {
             let s2n_errno = s2n_errno_location();
@@ -97,6 +97,12 @@ impl HandshakeFailure<'_> {
     pub fn error_code(&self) -> i32 {
         self.0.error_code
     }
+
+    /// The handshake failure as an [`Error`](crate::error::Error), for access
+    /// to its name and [`ErrorType`](crate::error::ErrorType).
+    pub fn error(&self) -> crate::error::Error {
+        crate::error::Error::from_code(self.0.error_code)
+    }
 }
 
 impl Debug for HandshakeEvent<'_> {
@@ -177,6 +183,163 @@ impl Debug for TimingCheckpoint<'_> {
     }
 }
 
+/// Whether this endpoint sent a message, or received it from the peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+impl Direction {
+    fn from_sent(sent: u8) -> Self {
+        if sent == 0 {
+            Self::Received
+        } else {
+            Self::Sent
+        }
+    }
+}
+
+/// Emitted once when a connection is freed or wiped, if it ever called
+/// s2n_negotiate.
+///
+/// The event is only valid for the duration of the callback invocation.
+pub struct ConnectionCloseEvent<'a>(&'a s2n_tls_sys::s2n_event_connection_close);
+
+impl<'a> ConnectionCloseEvent<'a> {
+    pub(crate) fn new(event: &'a s2n_tls_sys::s2n_event_connection_close) -> Self {
+        Self(event)
+    }
+
+    /// The number of bytes read from the transport, including record framing.
+    pub fn bytes_in(&self) -> u64 {
+        self.0.wire_bytes_in
+    }
+
+    /// The number of bytes written to the transport, including record framing.
+    pub fn bytes_out(&self) -> u64 {
+        self.0.wire_bytes_out
+    }
+
+    /// The time from the first call to s2n_negotiate until the connection was
+    /// closed.
+    pub fn duration(&self) -> Duration {
+        Duration::from_nanos(
+            self.0
+                .connection_end_ns
+                .saturating_sub(self.0.connection_start_ns),
+        )
+    }
+}
+
+impl Debug for ConnectionCloseEvent<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConnectionCloseEvent")
+            .field("bytes_in", &self.bytes_in())
+            .field("bytes_out", &self.bytes_out())
+            .field("duration", &self.duration())
+            .finish()
+    }
+}
+
+/// Emitted for each alert sent or received, including close_notify.
+///
+/// The event is only valid for the duration of the callback invocation.
+pub struct AlertEvent<'a>(&'a s2n_tls_sys::s2n_event_alert);
+
+impl<'a> AlertEvent<'a> {
+    pub(crate) fn new(event: &'a s2n_tls_sys::s2n_event_alert) -> Self {
+        Self(event)
+    }
+
+    pub fn direction(&self) -> Direction {
+        Direction::from_sent(self.0.sent)
+    }
+
+    /// The alert level: 1 = warning, 2 = fatal.
+    pub fn level(&self) -> u8 {
+        self.0.level
+    }
+
+    pub fn is_fatal(&self) -> bool {
+        self.0.level == 2
+    }
+
+    /// The alert description, e.g. 0 = close_notify, 40 = handshake_failure.
+    pub fn description(&self) -> u8 {
+        self.0.description
+    }
+}
+
+impl Debug for AlertEvent<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AlertEvent")
+            .field("direction", &self.direction())
+            .field("level", &self.level())
+            .field("description", &self.description())
+            .finish()
+    }
+}
+
+/// Emitted for each TLS 1.3 KeyUpdate message sent or received.
+///
+/// The event is only valid for the duration of the callback invocation.
+pub struct KeyUpdateEvent<'a>(&'a s2n_tls_sys::s2n_event_key_update);
+
+impl<'a> KeyUpdateEvent<'a> {
+    pub(crate) fn new(event: &'a s2n_tls_sys::s2n_event_key_update) -> Self {
+        Self(event)
+    }
+
+    pub fn direction(&self) -> Direction {
+        Direction::from_sent(self.0.sent)
+    }
+
+    /// Whether the KeyUpdate requested that the receiver also update its
+    /// sending keys.
+    pub fn update_requested(&self) -> bool {
+        self.0.update_requested != 0
+    }
+}
+
+impl Debug for KeyUpdateEvent<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyUpdateEvent")
+            .field("direction", &self.direction())
+            .field("update_requested", &self.update_requested())
+            .finish()
+    }
+}
+
+/// Emitted for each session ticket issued by a server or received by a client.
+///
+/// The event is only valid for the duration of the callback invocation.
+pub struct SessionTicketEvent<'a>(&'a s2n_tls_sys::s2n_event_session_ticket);
+
+impl<'a> SessionTicketEvent<'a> {
+    pub(crate) fn new(event: &'a s2n_tls_sys::s2n_event_session_ticket) -> Self {
+        Self(event)
+    }
+
+    pub fn direction(&self) -> Direction {
+        Direction::from_sent(self.0.sent)
+    }
+
+    /// The ticket lifetime hint.
+    pub fn lifetime(&self) -> Duration {
+        Duration::from_secs(self.0.lifetime_secs.into())
+    }
+}
+
+impl Debug for SessionTicketEvent<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionTicketEvent")
+            .field("direction", &self.direction())
+            .field("lifetime", &self.lifetime())
+            .finish()
+    }
+}
+
 impl<A: EventSubscriber, B: EventSubscriber> EventSubscriber for (A, B) {
     fn on_handshake_event(&self, connection: &Connection, event: &HandshakeEvent) {
         self.0.on_handshake_event(connection, event);
@@ -187,6 +350,26 @@ impl<A: EventSubscriber, B: EventSubscriber> EventSubscriber for (A, B) {
         self.0.on_timing_checkpoint(connection, checkpoint);
         self.1.on_timing_checkpoint(connection, checkpoint);
     }
+
+    fn on_connection_close(&self, connection: &Connection, event: &ConnectionCloseEvent) {
+        self.0.on_connection_close(connection, event);
+        self.1.on_connection_close(connection, event);
+    }
+
+    fn on_alert(&self, connection: &Connection, event: &AlertEvent) {
+        self.0.on_alert(connection, event);
+        self.1.on_alert(connection, event);
+    }
+
+    fn on_key_update(&self, connection: &Connection, event: &KeyUpdateEvent) {
+        self.0.on_key_update(connection, event);
+        self.1.on_key_update(connection, event);
+    }
+
+    fn on_session_ticket(&self, connection: &Connection, event: &SessionTicketEvent) {
+        self.0.on_session_ticket(connection, event);
+        self.1.on_session_ticket(connection, event);
+    }
 }
 
 pub trait EventSubscriber: 'static + Send + Sync {
@@ -202,6 +385,30 @@ pub trait EventSubscriber: 'static + Send + Sync {
     ///
     /// The default implementation is a no-op.
     fn on_timing_checkpoint(&self, _connection: &Connection, _checkpoint: &TimingCheckpoint) {}
+
+    /// Called once when a connection that called s2n_negotiate is dropped or
+    /// wiped, whether or not it was shut down cleanly.
+    ///
+    /// The connection is being torn down, so the subscriber should only read
+    /// from it.
+    ///
+    /// The default implementation is a no-op.
+    fn on_connection_close(&self, _connection: &Connection, _event: &ConnectionCloseEvent) {}
+
+    /// Called for each alert sent or received.
+    ///
+    /// The default implementation is a no-op.
+    fn on_alert(&self, _connection: &Connection, _event: &AlertEvent) {}
+
+    /// Called for each TLS 1.3 KeyUpdate sent or received.
+    ///
+    /// The default implementation is a no-op.
+    fn on_key_update(&self, _connection: &Connection, _event: &KeyUpdateEvent) {}
+
+    /// Called for each session ticket issued or received.
+    ///
+    /// The default implementation is a no-op.
+    fn on_session_ticket(&self, _connection: &Connection, _event: &SessionTicketEvent) {}
 }
 
 #[cfg(test)]
@@ -461,18 +668,25 @@ mod tests {
         #[derive(Debug, Default)]
         struct TestErrorSubscriber {
             error_code: Arc<Mutex<Option<i32>>>,
+            error: Arc<Mutex<Option<S2NError>>>,
+            last_message: Arc<Mutex<Option<String>>>,
         }
 
         impl EventSubscriber for TestErrorSubscriber {
-            fn on_handshake_event(&self, _conn: &Connection, event: &HandshakeEvent) {
+            fn on_handshake_event(&self, conn: &Connection, event: &HandshakeEvent) {
                 if let HandshakeResult::Failure(failure) = event.result() {
                     *self.error_code.lock().unwrap() = Some(failure.error_code());
+                    *self.error.lock().unwrap() = Some(failure.error());
+                    *self.last_message.lock().unwrap() =
+                        conn.last_message_name().ok().map(str::to_owned);
                 }
             }
         }
 
         let subscriber = TestErrorSubscriber::default();
         let error_code = subscriber.error_code.clone();
+        let error = subscriber.error.clone();
+        let last_message = subscriber.last_message.clone();
 
         let server_config = {
             // doesn't allow TLS 1.3
@@ -505,6 +719,14 @@ mod tests {
         };
         assert_eq!(event_error_name, server_err.name());
 
+        let error = error.lock().unwrap().take().unwrap();
+        assert_eq!(error.name(), server_err.name());
+        assert_eq!(error.kind(), server_err.kind());
+
+        // the server failed while processing the ClientHello
+        let last_message = last_message.lock().unwrap().take();
+        assert_eq!(last_message.as_deref(), Some("CLIENT_HELLO"));
+
         Ok(())
     }
 
@@ -568,4 +790,136 @@ mod tests {
 
         Ok(())
     }
+
+    /// Post-handshake lifecycle events are emitted from both endpoints, and a
+    /// close event is emitted once for each connection when it is dropped.
+    #[test]
+    fn lifecycle_events() -> Result<(), S2NError> {
+        #[derive(Debug, Default)]
+        struct LifecycleSubscriber {
+            events: Arc<Mutex<Vec<String>>>,
+        }
+
+        impl LifecycleSubscriber {
+            fn record(&self, conn: &Connection, event: String) {
+                let event = format!("{:?} {event}", conn.mode());
+                self.events.lock().unwrap().push(event);
+            }
+        }
+
+        impl EventSubscriber for LifecycleSubscriber {
+            fn on_handshake_event(&self, _conn: &Connection, _event: &HandshakeEvent) {}
+
+            fn on_connection_close(&self, conn: &Connection, event: &ConnectionCloseEvent) {
+                assert!(event.bytes_in() > 0);
+                assert!(event.bytes_out() > 0);
+                assert!(event.duration() > Duration::ZERO);
+                self.record(conn, "close".to_owned());
+            }
+
+            fn on_alert(&self, conn: &Connection, event: &AlertEvent) {
+                // close_notify
+                assert_eq!(event.description(), 0);
+                assert!(!event.is_fatal());
+                self.record(conn, format!("alert {:?}", event.direction()));
+            }
+
+            fn on_key_update(&self, conn: &Connection, event: &KeyUpdateEvent) {
+                assert!(!event.update_requested());
+                self.record(conn, format!("key_update {:?}", event.direction()));
+            }
+
+            fn on_session_ticket(&self, conn: &Connection, event: &SessionTicketEvent) {
+                assert!(event.lifetime() > Duration::ZERO);
+                self.record(conn, format!("session_ticket {:?}", event.direction()));
+            }
+        }
+
+        let subscriber = LifecycleSubscriber::default();
+        let events = subscriber.events.clone();
+
+        let config = {
+            let mut builder = config_builder(&DEFAULT_TLS13).unwrap();
+            builder
+                .set_event_subscriber(subscriber)?
+                .add_session_ticket_key(
+                    b"a key name",
+                    b"good enough bytes for test",
+                    SystemTime::UNIX_EPOCH,
+                )?
+                .enable_session_tickets(true)?;
+            builder.build()?
+        };
+
+        let mut pair = TestPair::from_config(&config);
+        pair.client.set_waker(Some(&noop_waker()))?;
+        pair.handshake().unwrap();
+
+        // the client reads the session ticket, then the key update, then the data
+        pair.server
+            .request_key_update(crate::enums::PeerKeyUpdate::KeyUpdateNotRequested)?;
+        assert!(pair.server.poll_send(&[1]).is_ready());
+        assert!(pair.client.poll_recv(&mut [0]).is_ready());
+
+        assert!(pair.server.poll_shutdown_send().is_ready());
+        let _ = pair.client.poll_recv(&mut [0]);
+        drop(pair);
+
+        let mut events = events.lock().unwrap().clone();
+        events.sort();
+        assert_eq!(
+            events,
+            [
+                "Client alert Received",
+                "Client close",
+                "Client key_update Received",
+                "Client session_ticket Received",
+                "Server alert Sent",
+                "Server close",
+                "Server key_update Sent",
+                "Server session_ticket Sent",
+            ]
+        );
+
+        Ok(())
+    }
+
+    /// Every event of a connection is reported with the connection's id.
+    #[test]
+    fn connection_id() -> Result<(), S2NError> {
+        #[derive(Debug, Default)]
+        struct IdSubscriber {
+            ids: Arc<Mutex<Vec<usize>>>,
+        }
+
+        impl EventSubscriber for IdSubscriber {
+            fn on_handshake_event(&self, conn: &Connection, _event: &HandshakeEvent) {
+                self.ids.lock().unwrap().push(conn.id());
+            }
+
+            fn on_timing_checkpoint(&self, conn: &Connection, _checkpoint: &TimingCheckpoint) {
+                self.ids.lock().unwrap().push(conn.id());
+            }
+        }
+
+        let subscriber = IdSubscriber::default();
+        let ids = subscriber.ids.clone();
+        let server_config = {
+            let mut builder = config_builder(&DEFAULT_TLS13).unwrap();
+            builder.set_event_subscriber(subscriber)?;
+            builder.build()?
+        };
+        let client_config = build_config(&DEFAULT_TLS13).unwrap();
+
+        let mut pair = TestPair::from_configs(&client_config, &server_config);
+        pair.client.set_waker(Some(&noop_waker()))?;
+        pair.handshake().unwrap();
+        assert_ne!(pair.client.id(), pair.server.id());
+
+        let ids = ids.lock().unwrap();
+        assert!(ids.len() > 1);
+        assert!(ids.iter().all(|id| *id == pair.server.id()));
+
+        Ok(())
+    }
 }
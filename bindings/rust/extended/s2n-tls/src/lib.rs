@@ -33,6 +33,8 @@ pub mod fingerprint;
 pub mod init;
 pub mod pool;
 pub mod psk;
+#[cfg(feature = "quic")]
+pub mod quic;
 #[cfg(feature = "unstable-renegotiate")]
 pub mod renegotiate;
 pub mod security;
@@ -0,0 +1,635 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A safe adapter for running the TLS handshake of a QUIC connection.
+//!
+//! QUIC ([RFC 9001]) carries TLS handshake messages in CRYPTO frames rather
+//! than TLS records, and protects packets with keys derived from the TLS
+//! traffic secrets. [`QuicTlsSession`] wraps a [`Connection`] with QUIC
+//! enabled, and exchanges handshake bytes and secrets with the QUIC stack
+//! instead of performing IO itself:
+//!
+//! 1. CRYPTO frame data received from the peer is provided with
+//!    [`QuicTlsSession::receive_handshake_data`].
+//! 2. [`QuicTlsSession::poll_handshake`] drives the handshake.
+//! 3. Handshake data to send in CRYPTO frames is retrieved with
+//!    [`QuicTlsSession::take_handshake_data`], and new secrets are retrieved
+//!    with [`QuicTlsSession::poll_event`].
+//!
+//! The QUIC APIs are considered experimental by s2n-tls, and are subject to
+//! change.
+//!
+//! [RFC 9001]: https://www.rfc-editor.org/rfc/rfc9001
+
+use crate::{
+    connection::Connection,
+    enums::Mode,
+    error::Error,
+    utilities::{cstr_to_str, set_io_would_block},
+};
+use alloc::collections::VecDeque;
+use core::{cell::RefCell, fmt, pin::Pin, task::Poll};
+use libc::{c_int, c_void};
+use s2n_tls_sys::*;
+
+/// The QUIC transport error code for an internal error.
+///
+/// See <https://www.rfc-editor.org/rfc/rfc9000#section-20.1>.
+pub const INTERNAL_ERROR: u64 = 0x01;
+
+/// The base of the QUIC transport error codes reserved for TLS alerts. The
+/// error code for an alert is `CRYPTO_ERROR + alert`.
+///
+/// See <https://www.rfc-editor.org/rfc/rfc9001#section-4.8>.
+pub const CRYPTO_ERROR: u64 = 0x100;
+
+/// The QUIC encryption levels.
+///
+/// Handshake data is exchanged at the `Initial`, `Handshake`, and
+/// `Application` levels. `EarlyData` (0-RTT) keys only protect application
+/// data.
+#[non_exhaustive]
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum EncryptionLevel {
+    Initial,
+    EarlyData,
+    Handshake,
+    Application,
+}
+
+impl EncryptionLevel {
+    /// The index of the level's handshake data buffers.
+    fn crypto_index(self) -> Option<usize> {
+        match self {
+            Self::Initial => Some(0),
+            Self::EarlyData => None,
+            Self::Handshake => Some(1),
+            Self::Application => Some(2),
+        }
+    }
+}
+
+/// Whether a secret protects packets sent or received by this endpoint.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum Direction {
+    Read,
+    Write,
+}
+
+/// Secret bytes, which are zeroed when dropped.
+///
+/// The `Debug` implementation does not print the secret.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret(Vec<u8>);
+
+impl Secret {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Secret").field(&"<redacted>").finish()
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        for byte in self.0.iter_mut() {
+            // SAFETY: the pointer is valid and aligned because it comes from a
+            // mutable reference. A volatile write prevents the zeroing from
+            // being optimized away.
+            unsafe { core::ptr::write_volatile(byte, 0) };
+        }
+        core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+/// A traffic secret used to derive the packet protection keys for one
+/// direction of an encryption level.
+#[derive(Debug, Clone)]
+pub struct TrafficSecret {
+    level: EncryptionLevel,
+    direction: Direction,
+    cipher_suite: &'static str,
+    secret: Secret,
+}
+
+impl TrafficSecret {
+    pub fn level(&self) -> EncryptionLevel {
+        self.level
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// The IANA name of the negotiated cipher suite, which determines the
+    /// AEAD and hash used to derive the packet protection keys.
+    pub fn cipher_suite(&self) -> &str {
+        self.cipher_suite
+    }
+
+    pub fn secret(&self) -> &Secret {
+        &self.secret
+    }
+}
+
+/// Events produced by a [`QuicTlsSession`] while it handshakes.
+#[non_exhaustive]
+#[derive(Debug, Clone)]
+pub enum QuicTlsEvent {
+    /// A new traffic secret is available. Packets at the secret's level may
+    /// now be protected or unprotected in the secret's direction.
+    TrafficSecret(TrafficSecret),
+    /// The exporter secret is available.
+    ExporterSecret(Secret),
+    /// The TLS handshake is complete.
+    HandshakeComplete,
+}
+
+/// An error that occurred during the TLS handshake of a QUIC connection.
+#[derive(Debug)]
+pub struct QuicTlsError {
+    error: Error,
+}
+
+impl QuicTlsError {
+    /// The underlying s2n-tls error.
+    pub fn error(&self) -> &Error {
+        &self.error
+    }
+
+    /// The TLS alert that corresponds to the error, if s2n-tls can map the
+    /// error to an alert.
+    pub fn alert(&self) -> Option<u8> {
+        self.error.alert()
+    }
+
+    /// The QUIC transport error code to close the connection with.
+    ///
+    /// This is `CRYPTO_ERROR + alert` if the error maps to a TLS alert, and
+    /// `INTERNAL_ERROR` otherwise.
+    pub fn transport_error_code(&self) -> u64 {
+        transport_error_code(self.alert())
+    }
+}
+
+impl fmt::Display for QuicTlsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.error)
+    }
+}
+
+impl std::error::Error for QuicTlsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<Error> for QuicTlsError {
+    fn from(error: Error) -> Self {
+        QuicTlsError { error }
+    }
+}
+
+fn transport_error_code(alert: Option<u8>) -> u64 {
+    match alert {
+        Some(alert) => CRYPTO_ERROR + u64::from(alert),
+        None => INTERNAL_ERROR,
+    }
+}
+
+/// The state shared with the connection's IO and secret callbacks.
+#[derive(Debug)]
+struct State {
+    mode: Mode,
+    read_level: EncryptionLevel,
+    write_level: EncryptionLevel,
+    incoming: [VecDeque<u8>; 3],
+    outgoing: [Vec<u8>; 3],
+    events: VecDeque<QuicTlsEvent>,
+}
+
+impl State {
+    fn on_secret(
+        &mut self,
+        secret_type: s2n_secret_type_t::Type,
+        secret: &[u8],
+        cipher_suite: &'static str,
+    ) -> Result<(), Error> {
+        let secret = Secret(secret.to_vec());
+        let (level, sender) = match secret_type {
+            s2n_secret_type_t::CLIENT_EARLY_TRAFFIC_SECRET => {
+                (EncryptionLevel::EarlyData, Mode::Client)
+            }
+            s2n_secret_type_t::CLIENT_HANDSHAKE_TRAFFIC_SECRET => {
+                (EncryptionLevel::Handshake, Mode::Client)
+            }
+            s2n_secret_type_t::SERVER_HANDSHAKE_TRAFFIC_SECRET => {
+                (EncryptionLevel::Handshake, Mode::Server)
+            }
+            s2n_secret_type_t::CLIENT_APPLICATION_TRAFFIC_SECRET => {
+                (EncryptionLevel::Application, Mode::Client)
+            }
+            s2n_secret_type_t::SERVER_APPLICATION_TRAFFIC_SECRET => {
+                (EncryptionLevel::Application, Mode::Server)
+            }
+            s2n_secret_type_t::EXPORTER_SECRET => {
+                self.events.push_back(QuicTlsEvent::ExporterSecret(secret));
+                return Ok(());
+            }
+            _ => return Err(Error::INVALID_INPUT),
+        };
+
+        let direction = if sender == self.mode {
+            Direction::Write
+        } else {
+            Direction::Read
+        };
+
+        // Handshake messages are always exchanged at the most recent level
+        // with keys. 0-RTT packets never carry handshake messages.
+        if level != EncryptionLevel::EarlyData {
+            match direction {
+                Direction::Read => self.read_level = level,
+                Direction::Write => self.write_level = level,
+            }
+        }
+
+        self.events
+            .push_back(QuicTlsEvent::TrafficSecret(TrafficSecret {
+                level,
+                direction,
+                cipher_suite,
+                secret,
+            }));
+        Ok(())
+    }
+}
+
+/// A TLS session for a QUIC connection.
+///
+/// See the [module documentation](crate::quic) for an overview.
+pub struct QuicTlsSession {
+    // The connection holds pointers to `state`, so it must be dropped first.
+    connection: Connection,
+    state: Pin<Box<RefCell<State>>>,
+    complete: bool,
+}
+
+impl fmt::Debug for QuicTlsSession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QuicTlsSession")
+            .field("connection", &self.connection)
+            .field("complete", &self.complete)
+            .finish_non_exhaustive()
+    }
+}
+
+impl QuicTlsSession {
+    /// Creates a session from a connection, which is configured for QUIC.
+    ///
+    /// `transport_parameters` are sent to the peer in the
+    /// quic_transport_parameters extension.
+    ///
+    /// The connection's IO callbacks are replaced, so the connection can't be
+    /// used for other IO.
+    pub fn new(mut connection: Connection, transport_parameters: &[u8]) -> Result<Self, Error> {
+        let state = Box::pin(RefCell::new(State {
+            mode: connection.mode(),
+            read_level: EncryptionLevel::Initial,
+            write_level: EncryptionLevel::Initial,
+            incoming: Default::default(),
+            outgoing: Default::default(),
+            events: VecDeque::new(),
+        }));
+
+        connection
+            .enable_quic()?
+            .set_quic_transport_parameters(transport_parameters)?
+            .set_send_callback(Some(Self::send_cb))?
+            .set_receive_callback(Some(Self::recv_cb))?;
+
+        // SAFETY: the state is pinned and owned by the session, and the
+        // connection is dropped before the state. The connection can't be
+        // moved out of the session, so the callbacks can't be invoked after
+        // the state is dropped. Access is serialized by the RefCell.
+        let context = &*state as *const RefCell<State> as *mut c_void;
+        unsafe {
+            connection
+                .set_send_context(context)?
+                .set_receive_context(context)?
+                .set_secret_callback(Some(Self::secret_cb), context)?;
+        }
+
+        Ok(QuicTlsSession {
+            connection,
+            state,
+            complete: false,
+        })
+    }
+
+    /// The underlying connection.
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    /// Drives the handshake, using any handshake data received from the peer.
+    ///
+    /// Returns `Pending` if more handshake data is needed from the peer.
+    /// After each call, any handshake data to send should be retrieved with
+    /// [`Self::take_handshake_data`], and any new secrets with
+    /// [`Self::poll_event`].
+    ///
+    /// If an error is returned, the QUIC connection should be closed with
+    /// [`QuicTlsError::transport_error_code`].
+    pub fn poll_handshake(&mut self) -> Poll<Result<(), QuicTlsError>> {
+        if self.complete {
+            return Poll::Ready(Ok(()));
+        }
+        match self.connection.poll_negotiate() {
+            Poll::Ready(Ok(_)) => {
+                self.complete = true;
+                self.state
+                    .borrow_mut()
+                    .events
+                    .push_back(QuicTlsEvent::HandshakeComplete);
+                Poll::Ready(Ok(()))
+            }
+            Poll::Ready(Err(error)) => Poll::Ready(Err(error.into())),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    /// Whether the handshake is complete.
+    pub fn is_handshake_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Provides handshake data received from the peer in CRYPTO frames at
+    /// `level`.
+    ///
+    /// Data must be provided in order, without gaps or duplicates.
+    pub fn receive_handshake_data(
+        &mut self,
+        level: EncryptionLevel,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        let index = level.crypto_index().ok_or(Error::INVALID_INPUT)?;
+        self.state.borrow_mut().incoming[index].extend(data);
+        Ok(())
+    }
+
+    /// Takes the handshake data to send to the peer in CRYPTO frames at
+    /// `level`.
+    pub fn take_handshake_data(&mut self, level: EncryptionLevel) -> Vec<u8> {
+        match level.crypto_index() {
+            Some(index) => core::mem::take(&mut self.state.borrow_mut().outgoing[index]),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns the next event produced by the session, if any.
+    pub fn poll_event(&mut self) -> Option<QuicTlsEvent> {
+        self.state.borrow_mut().events.pop_front()
+    }
+
+    /// The encryption level that the session expects to receive handshake
+    /// data at.
+    pub fn read_level(&self) -> EncryptionLevel {
+        self.state.borrow().read_level
+    }
+
+    /// The encryption level that the session currently sends handshake data
+    /// at.
+    pub fn write_level(&self) -> EncryptionLevel {
+        self.state.borrow().write_level
+    }
+
+    /// The transport parameters sent by the peer.
+    pub fn peer_transport_parameters(&mut self) -> Result<&[u8], Error> {
+        self.connection.quic_transport_parameters()
+    }
+
+    /// Processes the post-handshake messages, such as session tickets, that
+    /// have been received at the `Application` level.
+    pub fn process_post_handshake_data(&mut self) -> Result<(), QuicTlsError> {
+        while !self.state.borrow().incoming[2].is_empty() {
+            match self.connection.quic_process_post_handshake_message() {
+                Ok(_) => continue,
+                // The rest of the message hasn't been received yet.
+                Err(error) if error.is_retryable() => break,
+                Err(error) => return Err(error.into()),
+            }
+        }
+        Ok(())
+    }
+
+    unsafe extern "C" fn send_cb(context: *mut c_void, data: *const u8, len: u32) -> c_int {
+        let state = &*(context as *const RefCell<State>);
+        let data = core::slice::from_raw_parts(data, len as _);
+        let mut state = state.borrow_mut();
+        // The write level is never `EarlyData`.
+        let index = state.write_level.crypto_index().unwrap_or(0);
+        state.outgoing[index].extend_from_slice(data);
+        len as c_int
+    }
+
+    unsafe extern "C" fn recv_cb(context: *mut c_void, data: *mut u8, len: u32) -> c_int {
+        let state = &*(context as *const RefCell<State>);
+        let data = core::slice::from_raw_parts_mut(data, len as _);
+        let mut state = state.borrow_mut();
+        let index = state.read_level.crypto_index().unwrap_or(0);
+        let incoming = &mut state.incoming[index];
+
+        let count = data.len().min(incoming.len());
+        if count == 0 {
+            // Returning 0 would indicate that the peer closed the stream.
+            set_io_would_block();
+            return -1;
+        }
+        for (dest, src) in data.iter_mut().zip(incoming.drain(..count)) {
+            *dest = src;
+        }
+        count as c_int
+    }
+
+    unsafe extern "C" fn secret_cb(
+        context: *mut c_void,
+        conn: *mut s2n_connection,
+        secret_type: s2n_secret_type_t::Type,
+        secret: *mut u8,
+        secret_size: u8,
+    ) -> c_int {
+        let state = &*(context as *const RefCell<State>);
+        let secret = core::slice::from_raw_parts(secret, secret_size as _);
+
+        // The cipher suite is negotiated before any traffic secrets are
+        // derived. Cipher suite names are static strings.
+        let cipher = s2n_connection_get_cipher(conn);
+        let cipher_suite = if cipher.is_null() {
+            ""
+        } else {
+            cstr_to_str(cipher)
+        };
+
+        match state
+            .borrow_mut()
+            .on_secret(secret_type, secret, cipher_suite)
+        {
+            Ok(()) => s2n_status_code::SUCCESS,
+            Err(_) => s2n_status_code::FAILURE,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{connection::Builder, enums::Blinding, security, testing};
+
+    const CLIENT_PARAMS: &[u8] = &[1, 2, 3];
+    const SERVER_PARAMS: &[u8] = &[4, 5, 6, 7];
+    const LEVELS: [EncryptionLevel; 3] = [
+        EncryptionLevel::Initial,
+        EncryptionLevel::Handshake,
+        EncryptionLevel::Application,
+    ];
+
+    fn sessions() -> Result<(QuicTlsSession, QuicTlsSession), Box<dyn std::error::Error>> {
+        let config = testing::build_config(&security::DEFAULT_TLS13)?;
+        let session = |mode, transport_parameters| {
+            let mut connection = config.build_connection(mode)?;
+            // Avoid blinding delays when the handshake fails.
+            connection.set_blinding(Blinding::SelfService)?;
+            QuicTlsSession::new(connection, transport_parameters)
+        };
+        Ok((
+            session(Mode::Client, CLIENT_PARAMS)?,
+            session(Mode::Server, SERVER_PARAMS)?,
+        ))
+    }
+
+    /// Moves the handshake data written by `from` to `to`.
+    fn transfer(from: &mut QuicTlsSession, to: &mut QuicTlsSession) -> Result<(), Error> {
+        for level in LEVELS {
+            let data = from.take_handshake_data(level);
+            to.receive_handshake_data(level, &data)?;
+        }
+        Ok(())
+    }
+
+    fn drain_secrets(session: &mut QuicTlsSession) -> Vec<TrafficSecret> {
+        core::iter::from_fn(|| session.poll_event())
+            .filter_map(|event| match event {
+                QuicTlsEvent::TrafficSecret(secret) => Some(secret),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn handshake() -> Result<(), Box<dyn std::error::Error>> {
+        let (mut client, mut server) = sessions()?;
+        let mut client_secrets = Vec::new();
+        let mut server_secrets = Vec::new();
+
+        // The handshake completes within a bounded number of flights.
+        for _ in 0..10 {
+            let client_result = client.poll_handshake();
+            transfer(&mut client, &mut server)?;
+            client_secrets.extend(drain_secrets(&mut client));
+
+            let server_result = server.poll_handshake();
+            transfer(&mut server, &mut client)?;
+            server_secrets.extend(drain_secrets(&mut server));
+
+            if let (Poll::Ready(client_result), Poll::Ready(server_result)) =
+                (client_result, server_result)
+            {
+                client_result?;
+                server_result?;
+                break;
+            }
+        }
+        assert!(client.is_handshake_complete());
+        assert!(server.is_handshake_complete());
+
+        assert_eq!(client.peer_transport_parameters()?, SERVER_PARAMS);
+        assert_eq!(server.peer_transport_parameters()?, CLIENT_PARAMS);
+
+        assert_eq!(client.read_level(), EncryptionLevel::Application);
+        assert_eq!(client.write_level(), EncryptionLevel::Application);
+        assert_eq!(server.read_level(), EncryptionLevel::Application);
+        assert_eq!(server.write_level(), EncryptionLevel::Application);
+
+        // Each secret that one peer writes with is read with by the other.
+        for level in [EncryptionLevel::Handshake, EncryptionLevel::Application] {
+            for (writer, reader) in [
+                (&client_secrets, &server_secrets),
+                (&server_secrets, &client_secrets),
+            ] {
+                let find = |secrets: &[TrafficSecret], direction| {
+                    secrets
+                        .iter()
+                        .find(|s| s.level() == level && s.direction() == direction)
+                        .cloned()
+                        .unwrap()
+                };
+                let write = find(writer, Direction::Write);
+                let read = find(reader, Direction::Read);
+                assert_eq!(write.secret(), read.secret());
+                assert_eq!(write.cipher_suite(), client.connection().cipher_suite()?);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn handshake_error() -> Result<(), Box<dyn std::error::Error>> {
+        let (mut client, mut server) = sessions()?;
+        assert!(client.poll_handshake().is_pending());
+
+        // Corrupt the ClientHello.
+        let mut client_hello = client.take_handshake_data(EncryptionLevel::Initial);
+        client_hello[0] = 0xFF;
+        server.receive_handshake_data(EncryptionLevel::Initial, &client_hello)?;
+
+        let Poll::Ready(Err(error)) = server.poll_handshake() else {
+            panic!("the handshake should fail");
+        };
+        // An unknown handshake message type is an unexpected_message alert.
+        assert_eq!(error.alert(), Some(10));
+        assert_eq!(error.transport_error_code(), 0x100 + 10);
+
+        Ok(())
+    }
+
+    #[test]
+    fn early_data_has_no_handshake_data() -> Result<(), Box<dyn std::error::Error>> {
+        let (mut client, _) = sessions()?;
+        assert!(client
+            .receive_handshake_data(EncryptionLevel::EarlyData, &[1])
+            .is_err());
+        assert!(client
+            .take_handshake_data(EncryptionLevel::EarlyData)
+            .is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn transport_error_codes() {
+        assert_eq!(transport_error_code(None), INTERNAL_ERROR);
+        // handshake_failure
+        assert_eq!(transport_error_code(Some(40)), 0x128);
+    }
+
+    #[test]
+    fn secret_debug_redacted() {
+        let secret = Secret(vec![0xAB; 32]);
+        assert!(!format!("{secret:?}").contains("171"));
+    }
+}
@@ -189,7 +189,11 @@ impl Connection {
         }
     }
 
-    fn is_renegotiating(&self) -> bool {
+    /// Returns true if a scheduled renegotiation has started but not completed.
+    ///
+    /// While renegotiating, [`Connection::poll_send()`] fails and only
+    /// `poll_recv` makes progress on the new handshake.
+    pub fn is_renegotiating(&self) -> bool {
         self.renegotiate_state().needs_handshake
     }
 
@@ -432,7 +436,10 @@ mod tests {
         },
         config::ConnectionInitializer,
         error::{ErrorSource, ErrorType},
-        testing::{CertKeyPair, InsecureAcceptAllCertificatesHandler, TestPair, TestPairIO},
+        security,
+        testing::{
+            build_config, CertKeyPair, InsecureAcceptAllCertificatesHandler, TestPair, TestPairIO,
+        },
     };
     use foreign_types::ForeignTypeRef;
     use futures_test::task::new_count_waker;
@@ -1126,6 +1133,14 @@ mod tests {
         let error = connection.wipe_for_renegotiate().unwrap_err();
         assert_eq!(error.source(), ErrorSource::Library);
         assert_eq!(error.name(), "S2N_ERR_NO_RENEGOTIATION");
+
+        // The connection is still usable after the failure.
+        let config = build_config(&security::DEFAULT_TLS13)?;
+        connection.set_config(config.clone())?;
+        let mut client = Connection::new_client();
+        client.set_config(config)?;
+        let mut pair = TestPair::from_connections(client, connection);
+        pair.handshake()?;
         Ok(())
     }
 }
@@ -237,6 +237,12 @@ pub struct TestPairIO {
 /// the Harness. If the Harness goes out of scope, the data buffers will be dropped
 /// and the pointers will be invalid.
 ///
+/// Handshake transcripts are not reproducible: s2n-tls gets its randomness
+/// directly from the libcrypto (or /dev/urandom), and `s2n_rand_set_callbacks`
+/// is a deprecated no-op, so there is no way to substitute a deterministic
+/// random source. Tests should assert on negotiated parameters rather than
+/// comparing transcripts byte-for-byte.
+///
 /// The most common usecase is handshaking a simple config.
 /// ```ignore
 /// // given some config
@@ -404,31 +410,7 @@ impl TestPair {
     }
 }
 
-/// Signal a "would block" to s2n's C IO layer by setting the CRT `errno` to
-/// EWOULDBLOCK. `s2n_io.c` reads `errno` to distinguish a retriable blocked
-/// read/write from a fatal IO error. Shared by this crate's test IO callbacks.
-pub(crate) fn set_io_would_block() {
-    #[cfg(not(target_os = "windows"))]
-    {
-        // The `errno` crate writes the CRT errno, which is what s2n reads.
-        errno::set_errno(errno::Errno(libc::EWOULDBLOCK));
-    }
-
-    // On Windows the `errno` crate writes the Win32 last-error, not the CRT
-    // `errno` that s2n reads, so set the CRT errno directly. s2n and this code
-    // share one statically linked CRT, so `_set_errno` and `errno` hit the same
-    // thread-local variable.
-    #[cfg(target_os = "windows")]
-    {
-        extern "C" {
-            fn _set_errno(value: core::ffi::c_int) -> core::ffi::c_int;
-        }
-        // SAFETY: `_set_errno` only writes the thread-local CRT errno.
-        unsafe {
-            let _ = _set_errno(libc::EWOULDBLOCK);
-        }
-    }
-}
+pub(crate) use crate::utilities::set_io_would_block;
 
 type SessionState = Vec<u8>;
 
@@ -701,6 +701,56 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(feature = "unstable-npn")]
+    #[test]
+    fn npn() -> Result<(), Error> {
+        use crate::enums::ProtocolNegotiation;
+
+        let config = {
+            let mut config = config_builder(&security::TESTING_TLS12)?;
+            config.set_npn(true)?;
+            config.build()?
+        };
+
+        // With a common protocol, ALPN is preferred over NPN.
+        let mut pair = TestPair::from_config(&config);
+        pair.server
+            .set_application_protocol_preference(["http/1.1", "spdy/3"])?;
+        pair.client
+            .set_application_protocol_preference(["spdy/3"])?;
+        pair.handshake()?;
+        for conn in [&pair.client, &pair.server] {
+            assert_eq!(conn.application_protocol(), Some(b"spdy/3".as_slice()));
+            assert_eq!(
+                conn.application_protocol_negotiation()?,
+                Some(ProtocolNegotiation::Alpn)
+            );
+        }
+
+        // Without a common protocol, ALPN fails and the NPN client selects its
+        // own preferred protocol.
+        let mut pair = TestPair::from_config(&config);
+        pair.server
+            .set_application_protocol_preference(["http/1.1"])?;
+        pair.client
+            .set_application_protocol_preference(["spdy/3"])?;
+        pair.handshake()?;
+        for conn in [&pair.client, &pair.server] {
+            assert_eq!(conn.application_protocol(), Some(b"spdy/3".as_slice()));
+            assert_eq!(
+                conn.application_protocol_negotiation()?,
+                Some(ProtocolNegotiation::Npn)
+            );
+        }
+
+        // No protocol is reported if neither extension is used.
+        let mut pair = TestPair::from_config(&config);
+        pair.handshake()?;
+        assert_eq!(pair.server.application_protocol_negotiation()?, None);
+
+        Ok(())
+    }
+
     #[test]
     fn client_hello_sslv2_negative() -> Result<(), testing::Error> {
         let config = testing::build_config(&security::DEFAULT_TLS13)?;
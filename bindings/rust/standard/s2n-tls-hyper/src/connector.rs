@@ -1,7 +1,12 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{error::Error, stream::MaybeHttpsStream};
+use crate::{
+    error::Error,
+    proxy::Proxy,
+    resolver::ConfigResolver,
+    stream::{MaybeHttpsStream, TunnelStream},
+};
 use http::uri::Uri;
 use hyper::rt::{Read, Write};
 use hyper_util::{
@@ -13,6 +18,7 @@ use s2n_tls_tokio::TlsConnector;
 use std::{
     future::Future,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
 };
 use tower_service::Service;
@@ -28,6 +34,9 @@ pub struct HttpsConnector<Http, ConnBuilder = Config> {
     http: Http,
     conn_builder: ConnBuilder,
     plaintext_http: bool,
+    alpn: AlpnPolicy,
+    proxy: Option<Proxy>,
+    resolver: Option<Arc<dyn ConfigResolver<ConnBuilder>>>,
 }
 
 impl<ConnBuilder> HttpsConnector<HttpConnector, ConnBuilder>
@@ -44,7 +53,8 @@ where
     ///
     /// Note that s2n-tls-hyper will override the ALPN extension to negotiate HTTP. Any ALPN values
     /// configured on `conn_builder` with APIs like
-    /// `s2n_tls::config::Builder::set_application_protocol_preference()` will be ignored.
+    /// `s2n_tls::config::Builder::set_application_protocol_preference()` will be ignored, unless
+    /// `Builder::with_application_protocols()` is used to keep them.
     pub fn new(conn_builder: ConnBuilder) -> HttpsConnector<HttpConnector, ConnBuilder> {
         HttpsConnector::builder(conn_builder).build()
     }
@@ -59,7 +69,8 @@ where
     ///
     /// Note that s2n-tls-hyper will override the ALPN extension to negotiate HTTP. Any ALPN values
     /// configured on `conn_builder` with APIs like
-    /// `s2n_tls::config::Builder::set_application_protocol_preference()` will be ignored.
+    /// `s2n_tls::config::Builder::set_application_protocol_preference()` will be ignored, unless
+    /// `Builder::with_application_protocols()` is used to keep them.
     pub fn builder(conn_builder: ConnBuilder) -> Builder<HttpConnector, ConnBuilder> {
         let mut http = HttpConnector::new();
 
@@ -100,12 +111,16 @@ where
     ///
     /// Note that s2n-tls-hyper will override the ALPN extension to negotiate HTTP. Any ALPN values
     /// configured on `conn_builder` with APIs like
-    /// `s2n_tls::config::Builder::set_application_protocol_preference()` will be ignored.
+    /// `s2n_tls::config::Builder::set_application_protocol_preference()` will be ignored, unless
+    /// `Builder::with_application_protocols()` is used to keep them.
     pub fn builder_with_http(http: Http, conn_builder: ConnBuilder) -> Builder<Http, ConnBuilder> {
         Builder {
             http,
             conn_builder,
             plaintext_http: false,
+            alpn: AlpnPolicy::Auto,
+            proxy: None,
+            resolver: None,
         }
     }
 }
@@ -116,6 +131,9 @@ pub struct Builder<Http, ConnBuilder> {
     http: Http,
     conn_builder: ConnBuilder,
     plaintext_http: bool,
+    alpn: AlpnPolicy,
+    proxy: Option<Proxy>,
+    resolver: Option<Arc<dyn ConfigResolver<ConnBuilder>>>,
 }
 
 impl<Http, ConnBuilder> Builder<Http, ConnBuilder> {
@@ -126,12 +144,141 @@ impl<Http, ConnBuilder> Builder<Http, ConnBuilder> {
         self
     }
 
+    /// Connects to HTTPS endpoints through an HTTP proxy, by tunneling with the CONNECT method
+    /// (default: no proxy).
+    ///
+    /// See `s2n_tls_hyper::proxy::Proxy::from_env()` to use the proxy configured by environment
+    /// variables.
+    pub fn with_proxy(&mut self, proxy: Proxy) -> &mut Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Selects the connection builder for each connection with `resolver`, rather than always
+    /// using the `conn_builder` the `HttpsConnector` was created with (default: no resolver).
+    ///
+    /// `conn_builder` is still used for connections that the resolver doesn't select a connection
+    /// builder for. See `s2n_tls_hyper::resolver::HostRules` for a resolver that matches on the
+    /// URI host.
+    pub fn with_config_resolver<R>(&mut self, resolver: R) -> &mut Self
+    where
+        R: ConfigResolver<ConnBuilder> + 'static,
+    {
+        self.resolver = Some(Arc::new(resolver));
+        self
+    }
+
+    /// Only negotiate HTTP/1.x, by offering `http/1.1` and `http/1.0` in the ALPN extension.
+    ///
+    /// By default, HTTP/2 is also offered, and is preferred over HTTP/1.x.
+    pub fn with_http1_only(&mut self) -> &mut Self {
+        self.alpn = AlpnPolicy::Http1Only;
+        self
+    }
+
+    /// Only negotiate HTTP/2, by offering `h2` in the ALPN extension.
+    ///
+    /// The connection fails with `Error::UnexpectedApplicationProtocol` if the server does not
+    /// select `h2`.
+    pub fn with_http2_only(&mut self) -> &mut Self {
+        self.alpn = AlpnPolicy::Http2Only;
+        self
+    }
+
+    /// Use HTTP/2 with prior knowledge that the server supports it.
+    ///
+    /// `h2` is still offered in the ALPN extension, but the connection is also allowed if the
+    /// server does not negotiate ALPN. The connection fails with
+    /// `Error::UnexpectedApplicationProtocol` if the server selects any other protocol.
+    ///
+    /// hyper only uses HTTP/2 without ALPN if the client is configured to, so this should be
+    /// combined with `hyper_util::client::legacy::Builder::http2_only()`.
+    pub fn with_http2_prior_knowledge(&mut self) -> &mut Self {
+        self.alpn = AlpnPolicy::Http2PriorKnowledge;
+        self
+    }
+
+    /// Offer a custom list of protocols in the ALPN extension, in order of preference.
+    ///
+    /// Only `h2`, `http/1.1`, and `http/1.0` are usable by hyper, so the connection fails with
+    /// `Error::UnexpectedApplicationProtocol` if the server selects any other protocol.
+    ///
+    /// An empty list keeps any ALPN values configured on the `conn_builder`.
+    pub fn with_application_protocols<P: IntoIterator<Item = I>, I: AsRef<[u8]>>(
+        &mut self,
+        protocols: P,
+    ) -> &mut Self {
+        let protocols = protocols
+            .into_iter()
+            .map(|protocol| protocol.as_ref().to_vec())
+            .collect();
+        self.alpn = AlpnPolicy::Custom(protocols);
+        self
+    }
+
     /// Builds a new `HttpsConnector`.
     pub fn build(self) -> HttpsConnector<Http, ConnBuilder> {
         HttpsConnector {
             http: self.http,
             conn_builder: self.conn_builder,
             plaintext_http: self.plaintext_http,
+            alpn: self.alpn,
+            proxy: self.proxy,
+            resolver: self.resolver,
+        }
+    }
+}
+
+const H2: &[u8] = b"h2";
+const HTTP_1_1: &[u8] = b"http/1.1";
+const HTTP_1_0: &[u8] = b"http/1.0";
+
+/// The application protocols offered in the ALPN extension, and the protocols accepted from the
+/// server.
+#[derive(Clone, Debug)]
+enum AlpnPolicy {
+    Auto,
+    Http1Only,
+    Http2Only,
+    Http2PriorKnowledge,
+    Custom(Vec<Vec<u8>>),
+}
+
+impl AlpnPolicy {
+    /// The protocols to offer, or `None` to keep the values configured on the connection.
+    fn protocols(&self) -> Option<Vec<Vec<u8>>> {
+        let protocols: &[&[u8]] = match self {
+            // Attempt to negotiate HTTP/2 by including it in the ALPN extension. Other supported
+            // HTTP versions are also included to prevent the server from rejecting the TLS
+            // connection if HTTP/2 isn't supported:
+            //
+            // https://datatracker.ietf.org/doc/html/rfc7301#section-3.2
+            //    In the event that the server supports no
+            //    protocols that the client advertises, then the server SHALL respond
+            //    with a fatal "no_application_protocol" alert.
+            AlpnPolicy::Auto => &[H2, HTTP_1_1, HTTP_1_0],
+            AlpnPolicy::Http1Only => &[HTTP_1_1, HTTP_1_0],
+            AlpnPolicy::Http2Only | AlpnPolicy::Http2PriorKnowledge => &[H2],
+            AlpnPolicy::Custom(protocols) if protocols.is_empty() => return None,
+            AlpnPolicy::Custom(protocols) => return Some(protocols.clone()),
+        };
+        Some(protocols.iter().map(|protocol| protocol.to_vec()).collect())
+    }
+
+    /// Whether the protocol selected by the server can be used.
+    fn accepts(&self, protocol: Option<&[u8]>) -> bool {
+        let is_http1 = |protocol| protocol == HTTP_1_1 || protocol == HTTP_1_0;
+        match (self, protocol) {
+            // Without ALPN, HTTP/1.1 is assumed unless HTTP/2 is known to be supported.
+            (AlpnPolicy::Http2Only, None) => false,
+            (_, None) => true,
+            (AlpnPolicy::Http1Only, Some(protocol)) => is_http1(protocol),
+            (AlpnPolicy::Http2Only | AlpnPolicy::Http2PriorKnowledge, Some(protocol)) => {
+                protocol == H2
+            }
+            (AlpnPolicy::Auto | AlpnPolicy::Custom(_), Some(protocol)) => {
+                protocol == H2 || is_http1(protocol)
+            }
         }
     }
 }
@@ -183,21 +330,19 @@ where
             }
         }
 
-        // Attempt to negotiate HTTP/2 by including it in the ALPN extension. Other supported HTTP
-        // versions are also included to prevent the server from rejecting the TLS connection if
-        // HTTP/2 isn't supported:
-        //
-        // https://datatracker.ietf.org/doc/html/rfc7301#section-3.2
-        //    In the event that the server supports no
-        //    protocols that the client advertises, then the server SHALL respond
-        //    with a fatal "no_application_protocol" alert.
-        let builder = connection::ModifiedBuilder::new(self.conn_builder.clone(), |conn| {
-            conn.set_application_protocol_preference([
-                b"h2".to_vec(),
-                b"http/1.1".to_vec(),
-                b"http/1.0".to_vec(),
-            ])
-        });
+        let conn_builder = self
+            .resolver
+            .as_ref()
+            .and_then(|resolver| resolver.resolve(&req))
+            .unwrap_or_else(|| self.conn_builder.clone());
+
+        let protocols = self.alpn.protocols();
+        let builder =
+            connection::ModifiedBuilder::new(conn_builder, move |conn| match &protocols {
+                Some(protocols) => conn.set_application_protocol_preference(protocols),
+                None => Ok(conn),
+            });
+        let alpn = self.alpn.clone();
 
         // IPv6 addresses are enclosed in square brackets within the host of a URI (e.g.
         // `https://[::1:2:3:4]/`). These square brackets aren't part of the domain itself, so they
@@ -209,8 +354,21 @@ where
             }
         }
         let domain = domain.to_owned();
+        let port = req.port_u16().unwrap_or(443);
 
-        let call = self.http.call(req);
+        // When a proxy is used, the wrapped HTTP connector connects to the proxy instead of the
+        // server. The proxy is then asked to open a tunnel to the server's authority.
+        let proxy = self
+            .proxy
+            .as_ref()
+            .filter(|proxy| proxy.intercepts(&domain, port))
+            .cloned();
+        let authority = format!("{}:{port}", req.host().unwrap_or(""));
+
+        let call = match &proxy {
+            Some(proxy) => self.http.call(proxy.uri().clone()),
+            None => self.http.call(req),
+        };
         Box::pin(async move {
             // `HttpsConnector` wraps an HTTP connector that also implements `Service<Uri>`.
             // `call()` is invoked on the wrapped connector to get the underlying hyper TCP stream,
@@ -218,12 +376,24 @@ where
             let tcp = call.await.map_err(|e| Error::HttpError(e.into()))?;
             let tcp = TokioIo::new(tcp);
 
+            let stream = match proxy {
+                Some(proxy) => proxy.connect(tcp, &authority).await?,
+                None => TunnelStream::Direct(tcp),
+            };
+
             let connector = TlsConnector::new(builder);
             let tls = connector
-                .connect(&domain, tcp)
+                .connect(&domain, stream)
                 .await
                 .map_err(Error::TlsError)?;
 
+            let protocol = tls.as_ref().application_protocol();
+            if !alpn.accepts(protocol) {
+                return Err(Error::UnexpectedApplicationProtocol(
+                    protocol.map(|protocol| protocol.to_vec()),
+                ));
+            }
+
             Ok(MaybeHttpsStream::Https(TokioIo::new(tls)))
         })
     }
@@ -281,7 +451,27 @@ mod tests {
         // Ensure that plaintext HTTP is disabled by default.
         let connector = HttpsConnector::builder(Config::default()).build();
         assert!(!connector.plaintext_http);
+        assert!(matches!(connector.alpn, AlpnPolicy::Auto));
 
         Ok(())
     }
+
+    #[test]
+    fn alpn_policy_accepts() {
+        let h1 = Some(HTTP_1_1);
+        let h2 = Some(H2);
+        let other = Some(b"h3".as_slice());
+
+        let cases = [
+            (AlpnPolicy::Auto, [true, true, true, false]),
+            (AlpnPolicy::Http1Only, [true, true, false, false]),
+            (AlpnPolicy::Http2Only, [false, false, true, false]),
+            (AlpnPolicy::Http2PriorKnowledge, [true, false, true, false]),
+            (AlpnPolicy::Custom(vec![]), [true, true, true, false]),
+        ];
+        for (policy, expected) in cases {
+            let actual = [None, h1, h2, other].map(|protocol| policy.accepts(protocol));
+            assert_eq!(actual, expected, "{policy:?}");
+        }
+    }
 }
@@ -13,6 +13,13 @@ pub enum Error {
     HttpError(Box<dyn std::error::Error + Send + Sync>),
     /// Indicates that an error occurred in s2n-tls.
     TlsError(s2n_tls::error::Error),
+    /// Indicates that the server selected an application protocol via ALPN that the
+    /// `HttpsConnector` isn't configured to use. `None` indicates that no protocol was selected.
+    UnexpectedApplicationProtocol(Option<Vec<u8>>),
+    /// Indicates that a tunnel could not be established through the configured proxy.
+    ProxyError(Box<dyn std::error::Error + Send + Sync>),
+    /// Indicates that the proxy responded to the CONNECT request with an unsuccessful status.
+    ProxyTunnelRefused(http::StatusCode),
 }
 
 impl Display for Error {
@@ -21,6 +28,18 @@ impl Display for Error {
             Error::InvalidScheme => write!(f, "The provided URI contains an invalid scheme."),
             Error::HttpError(err) => write!(f, "{err}"),
             Error::TlsError(err) => write!(f, "{err}"),
+            Error::UnexpectedApplicationProtocol(Some(protocol)) => write!(
+                f,
+                "The server selected an unexpected application protocol: {}",
+                String::from_utf8_lossy(protocol)
+            ),
+            Error::UnexpectedApplicationProtocol(None) => {
+                write!(f, "The server did not select an application protocol.")
+            }
+            Error::ProxyError(err) => write!(f, "Unable to connect through the proxy: {err}"),
+            Error::ProxyTunnelRefused(status) => {
+                write!(f, "The proxy refused to establish a tunnel: {status}")
+            }
         }
     }
 }
@@ -44,4 +44,17 @@ pub mod connector;
 /// Provides errors returned by s2n-tls-hyper.
 pub mod error;
 
+/// Provides the `TlsListener` struct, used to serve axum apps over TLS.
+#[cfg(feature = "axum")]
+pub mod listener;
+
+/// Provides the `Proxy` struct, used to connect through an HTTP proxy.
+pub mod proxy;
+
+/// Provides the `ConfigResolver` trait, used to select a connection builder for each connection.
+pub mod resolver;
+
 mod stream;
+
+/// Provides the `TlsInfo` struct, which exposes the TLS parameters negotiated for a connection.
+pub mod tls_info;
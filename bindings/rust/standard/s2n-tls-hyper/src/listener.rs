@@ -0,0 +1,215 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::tls_info::TlsInfo;
+use axum::{
+    extract::connect_info::Connected,
+    serve::{IncomingStream, Listener},
+};
+use s2n_tls::{config::Config, connection::Builder};
+use s2n_tls_tokio::{TlsAcceptor, TlsStream};
+use std::{io, time::Duration};
+use tokio::task::JoinSet;
+
+/// The default number of handshakes that a `TlsListener` performs concurrently.
+pub const DEFAULT_MAX_HANDSHAKES: usize = 1024;
+
+type Handshake<L, B> = (
+    Result<TlsStream<<L as Listener>::Io, <B as Builder>::Output>, s2n_tls::error::Error>,
+    <L as Listener>::Addr,
+);
+
+/// An `axum::serve::Listener` that negotiates TLS with s2n-tls on each accepted connection.
+///
+/// `TlsListener` wraps another listener, usually a `tokio::net::TcpListener`, and only yields
+/// connections that complete the TLS handshake. Handshakes are performed concurrently, so a slow
+/// client doesn't delay other connections. Connections that fail the handshake are dropped.
+///
+/// To bound the resources that clients can hold without completing a handshake, at most
+/// `DEFAULT_MAX_HANDSHAKES` handshakes are in progress at once, and each handshake times out
+/// after the acceptor's handshake timeout, `s2n_tls_tokio::DEFAULT_HANDSHAKE_TIMEOUT` by default.
+/// While the limit is reached, no more connections are accepted from the wrapped listener. Use
+/// `TlsListener::with_max_handshakes()` and `TlsListener::with_handshake_timeout()` to change
+/// these limits.
+///
+/// ```no_run
+/// use axum::{routing::get, Router};
+/// use s2n_tls::config::Config;
+/// use s2n_tls_hyper::listener::TlsListener;
+/// use s2n_tls_tokio::TlsAcceptor;
+/// use tokio::net::TcpListener;
+///
+/// # async fn run(
+/// #     config: Config,
+/// #     shutdown_signal: impl std::future::Future<Output = ()> + Send + 'static,
+/// # ) -> Result<(), Box<dyn std::error::Error>> {
+/// let tcp = TcpListener::bind("0.0.0.0:443").await?;
+/// let listener = TlsListener::new(tcp, TlsAcceptor::new(config));
+///
+/// let app = Router::new().route("/", get(|| async { "Hello, World!" }));
+/// axum::serve(listener, app)
+///     .with_graceful_shutdown(shutdown_signal)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// During a graceful shutdown, axum stops calling `accept()` and waits for open connections to
+/// finish. Each connection sends a TLS close_notify alert when it's shut down. Handshakes that are
+/// still in progress are cancelled when the `TlsListener` is dropped.
+///
+/// To make the TLS details of each connection available to handlers, serve the app with
+/// `into_make_service_with_connect_info::<TlsConnectInfo<_>>()`.
+pub struct TlsListener<L, B = Config>
+where
+    L: Listener,
+    B: Builder,
+    <B as Builder>::Output: Unpin,
+{
+    inner: L,
+    acceptor: TlsAcceptor<B>,
+    handshakes: JoinSet<Handshake<L, B>>,
+    max_handshakes: usize,
+}
+
+impl<L, B> TlsListener<L, B>
+where
+    L: Listener,
+    B: Builder,
+    <B as Builder>::Output: Unpin,
+{
+    /// Creates a `TlsListener` that accepts connections from `inner`, and negotiates TLS on them
+    /// with `acceptor`.
+    pub fn new(inner: L, acceptor: TlsAcceptor<B>) -> Self {
+        TlsListener {
+            inner,
+            acceptor,
+            handshakes: JoinSet::new(),
+            max_handshakes: DEFAULT_MAX_HANDSHAKES,
+        }
+    }
+
+    /// Sets the maximum number of handshakes in progress at once.
+    ///
+    /// Once the maximum is reached, connections are left in the wrapped listener's backlog until a
+    /// handshake finishes. At least one handshake is always allowed. Defaults to
+    /// `DEFAULT_MAX_HANDSHAKES`.
+    pub fn with_max_handshakes(&mut self, max_handshakes: usize) -> &mut Self {
+        self.max_handshakes = max_handshakes.max(1);
+        self
+    }
+
+    /// Sets the time allowed for each handshake, after which the connection is dropped. `None`
+    /// disables the timeout.
+    ///
+    /// This sets the acceptor's handshake timeout, as `TlsAcceptor::set_handshake_timeout()`
+    /// does. The timeout never shortens blinding: a handshake that fails with an error that
+    /// requires blinding counts towards `TlsListener::with_max_handshakes()` until the full
+    /// blinding delay has elapsed.
+    pub fn with_handshake_timeout(&mut self, timeout: impl Into<Option<Duration>>) -> &mut Self {
+        self.acceptor.set_handshake_timeout(timeout);
+        self
+    }
+
+    /// The wrapped listener.
+    pub fn get_ref(&self) -> &L {
+        &self.inner
+    }
+
+    /// The acceptor used to negotiate TLS.
+    pub fn acceptor(&self) -> &TlsAcceptor<B> {
+        &self.acceptor
+    }
+}
+
+// `accept()` polls the wrapped listener alongside the in-progress handshakes, so the wrapped
+// listener's `accept()` must be cancel safe. This is true of the tokio listeners.
+impl<L, B> Listener for TlsListener<L, B>
+where
+    L: Listener,
+    L::Addr: 'static,
+    B: Builder + Send + Sync + 'static,
+    <B as Builder>::Output: Unpin + Send + Sync + 'static,
+{
+    type Io = TlsStream<L::Io, B::Output>;
+    type Addr = L::Addr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        loop {
+            tokio::select! {
+                (stream, addr) = self.inner.accept(),
+                    if self.handshakes.len() < self.max_handshakes =>
+                {
+                    let acceptor = self.acceptor.clone();
+                    self.handshakes
+                        .spawn(async move { (acceptor.accept(stream).await, addr) });
+                }
+                Some(result) = self.handshakes.join_next() => match result {
+                    Ok((Ok(tls), addr)) => return (tls, addr),
+                    // A failed handshake only affects its own connection.
+                    Ok((Err(_), _)) => continue,
+                    Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+                    Err(_) => continue,
+                },
+            }
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        self.inner.local_addr()
+    }
+}
+
+/// Information about a connection accepted by a `TlsListener`, made available to axum handlers
+/// with the `axum::extract::ConnectInfo` extractor.
+///
+/// ```no_run
+/// use axum::{extract::ConnectInfo, routing::get, Router};
+/// use s2n_tls_hyper::listener::{TlsConnectInfo, TlsListener};
+/// use std::net::SocketAddr;
+///
+/// async fn handler(ConnectInfo(info): ConnectInfo<TlsConnectInfo<SocketAddr>>) -> String {
+///     format!("{} connected with {}", info.remote_addr(), info.tls().cipher_suite())
+/// }
+///
+/// # async fn run(listener: TlsListener<tokio::net::TcpListener>) -> std::io::Result<()> {
+/// let app = Router::new().route("/", get(handler));
+/// axum::serve(
+///     listener,
+///     app.into_make_service_with_connect_info::<TlsConnectInfo<SocketAddr>>(),
+/// )
+/// .await
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct TlsConnectInfo<A> {
+    remote_addr: A,
+    tls: TlsInfo,
+}
+
+impl<A> TlsConnectInfo<A> {
+    /// The address of the peer.
+    pub fn remote_addr(&self) -> &A {
+        &self.remote_addr
+    }
+
+    /// The TLS parameters negotiated with the peer, including the peer's certificate chain.
+    pub fn tls(&self) -> &TlsInfo {
+        &self.tls
+    }
+}
+
+impl<L, B> Connected<IncomingStream<'_, TlsListener<L, B>>> for TlsConnectInfo<L::Addr>
+where
+    L: Listener,
+    L::Addr: Clone + Sync + 'static,
+    B: Builder + Send + Sync + 'static,
+    <B as Builder>::Output: Unpin + Send + Sync + 'static,
+{
+    fn connect_info(stream: IncomingStream<'_, TlsListener<L, B>>) -> Self {
+        TlsConnectInfo {
+            remote_addr: stream.remote_addr().clone(),
+            tls: TlsInfo::from_connection(stream.io().as_ref()),
+        }
+    }
+}
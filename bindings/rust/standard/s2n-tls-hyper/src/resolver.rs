@@ -0,0 +1,192 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::proxy::trim_brackets;
+use http::uri::Uri;
+
+/// A trait used by `HttpsConnector` to select the s2n-tls connection builder, usually an
+/// `s2n_tls::config::Config`, for each new connection.
+///
+/// This allows a single client to use different trust stores, client certificates, or security
+/// policies depending on the destination. The resolver is configured with
+/// `s2n_tls_hyper::connector::Builder::with_config_resolver()`.
+pub trait ConfigResolver<ConnBuilder>: Send + Sync {
+    /// Selects the connection builder used to negotiate TLS with the server at `uri`.
+    ///
+    /// If `None` is returned, the connection builder that the `HttpsConnector` was created with
+    /// is used.
+    fn resolve(&self, uri: &Uri) -> Option<ConnBuilder>;
+}
+
+impl<ConnBuilder, F> ConfigResolver<ConnBuilder> for F
+where
+    F: Fn(&Uri) -> Option<ConnBuilder> + Send + Sync,
+{
+    fn resolve(&self, uri: &Uri) -> Option<ConnBuilder> {
+        (self)(uri)
+    }
+}
+
+/// A `ConfigResolver` that selects a connection builder by matching the host of the URI against a
+/// list of rules.
+///
+/// Host names are compared case-insensitively. When several rules match, an exact match is
+/// preferred over a wildcard match, which is preferred over a suffix match. Among suffix matches,
+/// the longest suffix is preferred.
+///
+/// ```
+/// use s2n_tls::config::Config;
+/// use s2n_tls_hyper::{connector::HttpsConnector, resolver::HostRules};
+///
+/// # let (internal_config, partner_config) = (Config::default(), Config::default());
+/// let mut rules = HostRules::new();
+/// rules
+///     .suffix("internal.example.com", internal_config)
+///     .exact("partner.example.org", partner_config);
+///
+/// // Other hosts use the default config.
+/// let mut builder = HttpsConnector::builder(Config::default());
+/// builder.with_config_resolver(rules);
+/// let connector = builder.build();
+/// ```
+#[derive(Clone, Debug)]
+pub struct HostRules<ConnBuilder> {
+    exact: Vec<(String, ConnBuilder)>,
+    wildcard: Vec<(String, ConnBuilder)>,
+    suffix: Vec<(String, ConnBuilder)>,
+}
+
+impl<ConnBuilder> Default for HostRules<ConnBuilder> {
+    fn default() -> Self {
+        HostRules {
+            exact: Vec::new(),
+            wildcard: Vec::new(),
+            suffix: Vec::new(),
+        }
+    }
+}
+
+impl<ConnBuilder> HostRules<ConnBuilder> {
+    /// Creates an empty set of rules, which never selects a connection builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses `conn_builder` for connections to exactly `host`.
+    pub fn exact(&mut self, host: &str, conn_builder: ConnBuilder) -> &mut Self {
+        self.exact.push((normalize(host), conn_builder));
+        self
+    }
+
+    /// Uses `conn_builder` for connections to hosts matching a wildcard pattern such as
+    /// `*.example.com`.
+    ///
+    /// As with wildcard certificates, the `*` matches exactly one label: `api.example.com` matches
+    /// `*.example.com`, but `example.com` and `v1.api.example.com` do not. A pattern without a
+    /// leading `*.` is treated as an exact match.
+    pub fn wildcard(&mut self, pattern: &str, conn_builder: ConnBuilder) -> &mut Self {
+        match pattern.strip_prefix("*.") {
+            Some(parent) => self.wildcard.push((normalize(parent), conn_builder)),
+            None => self.exact.push((normalize(pattern), conn_builder)),
+        }
+        self
+    }
+
+    /// Uses `conn_builder` for connections to `domain` and all of its subdomains.
+    ///
+    /// `internal.example.com` matches `internal.example.com` and `api.internal.example.com`, but
+    /// not `notinternal.example.com`. A leading `.` is ignored.
+    pub fn suffix(&mut self, domain: &str, conn_builder: ConnBuilder) -> &mut Self {
+        let domain = normalize(domain.trim_start_matches('.'));
+        self.suffix.push((domain, conn_builder));
+        self
+    }
+
+    /// Returns the connection builder selected for `host`, if any rule matches.
+    pub fn lookup(&self, host: &str) -> Option<&ConnBuilder> {
+        let host = normalize(host);
+
+        if let Some((_, conn_builder)) = self.exact.iter().find(|(exact, _)| *exact == host) {
+            return Some(conn_builder);
+        }
+
+        if let Some((_, parent)) = host.split_once('.') {
+            if let Some((_, conn_builder)) = self
+                .wildcard
+                .iter()
+                .find(|(wildcard, _)| wildcard == parent)
+            {
+                return Some(conn_builder);
+            }
+        }
+
+        self.suffix
+            .iter()
+            .filter(|(domain, _)| {
+                host == *domain
+                    || host
+                        .strip_suffix(domain.as_str())
+                        .is_some_and(|prefix| prefix.ends_with('.'))
+            })
+            .max_by_key(|(domain, _)| domain.len())
+            .map(|(_, conn_builder)| conn_builder)
+    }
+}
+
+impl<ConnBuilder> ConfigResolver<ConnBuilder> for HostRules<ConnBuilder>
+where
+    ConnBuilder: Clone + Send + Sync,
+{
+    fn resolve(&self, uri: &Uri) -> Option<ConnBuilder> {
+        self.lookup(uri.host()?).cloned()
+    }
+}
+
+fn normalize(host: &str) -> String {
+    trim_brackets(host)
+        .trim_end_matches('.')
+        .to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn host_rules() {
+        let mut rules = HostRules::new();
+        rules
+            .exact("api.example.com", "exact")
+            .wildcard("*.example.com", "wildcard")
+            .suffix("example.com", "suffix")
+            .suffix(".internal.example.com", "internal")
+            .exact("[::1]", "ipv6");
+
+        let cases = [
+            ("api.example.com", Some("exact")),
+            ("API.Example.com.", Some("exact")),
+            ("www.example.com", Some("wildcard")),
+            ("example.com", Some("suffix")),
+            ("v1.api.example.com", Some("suffix")),
+            ("internal.example.com", Some("wildcard")),
+            ("db.internal.example.com", Some("internal")),
+            ("::1", Some("ipv6")),
+            ("notexample.com", None),
+            ("example.org", None),
+        ];
+        for (host, expected) in cases {
+            assert_eq!(rules.lookup(host).copied(), expected, "{host}");
+        }
+    }
+
+    #[test]
+    fn resolve_uri() {
+        let mut rules = HostRules::new();
+        rules.exact("::1", 1);
+
+        let resolve = |uri: &str| rules.resolve(&uri.parse::<Uri>().unwrap());
+        assert_eq!(resolve("https://[::1]:8443/path"), Some(1));
+        assert_eq!(resolve("https://localhost/"), None);
+        assert_eq!(resolve("/relative"), None);
+    }
+}
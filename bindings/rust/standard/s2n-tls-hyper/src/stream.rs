@@ -1,6 +1,7 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::tls_info::TlsInfo;
 use hyper::rt::{Read, ReadBufCursor, Write};
 use hyper_util::{
     client::legacy::connect::{Connected, Connection as HyperConnection},
@@ -13,6 +14,7 @@ use std::{
     pin::Pin,
     task::{Context, Poll},
 };
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 
 /// `MaybeHttpsStream` is a wrapper over a hyper TCP stream, `Transport`, allowing for TLS to be
 /// negotiated over the TCP stream via the `Https` type. The `Http` type bypasses TLS to optionally
@@ -34,10 +36,80 @@ where
     // wrapped in an additional `TokioIo` type, which already implements the conversion from hyper's
     // traits to tokio's. This allows the `Read` and `Write` implementations for `MaybeHttpsStream`
     // to simply call the `TokioIo` `poll` functions.
-    Https(TokioIo<TlsStream<TokioIo<Transport>, Builder::Output>>),
+    Https(TokioIo<TlsStream<TunnelStream<Transport>, Builder::Output>>),
     Http(Transport),
 }
 
+/// The stream that TLS is negotiated with the server over.
+///
+/// This is either the underlying hyper TCP stream, or a tunnel through a proxy. If TLS is used to
+/// connect to the proxy, the tunnel is itself a TLS stream.
+pub enum TunnelStream<Transport>
+where
+    Transport: Read + Write + Unpin,
+{
+    Direct(TokioIo<Transport>),
+    Tls(Box<TlsStream<TokioIo<Transport>, connection::Connection>>),
+}
+
+impl<Transport> TunnelStream<Transport>
+where
+    Transport: Read + Write + Unpin,
+{
+    fn transport(&self) -> &Transport {
+        match self {
+            Self::Direct(stream) => stream.inner(),
+            Self::Tls(stream) => stream.get_ref().inner(),
+        }
+    }
+}
+
+impl<Transport> AsyncRead for TunnelStream<Transport>
+where
+    Transport: Read + Write + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<(), Error>> {
+        match Pin::get_mut(self) {
+            Self::Direct(stream) => Pin::new(stream).poll_read(cx, buf),
+            Self::Tls(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl<Transport> AsyncWrite for TunnelStream<Transport>
+where
+    Transport: Read + Write + Unpin,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Error>> {
+        match Pin::get_mut(self) {
+            Self::Direct(stream) => Pin::new(stream).poll_write(cx, buf),
+            Self::Tls(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        match Pin::get_mut(self) {
+            Self::Direct(stream) => Pin::new(stream).poll_flush(cx),
+            Self::Tls(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        match Pin::get_mut(self) {
+            Self::Direct(stream) => Pin::new(stream).poll_shutdown(cx),
+            Self::Tls(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
+}
+
 impl<Transport, Builder> HyperConnection for MaybeHttpsStream<Transport, Builder>
 where
     Transport: Read + Write + HyperConnection + Unpin,
@@ -47,8 +119,15 @@ where
     fn connected(&self) -> Connected {
         match self {
             Self::Https(stream) => {
-                let connected = stream.inner().get_ref().connected();
                 let conn = stream.inner().as_ref();
+                // hyper inserts the extra `TlsInfo` into the extensions of each response received
+                // over the connection.
+                let connected = stream
+                    .inner()
+                    .get_ref()
+                    .transport()
+                    .connected()
+                    .extra(TlsInfo::from_connection(conn));
                 match conn.application_protocol() {
                     // Inform hyper that HTTP/2 was negotiated in the ALPN.
                     Some(b"h2") => connected.negotiated_h2(),
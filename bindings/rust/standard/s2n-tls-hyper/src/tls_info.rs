@@ -0,0 +1,194 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use http::Request;
+use hyper::service::Service;
+use s2n_tls::{connection::Connection, enums::Version};
+use std::task::{Context, Poll};
+use tower_layer::Layer;
+
+/// A snapshot of the TLS parameters negotiated for a connection.
+///
+/// For clients, `HttpsConnector` attaches a `TlsInfo` to each HTTPS connection, which hyper then
+/// inserts into the extensions of every response received over the connection:
+/// ```no_run
+/// # use http::Response;
+/// # use s2n_tls_hyper::tls_info::TlsInfo;
+/// # fn handle(response: Response<()>) {
+/// if let Some(info) = response.extensions().get::<TlsInfo>() {
+///     println!("negotiated {} with {:?}", info.cipher_suite(), info.protocol_version());
+/// }
+/// # }
+/// ```
+///
+/// For servers, `TlsInfoService` or `TlsInfoLayer` can be used to insert a `TlsInfo` into the
+/// extensions of every request received over a connection.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TlsInfo {
+    peer_cert_chain: Vec<Vec<u8>>,
+    cipher_suite: String,
+    protocol_version: Option<Version>,
+    application_protocol: Option<Vec<u8>>,
+    resumed: bool,
+}
+
+impl TlsInfo {
+    /// Captures the negotiated parameters of a connection that has completed its handshake.
+    pub fn from_connection(conn: &Connection) -> Self {
+        // The peer certificate chain is unavailable if the peer didn't send a certificate, or if
+        // the certificate wasn't validated. An empty chain is reported in these cases.
+        let peer_cert_chain = conn
+            .peer_cert_chain()
+            .ok()
+            .and_then(|chain| {
+                chain
+                    .iter()
+                    .map(|cert| cert.and_then(|cert| cert.der().map(<[u8]>::to_vec)))
+                    .collect::<Result<Vec<_>, _>>()
+                    .ok()
+            })
+            .unwrap_or_default();
+
+        TlsInfo {
+            peer_cert_chain,
+            cipher_suite: conn.cipher_suite().unwrap_or_default().to_owned(),
+            protocol_version: conn.actual_protocol_version().ok(),
+            application_protocol: conn.application_protocol().map(<[u8]>::to_vec),
+            resumed: conn.resumed(),
+        }
+    }
+
+    /// The DER-encoded certificates sent by the peer, starting with the leaf certificate.
+    ///
+    /// See `s2n_tls::connection::Connection::peer_cert_chain()`.
+    pub fn peer_cert_chain(&self) -> &[Vec<u8>] {
+        &self.peer_cert_chain
+    }
+
+    /// The IANA name of the negotiated cipher suite.
+    pub fn cipher_suite(&self) -> &str {
+        &self.cipher_suite
+    }
+
+    /// The negotiated TLS protocol version.
+    pub fn protocol_version(&self) -> Option<Version> {
+        self.protocol_version
+    }
+
+    /// The application protocol negotiated via ALPN.
+    pub fn application_protocol(&self) -> Option<&[u8]> {
+        self.application_protocol.as_deref()
+    }
+
+    /// Whether the handshake resumed a previous session.
+    pub fn resumed(&self) -> bool {
+        self.resumed
+    }
+}
+
+/// A hyper and tower `Service` that inserts a `TlsInfo` into the extensions of each request before
+/// forwarding it to the wrapped service.
+///
+/// A `TlsInfoService` is created for each accepted connection:
+/// ```no_run
+/// # use bytes::Bytes;
+/// # use http::{Request, Response};
+/// # use http_body_util::Full;
+/// # use hyper::{body::Incoming, service::service_fn};
+/// # use hyper_util::rt::{TokioExecutor, TokioIo};
+/// # use s2n_tls_hyper::tls_info::{TlsInfo, TlsInfoService};
+/// # use s2n_tls_tokio::TlsAcceptor;
+/// # use std::{convert::Infallible, error::Error};
+/// # use tokio::net::TcpStream;
+/// # async fn serve(acceptor: TlsAcceptor, tcp: TcpStream) -> Result<(), Box<dyn Error + Send + Sync>> {
+/// let tls = acceptor.accept(tcp).await?;
+/// let info = TlsInfo::from_connection(tls.as_ref());
+///
+/// let service = TlsInfoService::new(
+///     info,
+///     service_fn(|request: Request<Incoming>| async move {
+///         let info = request.extensions().get::<TlsInfo>().unwrap();
+///         let body = Bytes::from(info.cipher_suite().to_owned());
+///         Ok::<_, Infallible>(Response::new(Full::new(body)))
+///     }),
+/// );
+///
+/// hyper_util::server::conn::auto::Builder::new(TokioExecutor::new())
+///     .serve_connection(TokioIo::new(tls), service)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct TlsInfoService<S> {
+    info: TlsInfo,
+    inner: S,
+}
+
+impl<S> TlsInfoService<S> {
+    /// Wraps `inner`, inserting `info` into the extensions of each request.
+    pub fn new(info: TlsInfo, inner: S) -> Self {
+        TlsInfoService { info, inner }
+    }
+
+    /// The `TlsInfo` inserted into each request.
+    pub fn info(&self) -> &TlsInfo {
+        &self.info
+    }
+}
+
+impl<S, B> Service<Request<B>> for TlsInfoService<S>
+where
+    S: Service<Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn call(&self, mut request: Request<B>) -> Self::Future {
+        request.extensions_mut().insert(self.info.clone());
+        self.inner.call(request)
+    }
+}
+
+impl<S, B> tower_service::Service<Request<B>> for TlsInfoService<S>
+where
+    S: tower_service::Service<Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<B>) -> Self::Future {
+        request.extensions_mut().insert(self.info.clone());
+        self.inner.call(request)
+    }
+}
+
+/// A tower `Layer` that wraps a service in a `TlsInfoService`.
+///
+/// Like `TlsInfoService`, a `TlsInfoLayer` is created for each accepted connection, and applied
+/// to the tower stack that serves the connection.
+#[derive(Clone, Debug)]
+pub struct TlsInfoLayer {
+    info: TlsInfo,
+}
+
+impl TlsInfoLayer {
+    /// Creates a layer that inserts `info` into the extensions of each request.
+    pub fn new(info: TlsInfo) -> Self {
+        TlsInfoLayer { info }
+    }
+}
+
+impl<S> Layer<S> for TlsInfoLayer {
+    type Service = TlsInfoService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TlsInfoService::new(self.info.clone(), inner)
+    }
+}
@@ -0,0 +1,125 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "axum")]
+
+use axum::{extract::ConnectInfo, routing::get, Router};
+use bytes::Bytes;
+use http::Uri;
+use http_body_util::{BodyExt, Empty};
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use s2n_tls::{config, security::DEFAULT_TLS13};
+use s2n_tls_hyper::{
+    connector::HttpsConnector,
+    listener::{TlsConnectInfo, TlsListener},
+};
+use s2n_tls_tokio::TlsAcceptor;
+use std::{error::Error, future::IntoFuture, net::SocketAddr, str::FromStr, time::Duration};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::oneshot,
+};
+
+pub mod common;
+
+async fn handler(ConnectInfo(info): ConnectInfo<TlsConnectInfo<SocketAddr>>) -> String {
+    info.tls().cipher_suite().to_owned()
+}
+
+async fn get_body(
+    client_config: config::Config,
+    port: u16,
+) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let client: Client<_, Empty<Bytes>> =
+        Client::builder(TokioExecutor::new()).build(HttpsConnector::new(client_config));
+    let uri = Uri::from_str(format!("https://localhost:{port}").as_str())?;
+    let response = client.get(uri).await?;
+    assert_eq!(response.status(), 200);
+
+    let body = response.into_body().collect().await?.to_bytes();
+    Ok(String::from_utf8(body.to_vec())?)
+}
+
+#[tokio::test]
+async fn serve_with_connect_info() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let tcp_listener = TcpListener::bind("127.0.0.1:0").await?;
+    let port = tcp_listener.local_addr()?.port();
+    let listener = TlsListener::new(tcp_listener, TlsAcceptor::new(common::config()?.build()?));
+
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    let app = Router::new().route("/", get(handler));
+    let server = tokio::spawn(
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<TlsConnectInfo<SocketAddr>>(),
+        )
+        .with_graceful_shutdown(async move {
+            let _ = shutdown_rx.await;
+        })
+        .into_future(),
+    );
+
+    // The handler responds with the cipher suite from the connection's TLS info.
+    let cipher_suite = get_body(common::config()?.build()?, port).await?;
+    assert!(cipher_suite.starts_with("TLS_"));
+
+    // The server stops after the shutdown signal, once the client's connection is closed.
+    shutdown_tx.send(()).unwrap();
+    server.await??;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn handshake_failure_ignored() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let tcp_listener = TcpListener::bind("127.0.0.1:0").await?;
+    let port = tcp_listener.local_addr()?.port();
+    let listener = TlsListener::new(tcp_listener, TlsAcceptor::new(common::config()?.build()?));
+
+    let app = Router::new().route("/", get(|| async { "hello" }));
+    let server = tokio::spawn(axum::serve(listener, app).into_future());
+
+    // The client config won't trust the self-signed cert that the server uses.
+    let untrusting_config = {
+        let mut builder = config::Config::builder();
+        builder.set_security_policy(&DEFAULT_TLS13)?;
+        builder.set_max_blinding_delay(0)?;
+        builder.build()?
+    };
+    assert!(get_body(untrusting_config, port).await.is_err());
+
+    // The listener continues to accept connections after a failed handshake.
+    let body = get_body(common::config()?.build()?, port).await?;
+    assert_eq!(body, "hello");
+
+    server.abort();
+    Ok(())
+}
+
+#[tokio::test]
+async fn stalled_handshake_times_out() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let tcp_listener = TcpListener::bind("127.0.0.1:0").await?;
+    let port = tcp_listener.local_addr()?.port();
+    let mut listener = TlsListener::new(tcp_listener, TlsAcceptor::new(common::config()?.build()?));
+    listener
+        .with_max_handshakes(1)
+        .with_handshake_timeout(Duration::from_millis(100));
+
+    let app = Router::new().route("/", get(|| async { "hello" }));
+    let server = tokio::spawn(axum::serve(listener, app).into_future());
+
+    // The stalled client never sends a ClientHello, so it holds the only handshake slot until
+    // its handshake times out.
+    let _stalled = TcpStream::connect(("127.0.0.1", port)).await?;
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    let body = tokio::time::timeout(
+        Duration::from_secs(5),
+        get_body(common::config()?.build()?, port),
+    )
+    .await??;
+    assert_eq!(body, "hello");
+
+    server.abort();
+    Ok(())
+}
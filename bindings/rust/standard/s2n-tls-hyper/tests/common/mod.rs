@@ -4,6 +4,7 @@
 use s2n_tls::{callbacks::VerifyHostNameCallback, config, error::Error, security::DEFAULT_TLS13};
 
 pub mod echo;
+pub mod proxy;
 
 /// NOTE: this certificate and key are used for testing purposes only!
 pub const CERT_PEM: &[u8] =
@@ -0,0 +1,67 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use s2n_tls_tokio::TlsAcceptor;
+use std::error::Error;
+use tokio::{
+    io::{copy_bidirectional, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+/// A minimal HTTP proxy that accepts a single CONNECT request and tunnels it to the requested
+/// authority, which is returned.
+///
+/// If `authorization` is set, requests without a matching `Proxy-Authorization` header are refused
+/// with a 407 status. If `acceptor` is set, TLS is negotiated with the client before the CONNECT
+/// request is read.
+pub async fn serve_proxy(
+    listener: TcpListener,
+    acceptor: Option<TlsAcceptor>,
+    authorization: Option<&str>,
+) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let (tcp_stream, _) = listener.accept().await?;
+    match acceptor {
+        Some(acceptor) => {
+            let tls_stream = acceptor.accept(tcp_stream).await?;
+            tunnel(tls_stream, authorization).await
+        }
+        None => tunnel(tcp_stream, authorization).await,
+    }
+}
+
+async fn tunnel<S>(
+    mut client: S,
+    authorization: Option<&str>,
+) -> Result<String, Box<dyn Error + Send + Sync>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut request = Vec::new();
+    while !request.ends_with(b"\r\n\r\n") {
+        request.push(client.read_u8().await?);
+    }
+    let request = String::from_utf8(request)?;
+
+    let mut lines = request.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    assert_eq!(request_line.next(), Some("CONNECT"));
+    let authority = request_line.next().unwrap_or_default().to_owned();
+
+    if let Some(authorization) = authorization {
+        let expected = format!("proxy-authorization: {authorization}");
+        if !lines.any(|line| line.eq_ignore_ascii_case(&expected)) {
+            client
+                .write_all(b"HTTP/1.1 407 Proxy Authentication Required\r\n\r\n")
+                .await?;
+            return Ok(authority);
+        }
+    }
+
+    let mut server = TcpStream::connect(&authority).await?;
+    client
+        .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+        .await?;
+    // Errors are expected when either side closes the connection abruptly.
+    let _ = copy_bidirectional(&mut client, &mut server).await;
+    Ok(authority)
+}
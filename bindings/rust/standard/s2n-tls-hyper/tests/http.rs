@@ -7,6 +7,7 @@ use common::echo::serve_echo;
 use http::{Method, Request, Uri, Version};
 use http_body_util::{BodyExt, Empty, Full};
 use hyper::service::service_fn;
+use hyper_util::client::legacy::connect::HttpConnector;
 use hyper_util::{
     client::legacy::Client,
     rt::{TokioExecutor, TokioIo},
@@ -17,7 +18,11 @@ use s2n_tls::{
     connection::Connection,
     security::DEFAULT_TLS13,
 };
-use s2n_tls_hyper::{connector::HttpsConnector, error};
+use s2n_tls_hyper::{
+    connector::{Builder as ConnectorBuilder, HttpsConnector},
+    error,
+    resolver::HostRules,
+};
 use std::{error::Error, pin::Pin, str::FromStr};
 use tokio::{
     net::TcpListener,
@@ -334,6 +339,148 @@ async fn config_alpn_ignored() -> Result<(), Box<dyn Error + Send + Sync>> {
     Ok(())
 }
 
+fn https_connector(
+    configure: impl FnOnce(&mut ConnectorBuilder<HttpConnector, config::Config>),
+) -> Result<HttpsConnector<HttpConnector, config::Config>, s2n_tls::error::Error> {
+    let mut builder = HttpsConnector::builder(common::config()?.build()?);
+    configure(&mut builder);
+    Ok(builder.build())
+}
+
+/// Ensure that each HTTP version policy offers the expected protocols.
+#[tokio::test]
+async fn http_version_policies() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let cases = [
+        (
+            https_connector(|builder| {
+                builder.with_http1_only();
+            })?,
+            Version::HTTP_11,
+        ),
+        (
+            https_connector(|builder| {
+                builder.with_http2_only();
+            })?,
+            Version::HTTP_2,
+        ),
+        (
+            https_connector(|builder| {
+                builder.with_application_protocols(["http/1.1"]);
+            })?,
+            Version::HTTP_11,
+        ),
+    ];
+
+    for (connector, expected_http_version) in cases {
+        // The server prefers HTTP/2, but also supports HTTP/1.1.
+        let server_config = {
+            let mut builder = common::config()?;
+            builder.set_application_protocol_preference(["h2", "http/1.1"])?;
+            builder.build()?
+        };
+
+        common::echo::make_echo_request(server_config, move |port| async move {
+            let client: Client<_, Empty<Bytes>> =
+                Client::builder(TokioExecutor::new()).build(connector);
+
+            let uri = Uri::from_str(format!("https://localhost:{port}").as_str())?;
+            let response = client.get(uri).await?;
+            assert_eq!(response.status(), 200);
+            assert_eq!(response.version(), expected_http_version);
+
+            Ok(())
+        })
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Ensure that HTTP/2 can be used with a server that doesn't negotiate ALPN.
+#[tokio::test]
+async fn http2_prior_knowledge() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let server_config = common::config()?.build()?;
+
+    common::echo::make_echo_request(server_config, |port| async move {
+        let connector = https_connector(|builder| {
+            builder.with_http2_prior_knowledge();
+        })?;
+        let client: Client<_, Empty<Bytes>> = Client::builder(TokioExecutor::new())
+            .http2_only(true)
+            .build(connector);
+
+        let uri = Uri::from_str(format!("https://localhost:{port}").as_str())?;
+        let response = client.get(uri).await?;
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.version(), Version::HTTP_2);
+
+        Ok(())
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Ensure that the connection fails if the server selects a protocol that the connector isn't
+/// configured to use.
+#[tokio::test]
+async fn unexpected_application_protocol() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let cases = [
+        // The server doesn't negotiate ALPN, so HTTP/2 can't be used.
+        (
+            vec![],
+            https_connector(|builder| {
+                builder.with_http2_only();
+            })?,
+            None,
+        ),
+        // hyper can't speak a custom protocol selected by the server.
+        (
+            vec!["custom"],
+            https_connector(|builder| {
+                builder.with_application_protocols(["custom", "h2"]);
+            })?,
+            Some(b"custom".to_vec()),
+        ),
+    ];
+
+    for (server_protocols, connector, expected_protocol) in cases {
+        let server_config = {
+            let mut builder = common::config()?;
+            builder.set_max_blinding_delay(0)?;
+            builder.set_application_protocol_preference(server_protocols)?;
+            builder.build()?
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let server_task = tokio::spawn(serve_echo(listener, server_config));
+
+        let client: Client<_, Empty<Bytes>> =
+            Client::builder(TokioExecutor::new()).build(connector);
+        let uri = Uri::from_str(format!("https://localhost:{}", addr.port()).as_str())?;
+        let error = client.get(uri).await.unwrap_err();
+        assert!(error.is_connect());
+
+        let error = error
+            .source()
+            .unwrap()
+            .downcast_ref::<error::Error>()
+            .unwrap();
+        match error {
+            error::Error::UnexpectedApplicationProtocol(protocol) => {
+                assert_eq!(protocol, &expected_protocol);
+            }
+            _ => panic!("unexpected error type: {error:?}"),
+        }
+        assert!(!error.to_string().is_empty());
+
+        server_task.abort();
+    }
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn plaintext_http() -> Result<(), Box<dyn Error + Send + Sync>> {
     let listener = TcpListener::bind("127.0.0.1:0").await?;
@@ -393,3 +540,59 @@ async fn plaintext_http() -> Result<(), Box<dyn Error + Send + Sync>> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn config_resolver() -> Result<(), Box<dyn Error + Send + Sync>> {
+    fn connector(
+        resolved_host: &str,
+    ) -> Result<HttpsConnector<HttpConnector>, s2n_tls::error::Error> {
+        // The default client config won't trust the self-signed cert that the server uses.
+        let default_config = {
+            let mut builder = config::Config::builder();
+            builder.set_security_policy(&DEFAULT_TLS13)?;
+            builder.set_max_blinding_delay(0)?;
+            builder.build()?
+        };
+
+        let mut rules = HostRules::new();
+        rules.exact(resolved_host, common::config()?.build()?);
+
+        let mut builder = HttpsConnector::builder(default_config);
+        builder.with_config_resolver(rules);
+        Ok(builder.build())
+    }
+
+    // The resolver selects a config that trusts the server's cert.
+    common::echo::make_echo_request(common::config()?.build()?, |port| async move {
+        let client: Client<_, Empty<Bytes>> =
+            Client::builder(TokioExecutor::new()).build(connector("localhost")?);
+        let uri = Uri::from_str(format!("https://localhost:{port}").as_str())?;
+        let response = client.get(uri).await?;
+        assert_eq!(response.status(), 200);
+
+        Ok(())
+    })
+    .await?;
+
+    // The resolver doesn't match the host, so the default config is used.
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let server_task = tokio::spawn(serve_echo(listener, common::config()?.build()?));
+
+    let client: Client<_, Empty<Bytes>> =
+        Client::builder(TokioExecutor::new()).build(connector("example.com")?);
+    let uri = Uri::from_str(format!("https://localhost:{}", addr.port()).as_str())?;
+    let error = client.get(uri).await.unwrap_err();
+    let error = error
+        .source()
+        .unwrap()
+        .downcast_ref::<error::Error>()
+        .unwrap();
+    let error::Error::TlsError(error) = error else {
+        panic!("unexpected error type");
+    };
+    assert_eq!(error.name(), "S2N_ERR_CERT_UNTRUSTED");
+
+    server_task.abort();
+    Ok(())
+}
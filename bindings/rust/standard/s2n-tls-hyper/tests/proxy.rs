@@ -0,0 +1,144 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bytes::Bytes;
+use common::proxy::serve_proxy;
+use http::{StatusCode, Uri};
+use http_body_util::Empty;
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use s2n_tls_hyper::{
+    connector::HttpsConnector,
+    error,
+    proxy::{NoProxy, Proxy},
+};
+use s2n_tls_tokio::TlsAcceptor;
+use std::{error::Error, str::FromStr};
+use tokio::net::TcpListener;
+
+pub mod common;
+
+async fn get(
+    proxy: Proxy,
+    port: u16,
+) -> Result<http::Response<hyper::body::Incoming>, hyper_util::client::legacy::Error> {
+    let mut builder = HttpsConnector::builder(common::config().unwrap().build().unwrap());
+    builder.with_proxy(proxy);
+    let client: Client<_, Empty<Bytes>> =
+        Client::builder(TokioExecutor::new()).build(builder.build());
+
+    let uri = Uri::from_str(format!("https://localhost:{port}").as_str()).unwrap();
+    client.get(uri).await
+}
+
+#[tokio::test]
+async fn connect_through_proxy() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let config = common::config()?.build()?;
+    common::echo::make_echo_request(config, |port| async move {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let proxy_addr = listener.local_addr()?;
+        let proxy_task = tokio::spawn(serve_proxy(listener, None, None));
+
+        let proxy = Proxy::new(&format!("http://{proxy_addr}"))?;
+        let response = get(proxy, port).await?;
+        assert_eq!(response.status(), 200);
+
+        // The tunnel was opened to the server's authority.
+        drop(response);
+        let authority = proxy_task.await??;
+        assert_eq!(authority, format!("localhost:{port}"));
+
+        Ok(())
+    })
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn proxy_basic_auth() -> Result<(), Box<dyn Error + Send + Sync>> {
+    const AUTHORIZATION: &str = "Basic dXNlcjpwYXNzd29yZA==";
+
+    let config = common::config()?.build()?;
+    common::echo::make_echo_request(config, |port| async move {
+        // Credentials can be provided in the proxy URI.
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let proxy_addr = listener.local_addr()?;
+        let proxy_task = tokio::spawn(serve_proxy(listener, None, Some(AUTHORIZATION)));
+
+        let proxy = Proxy::new(&format!("http://user:password@{proxy_addr}"))?;
+        let response = get(proxy, port).await?;
+        assert_eq!(response.status(), 200);
+        drop(response);
+        proxy_task.await??;
+
+        Ok(())
+    })
+    .await?;
+
+    // Incorrect credentials are rejected by the proxy.
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_addr = listener.local_addr()?;
+    let proxy_task = tokio::spawn(serve_proxy(listener, None, Some(AUTHORIZATION)));
+
+    let mut proxy = Proxy::new(&format!("http://{proxy_addr}"))?;
+    proxy.with_basic_auth("user", "wrong");
+    let error = get(proxy, 443).await.unwrap_err();
+    assert!(error.is_connect());
+
+    let error = error
+        .source()
+        .unwrap()
+        .downcast_ref::<error::Error>()
+        .unwrap();
+    assert!(matches!(
+        error,
+        error::Error::ProxyTunnelRefused(StatusCode::PROXY_AUTHENTICATION_REQUIRED)
+    ));
+    assert!(!error.to_string().is_empty());
+    proxy_task.await??;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn tls_to_proxy() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let config = common::config()?.build()?;
+    common::echo::make_echo_request(config, |port| async move {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let proxy_port = listener.local_addr()?.port();
+        let acceptor = TlsAcceptor::new(common::config()?.build()?);
+        let proxy_task = tokio::spawn(serve_proxy(listener, Some(acceptor), None));
+
+        let mut proxy = Proxy::new(&format!("https://localhost:{proxy_port}"))?;
+        proxy.with_tls_config(common::config()?.build()?);
+        let response = get(proxy, port).await?;
+        assert_eq!(response.status(), 200);
+        drop(response);
+        proxy_task.await??;
+
+        Ok(())
+    })
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn no_proxy() -> Result<(), Box<dyn Error + Send + Sync>> {
+    // Nothing is listening at the proxy address, so the request can only succeed if the proxy
+    // is bypassed.
+    let proxy_addr = TcpListener::bind("127.0.0.1:0").await?.local_addr()?;
+
+    let config = common::config()?.build()?;
+    common::echo::make_echo_request(config, |port| async move {
+        let mut proxy = Proxy::new(&format!("http://{proxy_addr}"))?;
+        proxy.with_no_proxy(NoProxy::new("example.com,localhost"));
+        let response = get(proxy, port).await?;
+        assert_eq!(response.status(), 200);
+
+        Ok(())
+    })
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use bytes::Bytes;
+use http::{Request, Response, Uri};
+use http_body_util::{BodyExt, Empty, Full};
+use hyper::{body::Incoming, service::service_fn};
+use hyper_util::{
+    client::legacy::Client,
+    rt::{TokioExecutor, TokioIo},
+};
+use s2n_tls::enums::Version;
+use s2n_tls_hyper::{
+    connector::HttpsConnector,
+    tls_info::{TlsInfo, TlsInfoService},
+};
+use s2n_tls_tokio::TlsAcceptor;
+use std::{convert::Infallible, error::Error, str::FromStr};
+use tokio::{net::TcpListener, task::JoinHandle};
+
+pub mod common;
+
+#[tokio::test]
+async fn client_response_extensions() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let config = common::config()?.build()?;
+    common::echo::make_echo_request(config.clone(), |port| async move {
+        let connector = HttpsConnector::new(config);
+        let client: Client<_, Empty<Bytes>> =
+            Client::builder(TokioExecutor::new()).build(connector);
+
+        let uri = Uri::from_str(format!("https://localhost:{port}").as_str())?;
+        let response = client.get(uri).await?;
+        assert_eq!(response.status(), 200);
+
+        let info = response.extensions().get::<TlsInfo>().unwrap();
+        assert_eq!(info.protocol_version(), Some(Version::TLS13));
+        assert!(info.cipher_suite().starts_with("TLS_"));
+        // The server isn't configured to negotiate ALPN.
+        assert_eq!(info.application_protocol(), None);
+        assert!(!info.resumed());
+
+        // The certificate chain sent by the server is reported.
+        assert!(!info.peer_cert_chain().is_empty());
+
+        Ok(())
+    })
+    .await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn server_request_extensions() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+
+    let server_task: JoinHandle<Result<(), Box<dyn Error + Send + Sync>>> =
+        tokio::spawn(async move {
+            let (tcp_stream, _) = listener.accept().await?;
+            let acceptor = TlsAcceptor::new(common::config()?.build()?);
+            let tls_stream = acceptor.accept(tcp_stream).await?;
+            let info = TlsInfo::from_connection(tls_stream.as_ref());
+
+            // Respond with the cipher suite found in the request extensions.
+            let service = TlsInfoService::new(
+                info,
+                service_fn(|request: Request<Incoming>| async move {
+                    let info = request.extensions().get::<TlsInfo>().unwrap();
+                    let body = Bytes::from(info.cipher_suite().to_owned());
+                    Ok::<_, Infallible>(Response::new(Full::new(body)))
+                }),
+            );
+
+            hyper_util::server::conn::auto::Builder::new(TokioExecutor::new())
+                .serve_connection(TokioIo::new(tls_stream), service)
+                .await
+        });
+
+    let connector = HttpsConnector::new(common::config()?.build()?);
+    let client: Client<_, Empty<Bytes>> = Client::builder(TokioExecutor::new()).build(connector);
+    let uri = Uri::from_str(format!("https://localhost:{}", addr.port()).as_str())?;
+    let response = client.get(uri).await?;
+    assert_eq!(response.status(), 200);
+
+    let client_info = response.extensions().get::<TlsInfo>().unwrap().clone();
+    let body = response.into_body().collect().await?.to_bytes();
+    assert_eq!(body, client_info.cipher_suite().as_bytes());
+
+    // The hyper client doesn't wait for the server's shutdown, so the server's result is ignored.
+    server_task.abort();
+    Ok(())
+}
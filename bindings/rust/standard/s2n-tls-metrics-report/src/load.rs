@@ -0,0 +1,120 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{error::Error, fs, io::BufReader, path::Path};
+
+use s2n_tls_metrics_schema::record::MetricRecord;
+
+/// Read every record from the `.json`, `.jsonl`, and `.cbor` files in `dir`.
+///
+/// Files with other extensions are skipped. Files are read in name order, and
+/// a malformed file fails the whole load rather than producing a partial
+/// report.
+pub fn load_dir(dir: &Path) -> Result<Vec<MetricRecord>, Box<dyn Error>> {
+    let mut paths = fs::read_dir(dir)
+        .map_err(|e| format!("{}: {e}", dir.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    paths.sort();
+
+    let mut records = Vec::new();
+    for path in paths {
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        let result = match extension {
+            Some("json" | "jsonl") => load_json(&path, &mut records),
+            Some("cbor") => load_cbor(&path, &mut records),
+            _ => continue,
+        };
+        result.map_err(|e| format!("{}: {e}", path.display()))?;
+    }
+    Ok(records)
+}
+
+fn load_json(path: &Path, records: &mut Vec<MetricRecord>) -> Result<(), Box<dyn Error>> {
+    let reader = BufReader::new(fs::File::open(path)?);
+    for record in serde_json::Deserializer::from_reader(reader).into_iter() {
+        records.push(record?);
+    }
+    Ok(())
+}
+
+fn load_cbor(path: &Path, records: &mut Vec<MetricRecord>) -> Result<(), Box<dyn Error>> {
+    let contents = fs::read(path)?;
+    let mut remaining = contents.as_slice();
+    while !remaining.is_empty() {
+        records.push(ciborium::from_reader(&mut remaining)?);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use s2n_tls_metrics_schema::{attribution::Attribution, record::FrozenHandshakeRecord};
+
+    use super::*;
+
+    fn record(success: u64) -> MetricRecord {
+        MetricRecord {
+            attribution: Attribution {
+                service: "service".to_owned(),
+                resource: "resource".to_owned(),
+                component: String::new(),
+            },
+            handshake: FrozenHandshakeRecord {
+                handshake_success_count: success,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "s2n-tls-metrics-report-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn load_json_and_cbor() {
+        let dir = test_dir("load");
+
+        let mut json = Vec::new();
+        for success in [1, 2] {
+            serde_json::to_writer(&mut json, &record(success)).unwrap();
+            json.push(b'\n');
+        }
+        fs::write(dir.join("a.jsonl"), json).unwrap();
+
+        let mut cbor = Vec::new();
+        ciborium::into_writer(&record(3), &mut cbor).unwrap();
+        ciborium::into_writer(&record(4), &mut cbor).unwrap();
+        fs::write(dir.join("b.cbor"), cbor).unwrap();
+
+        fs::write(dir.join("notes.txt"), "not a record").unwrap();
+
+        let records = load_dir(&dir).unwrap();
+        let successes: Vec<u64> = records
+            .iter()
+            .map(|record| record.handshake.handshake_success_count)
+            .collect();
+        assert_eq!(successes, vec![1, 2, 3, 4]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn malformed_file_names_path() {
+        let dir = test_dir("malformed");
+        fs::write(dir.join("bad.json"), "{ not json").unwrap();
+
+        let error = load_dir(&dir).unwrap_err().to_string();
+        assert!(error.contains("bad.json"), "{error}");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,47 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Summarize a directory of serialized s2n-tls metric records.
+//!
+//! ```text
+//! s2n-tls-metrics-report <DIR>
+//! ```
+//!
+//! Every `.json`/`.jsonl` file (a sequence of JSON `MetricRecord`s) and every
+//! `.cbor` file (a sequence of CBOR `MetricRecord`s) in `DIR` is read. Records
+//! with the same attribution are merged, regardless of which host or time
+//! window they came from, and a report is printed for each attribution.
+
+mod load;
+mod report;
+
+use std::{path::PathBuf, process::ExitCode};
+
+const USAGE: &str = "usage: s2n-tls-metrics-report <DIR>";
+
+fn main() -> ExitCode {
+    let mut args = std::env::args_os().skip(1);
+    let (Some(dir), None) = (args.next(), args.next()) else {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+    if dir == "-h" || dir == "--help" {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+
+    let records = match load::load_dir(&PathBuf::from(dir)) {
+        Ok(records) => records,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if records.is_empty() {
+        eprintln!("error: no metric records found");
+        return ExitCode::FAILURE;
+    }
+
+    print!("{}", report::render(&report::merge(records)));
+    ExitCode::SUCCESS
+}
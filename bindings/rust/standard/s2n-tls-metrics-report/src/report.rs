@@ -0,0 +1,358 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::{BTreeMap, btree_map::Entry},
+    fmt::{Display, Write as _},
+    time::SystemTime,
+};
+
+use s2n_tls_metrics_schema::{
+    attribution::Attribution,
+    counter::FrozenCounter,
+    record::{FrozenHandshakeRecord, MetricRecord},
+    static_lists::FiniteCounter,
+};
+use tabled::{Table, Tabled, settings::Style};
+
+/// The merge of every record with the same attribution.
+#[derive(Debug)]
+pub struct Group {
+    pub attribution: Attribution,
+    /// the number of records merged into `handshake`
+    pub records: usize,
+    /// the earliest freeze time of the merged records. The latest is the
+    /// freeze time of `handshake`.
+    pub first_freeze_time: SystemTime,
+    pub handshake: FrozenHandshakeRecord,
+}
+
+/// Merge `records` by attribution, ordered by service, resource, then
+/// component.
+pub fn merge(records: Vec<MetricRecord>) -> Vec<Group> {
+    let mut groups = BTreeMap::new();
+    for MetricRecord {
+        attribution,
+        handshake,
+    } in records
+    {
+        let key = (
+            attribution.service.clone(),
+            attribution.resource.clone(),
+            attribution.component.clone(),
+        );
+        match groups.entry(key) {
+            Entry::Vacant(entry) => {
+                entry.insert(Group {
+                    attribution,
+                    records: 1,
+                    first_freeze_time: handshake.freeze_time,
+                    handshake,
+                });
+            }
+            Entry::Occupied(mut entry) => {
+                let group = entry.get_mut();
+                group.records += 1;
+                group.first_freeze_time = group.first_freeze_time.min(handshake.freeze_time);
+                group.handshake.merge(&handshake);
+            }
+        }
+    }
+    groups.into_values().collect()
+}
+
+#[derive(Tabled)]
+struct ParameterRow {
+    parameter: &'static str,
+    value: String,
+    count: u64,
+    share: String,
+}
+
+#[derive(Tabled)]
+struct CountRow {
+    name: String,
+    count: u64,
+    share: String,
+}
+
+/// `count` as a percentage of `total`.
+fn share(count: u64, total: u64) -> String {
+    if total == 0 {
+        "-".to_owned()
+    } else {
+        format!("{:.1}%", count as f64 * 100.0 / total as f64)
+    }
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+fn parameter_rows<const N: usize, T: FiniteCounter<N> + Display>(
+    rows: &mut Vec<ParameterRow>,
+    parameter: &'static str,
+    counter: &FrozenCounter<N, T>,
+    total: u64,
+) {
+    rows.extend(
+        counter
+            .iter_non_zero()
+            .map(|(_, element, count)| ParameterRow {
+                parameter,
+                value: element.to_string(),
+                count,
+                share: share(count, total),
+            }),
+    );
+}
+
+fn write_table<R: Tabled>(output: &mut String, title: &str, rows: Vec<R>) {
+    // writing to a String is infallible
+    let _ = writeln!(output, "## {title}\n");
+    if rows.is_empty() {
+        output.push_str("none\n\n");
+    } else {
+        let mut table = Table::new(rows);
+        table.with(Style::markdown());
+        let _ = writeln!(output, "{table}\n");
+    }
+}
+
+/// Render a markdown report with a section for each group.
+pub fn render(groups: &[Group]) -> String {
+    let mut output = String::new();
+    for group in groups {
+        render_group(&mut output, group);
+    }
+    output
+}
+
+fn render_group(output: &mut String, group: &Group) {
+    let Group {
+        attribution,
+        records,
+        first_freeze_time,
+        handshake,
+    } = group;
+    // Only successful handshakes record their parameters, client issues, and
+    // compatibility, so they are all shares of the successful handshakes.
+    let successes = handshake.handshake_success_count;
+    let handshakes = successes + handshake.handshake_failure_count;
+
+    let _ = writeln!(
+        output,
+        "# service={:?} resource={:?} component={:?}\n",
+        attribution.service, attribution.resource, attribution.component
+    );
+    let _ = writeln!(
+        output,
+        "records: {records}, frozen between {} and {} (seconds since the unix epoch)",
+        unix_seconds(*first_freeze_time),
+        unix_seconds(handshake.freeze_time),
+    );
+    let _ = writeln!(
+        output,
+        "handshakes: {successes} succeeded, {} failed ({} failed)\n",
+        handshake.handshake_failure_count,
+        share(handshake.handshake_failure_count, handshakes),
+    );
+
+    let mut negotiated = Vec::new();
+    parameter_rows(
+        &mut negotiated,
+        "protocol",
+        &handshake.negotiated_protocols,
+        successes,
+    );
+    parameter_rows(
+        &mut negotiated,
+        "cipher",
+        &handshake.negotiated_ciphers,
+        successes,
+    );
+    parameter_rows(
+        &mut negotiated,
+        "group",
+        &handshake.negotiated_groups,
+        successes,
+    );
+    parameter_rows(
+        &mut negotiated,
+        "signature",
+        &handshake.negotiated_signatures,
+        successes,
+    );
+    write_table(
+        output,
+        "Negotiated parameters (share of successful handshakes)",
+        negotiated,
+    );
+
+    let mut supported = Vec::new();
+    parameter_rows(
+        &mut supported,
+        "protocol",
+        &handshake.supported_protocols,
+        successes,
+    );
+    parameter_rows(
+        &mut supported,
+        "cipher",
+        &handshake.supported_ciphers,
+        successes,
+    );
+    parameter_rows(
+        &mut supported,
+        "group",
+        &handshake.supported_groups,
+        successes,
+    );
+    parameter_rows(
+        &mut supported,
+        "signature",
+        &handshake.supported_signatures,
+        successes,
+    );
+    write_table(
+        output,
+        "Supported parameters (share of successful handshakes)",
+        supported,
+    );
+
+    let issues = handshake
+        .client_issues
+        .iter_non_zero()
+        .map(|(_, issue, count)| CountRow {
+            name: issue.to_string(),
+            count,
+            share: share(count, successes),
+        })
+        .collect();
+    write_table(
+        output,
+        "Client issues (share of successful handshakes)",
+        issues,
+    );
+
+    let builtin = [
+        ("general20251201", handshake.compatibility_general20251201),
+        ("fips20251201", handshake.compatibility_fips20251201),
+        ("cnsa1", handshake.compatibility_cnsa1),
+        ("cnsa2", handshake.compatibility_cnsa2),
+    ]
+    .into_iter()
+    .map(|(name, count)| (name.to_owned(), count));
+    let profiles = handshake
+        .compatibility_profiles
+        .entries
+        .iter()
+        .map(|(name, &count)| (name.clone(), count));
+    let overflow = Some(handshake.compatibility_profiles.overflow)
+        .filter(|&count| count > 0)
+        .map(|count| ("(other profiles)".to_owned(), count));
+    let compatibility = builtin
+        .chain(profiles)
+        .chain(overflow)
+        .map(|(name, count)| CountRow {
+            name,
+            count,
+            share: share(count, successes),
+        })
+        .collect();
+    write_table(
+        output,
+        "Compatible clients (share of successful handshakes)",
+        compatibility,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use s2n_tls_metrics_schema::{
+        bounded_set::FrozenBoundedStringCounter,
+        static_lists::{CIPHER_COUNT, Cipher},
+    };
+
+    use super::*;
+
+    fn record(service: &str, freeze_secs: u64, success: u64) -> MetricRecord {
+        MetricRecord {
+            attribution: Attribution {
+                service: service.to_owned(),
+                resource: "resource".to_owned(),
+                component: String::new(),
+            },
+            handshake: FrozenHandshakeRecord {
+                freeze_time: SystemTime::UNIX_EPOCH + Duration::from_secs(freeze_secs),
+                handshake_success_count: success,
+                compatibility_cnsa2: success,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn merge_groups_by_attribution() {
+        let groups = merge(vec![
+            record("b", 120, 1),
+            record("a", 60, 2),
+            record("b", 60, 3),
+        ]);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].attribution.service, "a");
+        assert_eq!(groups[0].records, 1);
+
+        let b = &groups[1];
+        assert_eq!(b.records, 2);
+        assert_eq!(unix_seconds(b.first_freeze_time), 60);
+        assert_eq!(unix_seconds(b.handshake.freeze_time), 120);
+        assert_eq!(b.handshake.handshake_success_count, 4);
+    }
+
+    #[test]
+    fn render_tables() {
+        // 4 of the 8 handshakes succeeded. Every success was cnsa2 compatible,
+        // half of them legacy compatible, and all of them supported the cipher.
+        let mut record = record("service", 60, 4);
+        record.handshake.handshake_failure_count = 4;
+        let mut slots = [0; CIPHER_COUNT];
+        slots[0] = 4;
+        record.handshake.negotiated_ciphers = FrozenCounter::from_slots(slots);
+        record.handshake.supported_ciphers = FrozenCounter::from_slots(slots);
+        record.handshake.compatibility_profiles = FrozenBoundedStringCounter {
+            entries: [("legacy".to_owned(), 2)].into(),
+            overflow: 0,
+        };
+
+        let report = render(&merge(vec![record]));
+
+        assert!(report.contains("handshakes: 4 succeeded, 4 failed (50.0% failed)"));
+        assert!(report.contains("## Client issues (share of successful handshakes)\n\nnone\n"));
+        let cipher = Cipher::key_from_slot(0).unwrap().to_string();
+        // whether the table titled `title` has a row containing every cell
+        let has_row = |title: &str, cells: &[&str]| {
+            let table = report.split("## ").find(|table| table.starts_with(title));
+            table
+                .unwrap()
+                .lines()
+                .any(|line| cells.iter().all(|cell| line.contains(&format!(" {cell} "))))
+        };
+        let cipher_row = ["cipher", &cipher, "4", "100.0%"];
+        assert!(has_row("Negotiated parameters", &cipher_row), "{report}");
+        assert!(has_row("Supported parameters", &cipher_row), "{report}");
+        assert!(
+            has_row("Compatible clients", &["cnsa2", "4", "100.0%"]),
+            "{report}"
+        );
+        assert!(
+            has_row("Compatible clients", &["legacy", "2", "50.0%"]),
+            "{report}"
+        );
+    }
+}
@@ -1,7 +1,7 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 use serde::{Deserialize, Serialize};
 
@@ -12,8 +12,57 @@ pub enum FrozenBoundedStringSet {
     Entries(BTreeSet<String>),
 }
 
+impl FrozenBoundedStringSet {
+    /// Add the values of `other` to this set. If either set had too many
+    /// values, so does the merged set.
+    pub fn merge(&mut self, other: &Self) {
+        match (&mut *self, other) {
+            (FrozenBoundedStringSet::Entries(entries), FrozenBoundedStringSet::Entries(other)) => {
+                entries.extend(other.iter().cloned());
+            }
+            _ => *self = FrozenBoundedStringSet::TooMany,
+        }
+    }
+}
+
 impl Default for FrozenBoundedStringSet {
     fn default() -> Self {
         FrozenBoundedStringSet::Entries(BTreeSet::new())
     }
 }
+
+/// Counts for a bounded number of distinct strings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FrozenBoundedStringCounter {
+    #[serde(default)]
+    pub entries: BTreeMap<String, u64>,
+    /// the number of values which aren't counted in `entries`, because it was
+    /// full
+    #[serde(default)]
+    pub overflow: u64,
+}
+
+impl FrozenBoundedStringCounter {
+    /// Returns the count for `item`, which is 0 if `item` is not present.
+    ///
+    /// Values which overflowed are only counted in `overflow`.
+    pub fn get(&self, item: &str) -> u64 {
+        self.entries.get(item).copied().unwrap_or(0)
+    }
+
+    /// Sum of all counted values, including overflow.
+    pub fn total(&self) -> u64 {
+        self.entries.values().sum::<u64>() + self.overflow
+    }
+
+    /// Add the counts of `other` to this counter.
+    ///
+    /// The merged counter isn't bounded, so it may have more entries than
+    /// either of its inputs.
+    pub fn merge(&mut self, other: &Self) {
+        for (item, count) in &other.entries {
+            *self.entries.entry(item.clone()).or_default() += count;
+        }
+        self.overflow += other.overflow;
+    }
+}
@@ -68,6 +68,13 @@ impl<const N: usize, T: FiniteCounter<N>> FrozenCounter<N, T> {
             .map(|slot| self.slots[slot])
             .unwrap_or(0)
     }
+
+    /// Add the counts of `other` to this counter.
+    pub fn merge(&mut self, other: &Self) {
+        for (slot, count) in self.slots.iter_mut().zip(other.slots.iter()) {
+            *slot += count;
+        }
+    }
 }
 
 impl<const N: usize, T: FiniteCounter<N>> Default for FrozenCounter<N, T> {
@@ -247,6 +254,21 @@ mod tests {
         let slot_7_cipher = Cipher::key_from_slot(7).unwrap();
         assert_eq!(pairs, vec![(2, slot_2_cipher, 3), (7, slot_7_cipher, 5)]);
     }
+
+    #[test]
+    fn frozen_counter_merge_adds_slots() {
+        let mut slots = [0u64; CIPHER_COUNT];
+        slots[2] = 3;
+        let mut merged = FrozenCounter::<CIPHER_COUNT, Cipher>::from_slots(slots);
+        slots[7] = 5;
+        merged.merge(&FrozenCounter::from_slots(slots));
+
+        let pairs: Vec<(usize, u64)> = merged
+            .iter_non_zero()
+            .map(|(slot, _, count)| (slot, count))
+            .collect();
+        assert_eq!(pairs, vec![(2, 6), (7, 5)]);
+    }
 }
 
 #[cfg(test)]
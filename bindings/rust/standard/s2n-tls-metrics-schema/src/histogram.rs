@@ -0,0 +1,410 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Fixed-bucket latency histograms used by the handshake record.
+//!
+//! Buckets are log-linear over microseconds. Values below 4µs each get their
+//! own bucket, and every power of two above that is split into 4 equal-width
+//! buckets, so a bucket is never wider than 25% of its lower bound. Values of
+//! [`HISTOGRAM_MAX_US`] or more all land in the last bucket.
+//!
+//! Because the bucket layout is fixed, histograms from different records (or
+//! different hosts) can be merged by adding their buckets together.
+
+use std::marker::PhantomData;
+
+use crate::static_lists::FiniteCounter;
+
+const SUB_BUCKET_BITS: u32 = 2;
+const SUB_BUCKETS: usize = 1 << SUB_BUCKET_BITS;
+
+/// Number of buckets in a [`FrozenHistogram`].
+pub const HISTOGRAM_BUCKETS: usize = 100;
+
+/// Values at or above this many microseconds (~67 seconds) share the last bucket.
+pub const HISTOGRAM_MAX_US: u64 = 1 << (HISTOGRAM_BUCKETS / SUB_BUCKETS + 1);
+
+/// Index of the bucket that `value_us` is counted in.
+pub fn bucket_index(value_us: u64) -> usize {
+    if value_us < SUB_BUCKETS as u64 {
+        return value_us as usize;
+    }
+    let exponent = u64::BITS - 1 - value_us.leading_zeros();
+    let sub_bucket = (value_us >> (exponent - SUB_BUCKET_BITS)) as usize & (SUB_BUCKETS - 1);
+    let index = (exponent - SUB_BUCKET_BITS + 1) as usize * SUB_BUCKETS + sub_bucket;
+    index.min(HISTOGRAM_BUCKETS - 1)
+}
+
+/// Smallest value, in microseconds, counted in the bucket at `index`.
+pub fn bucket_lower_bound(index: usize) -> u64 {
+    if index < SUB_BUCKETS {
+        return index as u64;
+    }
+    let exponent = (index / SUB_BUCKETS) as u32 + SUB_BUCKET_BITS - 1;
+    let sub_bucket = (index % SUB_BUCKETS) as u64;
+    (1 << exponent) + sub_bucket * (1 << (exponent - SUB_BUCKET_BITS))
+}
+
+/// Largest value, in microseconds, counted in the bucket at `index`.
+///
+/// The last bucket is unbounded, so its lower bound is returned instead.
+pub fn bucket_upper_bound(index: usize) -> u64 {
+    if index + 1 >= HISTOGRAM_BUCKETS {
+        bucket_lower_bound(HISTOGRAM_BUCKETS - 1)
+    } else {
+        bucket_lower_bound(index + 1) - 1
+    }
+}
+
+/// Exportable, immutable snapshot of a latency histogram.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrozenHistogram {
+    pub buckets: [u64; HISTOGRAM_BUCKETS],
+}
+
+impl FrozenHistogram {
+    /// Creates a `FrozenHistogram` from raw bucket values.
+    pub fn from_buckets(buckets: [u64; HISTOGRAM_BUCKETS]) -> Self {
+        Self { buckets }
+    }
+
+    /// The underlying bucket array, ordered from smallest to largest value.
+    pub fn buckets(&self) -> &[u64; HISTOGRAM_BUCKETS] {
+        &self.buckets
+    }
+
+    /// Count a single observation of `value_us` microseconds.
+    pub fn record(&mut self, value_us: u64) {
+        self.buckets[bucket_index(value_us)] += 1;
+    }
+
+    /// Add the observations of `other` to this histogram.
+    pub fn merge(&mut self, other: &FrozenHistogram) {
+        for (bucket, count) in self.buckets.iter_mut().zip(other.buckets.iter()) {
+            *bucket += count;
+        }
+    }
+
+    /// Total number of observations.
+    pub fn count(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buckets.iter().all(|&c| c == 0)
+    }
+
+    /// Estimate the `quantile` (e.g. `0.99` for p99) in microseconds.
+    ///
+    /// Returns the upper bound of the bucket containing the quantile, so the
+    /// estimate errs on the side of being too slow. Returns `None` if the
+    /// histogram is empty.
+    pub fn quantile(&self, quantile: f64) -> Option<u64> {
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+        // the rank of the observation at `quantile`, in [1, count]
+        let rank = ((quantile.clamp(0.0, 1.0) * count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (index, &bucket) in self.buckets.iter().enumerate() {
+            seen += bucket;
+            if seen >= rank {
+                return Some(bucket_upper_bound(index));
+            }
+        }
+        None
+    }
+
+    /// `(lower bound in µs, count)` pairs for non-empty buckets, in bucket order.
+    pub fn iter_non_zero(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.buckets
+            .iter()
+            .enumerate()
+            .filter(|&(_, &c)| c > 0)
+            .map(|(index, &c)| (bucket_lower_bound(index), c))
+    }
+}
+
+impl Default for FrozenHistogram {
+    fn default() -> Self {
+        Self {
+            buckets: [0u64; HISTOGRAM_BUCKETS],
+        }
+    }
+}
+
+impl serde::Serialize for FrozenHistogram {
+    /// Emit non-empty buckets as a sequence of `(lower bound in µs, count)`
+    /// pairs. Using the lower bound rather than the bucket index keeps the
+    /// wire format meaningful if the bucket layout ever changes.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeSeq;
+        let non_zero_count = self.buckets.iter().filter(|&&c| c > 0).count();
+        let mut seq = serializer.serialize_seq(Some(non_zero_count))?;
+        for pair in self.iter_non_zero() {
+            seq.serialize_element(&pair)?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for FrozenHistogram {
+    /// Decode a sequence of `(µs, u64)` pairs. Each count is added to the
+    /// bucket containing its value, so missing buckets default to 0.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SeqVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for SeqVisitor {
+            type Value = FrozenHistogram;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a sequence of (u64, u64) pairs")
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut access: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut histogram = FrozenHistogram::default();
+                while let Some((value_us, count)) = access.next_element::<(u64, u64)>()? {
+                    histogram.buckets[bucket_index(value_us)] += count;
+                }
+                Ok(histogram)
+            }
+        }
+
+        deserializer.deserialize_seq(SeqVisitor)
+    }
+}
+
+/// Exportable, immutable snapshot of one histogram per value of `T`, e.g. one
+/// latency histogram per handshake message.
+#[derive(Clone, PartialEq)]
+pub struct FrozenHistograms<const N: usize, T: FiniteCounter<N>> {
+    pub histograms: [FrozenHistogram; N],
+    pub element: PhantomData<T>,
+}
+
+impl<const N: usize, T: FiniteCounter<N>> std::fmt::Debug for FrozenHistograms<N, T>
+where
+    T: std::fmt::Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = format!("FrozenHistograms<{}>", std::any::type_name::<T>());
+        let mut dbg = f.debug_struct(&name);
+        for (_slot, element, histogram) in self.iter_non_empty() {
+            dbg.field(&element.to_string(), histogram);
+        }
+        dbg.finish()
+    }
+}
+
+impl<const N: usize, T: FiniteCounter<N>> FrozenHistograms<N, T> {
+    /// Creates a `FrozenHistograms` from one histogram per slot.
+    pub fn from_histograms(histograms: [FrozenHistogram; N]) -> Self {
+        Self {
+            histograms,
+            element: PhantomData,
+        }
+    }
+
+    /// Returns the histogram for a given element.
+    pub fn get(&self, element: &T) -> Option<&FrozenHistogram> {
+        element
+            .slot_from_key()
+            .and_then(|slot| self.histograms.get(slot))
+    }
+
+    /// Add the observations of `other` to these histograms.
+    pub fn merge(&mut self, other: &Self) {
+        for (histogram, other) in self.histograms.iter_mut().zip(other.histograms.iter()) {
+            histogram.merge(other);
+        }
+    }
+
+    /// `(slot, element, histogram)` triples for non-empty histograms, in slot order.
+    pub fn iter_non_empty(&self) -> impl Iterator<Item = (usize, T, &FrozenHistogram)> + '_ {
+        self.histograms
+            .iter()
+            .enumerate()
+            .filter(|(_, histogram)| !histogram.is_empty())
+            .filter_map(|(slot, histogram)| {
+                T::key_from_slot(slot).map(|key| (slot, key, histogram))
+            })
+    }
+}
+
+impl<const N: usize, T: FiniteCounter<N>> Default for FrozenHistograms<N, T> {
+    fn default() -> Self {
+        Self {
+            histograms: std::array::from_fn(|_| FrozenHistogram::default()),
+            element: PhantomData,
+        }
+    }
+}
+
+impl<const N: usize, T> serde::Serialize for FrozenHistograms<N, T>
+where
+    T: FiniteCounter<N> + serde::Serialize,
+{
+    /// Emit non-empty histograms as a sequence of `(T, FrozenHistogram)` pairs.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeSeq;
+        let non_empty_count = self.histograms.iter().filter(|h| !h.is_empty()).count();
+        let mut seq = serializer.serialize_seq(Some(non_empty_count))?;
+        for (_slot, element, histogram) in self.iter_non_empty() {
+            seq.serialize_element(&(element, histogram))?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de, const N: usize, T> serde::Deserialize<'de> for FrozenHistograms<N, T>
+where
+    T: FiniteCounter<N> + serde::de::DeserializeOwned + std::fmt::Display,
+{
+    /// Decode a sequence of `(T, FrozenHistogram)` pairs. Elements unknown to
+    /// this build's `ELEMENTS` are dropped (and logged at `debug!`), matching
+    /// [`FrozenCounter`](crate::counter::FrozenCounter).
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SeqVisitor<const N: usize, T: FiniteCounter<N>>(PhantomData<T>);
+
+        impl<'de, const N: usize, T> serde::de::Visitor<'de> for SeqVisitor<N, T>
+        where
+            T: FiniteCounter<N> + serde::de::DeserializeOwned + std::fmt::Display,
+        {
+            type Value = FrozenHistograms<N, T>;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(
+                    f,
+                    "a sequence of ({}, histogram) pairs",
+                    std::any::type_name::<T>()
+                )
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut access: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut histograms = FrozenHistograms::<N, T>::default();
+                while let Some((element, histogram)) =
+                    access.next_element::<(T, FrozenHistogram)>()?
+                {
+                    match element.slot_from_key() {
+                        Some(slot) => histograms.histograms[slot] = histogram,
+                        None => tracing::debug!(
+                            kind = std::any::type_name::<T>(),
+                            unknown = %element,
+                            "FrozenHistograms deserialize dropped unknown element",
+                        ),
+                    }
+                }
+                Ok(histograms)
+            }
+        }
+
+        deserializer.deserialize_seq(SeqVisitor::<N, T>(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::static_lists::HandshakeMessage;
+
+    #[test]
+    fn bucket_bounds_roundtrip() {
+        for index in 0..HISTOGRAM_BUCKETS {
+            let lower = bucket_lower_bound(index);
+            let upper = bucket_upper_bound(index);
+            assert_eq!(bucket_index(lower), index, "lower bound of {index}");
+            assert_eq!(bucket_index(upper), index, "upper bound of {index}");
+            if index + 1 < HISTOGRAM_BUCKETS {
+                assert_eq!(bucket_lower_bound(index + 1), upper + 1);
+            }
+        }
+    }
+
+    #[test]
+    fn bucket_width_is_bounded() {
+        for index in SUB_BUCKETS..HISTOGRAM_BUCKETS - 1 {
+            let lower = bucket_lower_bound(index);
+            let width = bucket_upper_bound(index) - lower + 1;
+            assert!(width * SUB_BUCKETS as u64 <= lower, "bucket {index}");
+        }
+    }
+
+    #[test]
+    fn large_values_share_last_bucket() {
+        assert_eq!(bucket_index(HISTOGRAM_MAX_US - 1), HISTOGRAM_BUCKETS - 1);
+        assert_eq!(bucket_index(HISTOGRAM_MAX_US), HISTOGRAM_BUCKETS - 1);
+        assert_eq!(bucket_index(u64::MAX), HISTOGRAM_BUCKETS - 1);
+    }
+
+    #[test]
+    fn quantiles() {
+        let mut histogram = FrozenHistogram::default();
+        assert_eq!(histogram.quantile(0.5), None);
+
+        for value in 1..=100 {
+            histogram.record(value * 100);
+        }
+        assert_eq!(histogram.count(), 100);
+
+        // the estimate is the upper bound of the bucket, which is at most
+        // 25% larger than the real value.
+        for (quantile, exact) in [(0.0, 100), (0.5, 5_000), (0.99, 9_900), (1.0, 10_000)] {
+            let estimate = histogram.quantile(quantile).unwrap();
+            assert!(estimate >= exact, "p{quantile}: {estimate} < {exact}");
+            assert!(
+                estimate <= exact + exact / 4,
+                "p{quantile}: {estimate} > {exact}"
+            );
+        }
+    }
+
+    #[test]
+    fn merge_adds_buckets() {
+        let mut a = FrozenHistogram::default();
+        a.record(10);
+        a.record(1_000);
+        let mut b = FrozenHistogram::default();
+        b.record(10);
+
+        a.merge(&b);
+        assert_eq!(a.count(), 3);
+        assert_eq!(a.buckets()[bucket_index(10)], 2);
+        assert_eq!(a.buckets()[bucket_index(1_000)], 1);
+    }
+
+    #[test]
+    fn frozen_histogram_serde_roundtrip() {
+        let mut histogram = FrozenHistogram::default();
+        histogram.record(3);
+        histogram.record(1_000);
+        histogram.record(1_001);
+
+        let value = serde_json::to_value(&histogram).unwrap();
+        assert_eq!(value, serde_json::json!([[3, 1], [896, 2]]));
+
+        let decoded: FrozenHistogram = serde_json::from_value(value).unwrap();
+        assert_eq!(decoded, histogram);
+    }
+
+    #[test]
+    fn frozen_histograms_serde_roundtrip() {
+        let mut histograms =
+            FrozenHistograms::<{ HandshakeMessage::COUNT }, HandshakeMessage>::default();
+        let slot = HandshakeMessage::ServerCert.slot_from_key().unwrap();
+        histograms.histograms[slot].record(100);
+
+        let value = serde_json::to_value(&histograms).unwrap();
+        assert_eq!(value, serde_json::json!([["SERVER_CERT", [[96, 1]]]]));
+
+        let decoded: FrozenHistograms<{ HandshakeMessage::COUNT }, HandshakeMessage> =
+            serde_json::from_value(value).unwrap();
+        assert_eq!(decoded, histograms);
+    }
+}
@@ -10,6 +10,7 @@
 pub mod attribution;
 pub mod bounded_set;
 pub mod counter;
+pub mod histogram;
 pub mod metric_names;
 pub mod record;
 pub mod static_lists;
@@ -15,8 +15,8 @@ use std::{
 
 use crate::static_lists::{
     Alert, CERT_KEY_COUNT, CERT_SIG_COUNT, CIPHER_COUNT, CertKeyType, CertSignatureAlgorithm,
-    Cipher, ClientIssue, DEFINED_ALERTS_COUNT, FiniteCounter, GROUP_COUNT, Group, PROTOCOL_COUNT,
-    SIGNATURE_COUNT, Signature, Version,
+    Cipher, ClientIssue, DEFINED_ALERTS_COUNT, EarlyData, ErrorType, FiniteCounter, GROUP_COUNT,
+    Group, HandshakeMessage, PROTOCOL_COUNT, Resumption, SIGNATURE_COUNT, Signature, Version,
 };
 
 /// Cache key keyed by slot index so the cache type stays non-generic.
@@ -75,6 +75,16 @@ pub const COMPATIBILITY_GENERAL20251201: &str = "compatibility.general20251201";
 pub const COMPATIBILITY_FIPS20251201: &str = "compatibility.fips20251201";
 pub const COMPATIBILITY_CNSA1: &str = "compatibility.cnsa1";
 pub const COMPATIBILITY_CNSA2: &str = "compatibility.cnsa2";
+pub const COMPATIBILITY_PROFILE_PREFIX: &str = "compatibility.profile";
+/// The number of compatible handshakes which weren't recorded, because too many
+/// distinct profiles were seen.
+pub const COMPATIBILITY_PROFILE_TOO_MANY: &str = "compatibility.profile.TOO_MANY";
+
+/// e.g. "compatibility.profile.my-policy"
+pub fn compatibility_profile_name(profile: &str) -> String {
+    format!("{COMPATIBILITY_PROFILE_PREFIX}.{profile}")
+}
+
 pub const SECURITY_POLICY_PREFIX: &str = "tls_policy";
 pub const SECURITY_POLICY_TOO_MANY: &str = "tls_policy.TOO_MANY";
 
@@ -82,7 +92,28 @@ pub fn security_policy_name(policy: &str) -> String {
     format!("{SECURITY_POLICY_PREFIX}.{policy}")
 }
 
+pub const HANDSHAKE_FAILURE_ERROR_PREFIX: &str = "handshake_failure.error";
+/// The number of failures whose error name wasn't recorded, because too many
+/// distinct error names were seen.
+pub const HANDSHAKE_FAILURE_ERROR_TOO_MANY: &str = "handshake_failure.error.TOO_MANY";
+
+/// e.g. "handshake_failure.error.S2N_ERR_CERT_UNTRUSTED"
+pub fn handshake_failure_error_name(error: &str) -> String {
+    format!("{HANDSHAKE_FAILURE_ERROR_PREFIX}.{error}")
+}
+
+pub const CLIENT_HELLO_JA4_PREFIX: &str = "client_hello.ja4";
+/// The number of handshakes whose JA4 fingerprint wasn't one of the most
+/// frequent fingerprints in the record.
+pub const CLIENT_HELLO_JA4_OTHER: &str = "client_hello.ja4.OTHER";
+
+/// e.g. "client_hello.ja4.t13d1516h2_8daaf6152771_02713d6af862"
+pub fn client_hello_ja4_name(fingerprint: &str) -> String {
+    format!("{CLIENT_HELLO_JA4_PREFIX}.{fingerprint}")
+}
+
 pub const SSLV2_CLIENT_HELLO: &str = "sslv2_client_hello";
+pub const HELLO_RETRY_REQUEST: &str = "hello_retry_request";
 pub const HANDSHAKE_DURATION_US: &str = "handshake_duration_us";
 pub const HANDSHAKE_COMPUTE_US: &str = "handshake_compute_us";
 pub const SYNTHETIC_TRAFFIC_COUNT: &str = "synthetic_traffic_count";
@@ -96,6 +127,7 @@ pub const ALL_SCALARS: &[&str] = &[
     SSLV2_CLIENT_HELLO,
     HANDSHAKE_SUCCESS_COUNT,
     HANDSHAKE_FAILURE_COUNT,
+    HELLO_RETRY_REQUEST,
     HANDSHAKE_DURATION_US,
     HANDSHAKE_COMPUTE_US,
     SYNTHETIC_TRAFFIC_COUNT,
@@ -104,6 +136,20 @@ pub const ALL_SCALARS: &[&str] = &[
     INTERNAL_FAILURE,
 ];
 
+pub const HANDSHAKE_DURATION_US_P50: &str = "handshake_duration_us.p50";
+pub const HANDSHAKE_DURATION_US_P99: &str = "handshake_duration_us.p99";
+pub const HANDSHAKE_COMPUTE_US_P50: &str = "handshake_compute_us.p50";
+pub const HANDSHAKE_COMPUTE_US_P99: &str = "handshake_compute_us.p99";
+
+/// Percentiles of the handshake latency histograms. Unlike [`ALL_SCALARS`],
+/// these are only emitted when the record contains at least one handshake.
+pub const ALL_LATENCY_PERCENTILES: &[&str] = &[
+    HANDSHAKE_DURATION_US_P50,
+    HANDSHAKE_DURATION_US_P99,
+    HANDSHAKE_COMPUTE_US_P50,
+    HANDSHAKE_COMPUTE_US_P99,
+];
+
 /// A counter group descriptor: prefix string, element count, and cached name accessor.
 ///
 /// Each group represents one (TlsParam, State) combination, e.g. "cipher.negotiated".
@@ -168,6 +214,72 @@ pub const CLIENT_ISSUES: CounterGroup = CounterGroup {
     name_from_slot: client_issue_metric_name,
 };
 
+fn resumption_metric_name(slot: usize, prefix: &'static str) -> &'static str {
+    telemetry_label(slot, Resumption::key_from_slot(slot).unwrap(), prefix)
+}
+
+fn early_data_metric_name(slot: usize, prefix: &'static str) -> &'static str {
+    telemetry_label(slot, EarlyData::key_from_slot(slot).unwrap(), prefix)
+}
+
+pub const RESUMPTION: CounterGroup = CounterGroup {
+    prefix: "resumption",
+    count: Resumption::COUNT,
+    name_from_slot: resumption_metric_name,
+};
+
+pub const EARLY_DATA: CounterGroup = CounterGroup {
+    prefix: "early_data",
+    count: EarlyData::COUNT,
+    name_from_slot: early_data_metric_name,
+};
+
+fn handshake_message_metric_name(slot: usize, prefix: &'static str) -> &'static str {
+    telemetry_label(slot, HandshakeMessage::key_from_slot(slot).unwrap(), prefix)
+}
+
+/// Percentiles of the time spent in each handshake message, e.g.
+/// `handshake_message_us.p99.SERVER_CERT_VERIFY`.
+pub mod handshake_message {
+    use super::*;
+
+    pub const P50: CounterGroup = CounterGroup {
+        prefix: "handshake_message_us.p50",
+        count: HandshakeMessage::COUNT,
+        name_from_slot: handshake_message_metric_name,
+    };
+    pub const P99: CounterGroup = CounterGroup {
+        prefix: "handshake_message_us.p99",
+        count: HandshakeMessage::COUNT,
+        name_from_slot: handshake_message_metric_name,
+    };
+
+    pub const ALL: &[&CounterGroup] = &[&P50, &P99];
+}
+
+fn error_type_metric_name(slot: usize, prefix: &'static str) -> &'static str {
+    telemetry_label(slot, ErrorType::key_from_slot(slot).unwrap(), prefix)
+}
+
+/// Breakdown of handshake failures, e.g. `handshake_failure.error_type.ProtocolError`.
+pub mod failure {
+    use super::*;
+
+    pub const ERROR_TYPES: CounterGroup = CounterGroup {
+        prefix: "handshake_failure.error_type",
+        count: ErrorType::COUNT,
+        name_from_slot: error_type_metric_name,
+    };
+    /// The handshake message being processed when the handshake failed.
+    pub const MESSAGES: CounterGroup = CounterGroup {
+        prefix: "handshake_failure.message",
+        count: HandshakeMessage::COUNT,
+        name_from_slot: handshake_message_metric_name,
+    };
+
+    pub const ALL: &[&CounterGroup] = &[&ERROR_TYPES, &MESSAGES];
+}
+
 fn cert_key_metric_name(slot: usize, prefix: &'static str) -> &'static str {
     telemetry_label(slot, CertKeyType::key_from_slot(slot).unwrap(), prefix)
 }
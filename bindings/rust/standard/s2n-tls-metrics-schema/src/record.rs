@@ -1,18 +1,19 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::time::SystemTime;
+use std::{borrow::Cow, time::SystemTime};
 
 use serde::{Deserialize, Serialize};
 
 use crate::{
     attribution::Attribution,
-    bounded_set::FrozenBoundedStringSet,
+    bounded_set::{FrozenBoundedStringCounter, FrozenBoundedStringSet},
     counter::FrozenCounter,
+    histogram::{FrozenHistogram, FrozenHistograms},
     static_lists::{
         Alert, CERT_KEY_COUNT, CERT_SIG_COUNT, CIPHER_COUNT, CertKeyType, CertSignatureAlgorithm,
-        Cipher, ClientIssue, DEFINED_ALERTS_COUNT, GROUP_COUNT, Group, PROTOCOL_COUNT,
-        SIGNATURE_COUNT, Signature, Version,
+        Cipher, ClientIssue, DEFINED_ALERTS_COUNT, EarlyData, ErrorType, GROUP_COUNT, Group,
+        HandshakeMessage, PROTOCOL_COUNT, Resumption, SIGNATURE_COUNT, Signature, Version,
     },
 };
 
@@ -66,6 +67,14 @@ pub struct FrozenHandshakeRecord {
 
     #[serde(default)]
     pub alerts: FrozenCounter<DEFINED_ALERTS_COUNT, Alert>,
+    /// The s2n-tls error name of each failed handshake, e.g. "S2N_ERR_CERT_UNTRUSTED".
+    #[serde(default)]
+    pub failure_errors: FrozenBoundedStringCounter,
+    #[serde(default)]
+    pub failure_error_types: FrozenCounter<{ ErrorType::COUNT }, ErrorType>,
+    /// The handshake message being processed when each handshake failed.
+    #[serde(default)]
+    pub failure_messages: FrozenCounter<{ HandshakeMessage::COUNT }, HandshakeMessage>,
 
     #[serde(default)]
     pub negotiated_protocols: FrozenCounter<PROTOCOL_COUNT, Version>,
@@ -116,14 +125,41 @@ pub struct FrozenHandshakeRecord {
     pub compatibility_cnsa1: u64,
     #[serde(default)]
     pub compatibility_cnsa2: u64,
+    /// Number of handshakes whose client could have handshaken with each
+    /// compatibility profile registered on the subscriber, by profile name.
+    #[serde(default)]
+    pub compatibility_profiles: FrozenBoundedStringCounter,
 
     #[serde(default)]
     pub client_issues: FrozenCounter<{ ClientIssue::COUNT }, ClientIssue>,
 
+    #[serde(default)]
+    pub resumption: FrozenCounter<{ Resumption::COUNT }, Resumption>,
+    /// Number of successful handshakes which included a HelloRetryRequest.
+    #[serde(default)]
+    pub hello_retry_request: u64,
+    #[serde(default)]
+    pub early_data: FrozenCounter<{ EarlyData::COUNT }, EarlyData>,
+    /// The most frequent JA4 fingerprints of the ClientHellos. Only populated
+    /// if fingerprinting was enabled on the subscriber.
+    #[serde(default)]
+    pub client_hello_ja4: FrozenBoundedStringCounter,
+
     #[serde(default)]
     pub handshake_duration_us: u64,
     #[serde(default)]
     pub handshake_compute_us: u64,
+    /// Distribution of the durations summed in `handshake_duration_us`.
+    #[serde(default)]
+    pub handshake_duration_histogram: FrozenHistogram,
+    /// Distribution of the compute times summed in `handshake_compute_us`.
+    #[serde(default)]
+    pub handshake_compute_histogram: FrozenHistogram,
+    /// Time spent processing each handshake message, measured from the
+    /// previous s2n-tls timing checkpoint.
+    #[serde(default)]
+    pub handshake_message_histograms:
+        FrozenHistograms<{ HandshakeMessage::COUNT }, HandshakeMessage>,
 
     #[serde(default)]
     pub synthetic_traffic_count: u64,
@@ -141,6 +177,9 @@ impl Default for FrozenHandshakeRecord {
             handshake_success_count: 0,
             handshake_failure_count: 0,
             alerts: FrozenCounter::default(),
+            failure_errors: FrozenBoundedStringCounter::default(),
+            failure_error_types: FrozenCounter::default(),
+            failure_messages: FrozenCounter::default(),
             negotiated_protocols: FrozenCounter::default(),
             negotiated_ciphers: FrozenCounter::default(),
             negotiated_groups: FrozenCounter::default(),
@@ -164,9 +203,17 @@ impl Default for FrozenHandshakeRecord {
             compatibility_fips20251201: 0,
             compatibility_cnsa1: 0,
             compatibility_cnsa2: 0,
+            compatibility_profiles: FrozenBoundedStringCounter::default(),
             client_issues: FrozenCounter::default(),
+            resumption: FrozenCounter::default(),
+            hello_retry_request: 0,
+            early_data: FrozenCounter::default(),
+            client_hello_ja4: FrozenBoundedStringCounter::default(),
             handshake_duration_us: 0,
             handshake_compute_us: 0,
+            handshake_duration_histogram: FrozenHistogram::default(),
+            handshake_compute_histogram: FrozenHistogram::default(),
+            handshake_message_histograms: FrozenHistograms::default(),
             synthetic_traffic_count: 0,
             internal_failure: 0,
             security_policies: Default::default(),
@@ -174,120 +221,355 @@ impl Default for FrozenHandshakeRecord {
     }
 }
 
-impl metrique_writer::Entry for FrozenHandshakeRecord {
-    fn write<'a>(&'a self, writer: &mut impl metrique_writer::EntryWriter<'a>) {
+impl FrozenHandshakeRecord {
+    /// Combine `other` into this record, e.g. to aggregate the records of
+    /// several hosts or time windows.
+    ///
+    /// Counts, sums, and histograms are added together, and the freeze time is
+    /// the later of the two.
+    pub fn merge(&mut self, other: &Self) {
+        // destructure, so that adding a field without merging it fails to compile
+        let FrozenHandshakeRecord {
+            freeze_time,
+            security_policies,
+            handshake_success_count,
+            handshake_failure_count,
+            alerts,
+            failure_errors,
+            failure_error_types,
+            failure_messages,
+            negotiated_protocols,
+            negotiated_ciphers,
+            negotiated_groups,
+            negotiated_signatures,
+            sslv2_client_hello,
+            supported_protocols,
+            supported_ciphers,
+            supported_groups,
+            supported_signatures,
+            server_leaf_cert_key,
+            server_leaf_cert_sig,
+            server_chain_cert_key,
+            server_chain_cert_sig,
+            client_leaf_cert_key,
+            client_leaf_cert_sig,
+            client_chain_cert_key,
+            client_chain_cert_sig,
+            server_cert_parsing_failure,
+            client_cert_parsing_failure,
+            compatibility_general20251201,
+            compatibility_fips20251201,
+            compatibility_cnsa1,
+            compatibility_cnsa2,
+            compatibility_profiles,
+            client_issues,
+            resumption,
+            hello_retry_request,
+            early_data,
+            client_hello_ja4,
+            handshake_duration_us,
+            handshake_compute_us,
+            handshake_duration_histogram,
+            handshake_compute_histogram,
+            handshake_message_histograms,
+            synthetic_traffic_count,
+            internal_failure,
+        } = other;
+
+        self.freeze_time = self.freeze_time.max(*freeze_time);
+        self.security_policies.merge(security_policies);
+        self.handshake_success_count += handshake_success_count;
+        self.handshake_failure_count += handshake_failure_count;
+        self.alerts.merge(alerts);
+        self.failure_errors.merge(failure_errors);
+        self.failure_error_types.merge(failure_error_types);
+        self.failure_messages.merge(failure_messages);
+        self.negotiated_protocols.merge(negotiated_protocols);
+        self.negotiated_ciphers.merge(negotiated_ciphers);
+        self.negotiated_groups.merge(negotiated_groups);
+        self.negotiated_signatures.merge(negotiated_signatures);
+        self.sslv2_client_hello += sslv2_client_hello;
+        self.supported_protocols.merge(supported_protocols);
+        self.supported_ciphers.merge(supported_ciphers);
+        self.supported_groups.merge(supported_groups);
+        self.supported_signatures.merge(supported_signatures);
+        self.server_leaf_cert_key.merge(server_leaf_cert_key);
+        self.server_leaf_cert_sig.merge(server_leaf_cert_sig);
+        self.server_chain_cert_key.merge(server_chain_cert_key);
+        self.server_chain_cert_sig.merge(server_chain_cert_sig);
+        self.client_leaf_cert_key.merge(client_leaf_cert_key);
+        self.client_leaf_cert_sig.merge(client_leaf_cert_sig);
+        self.client_chain_cert_key.merge(client_chain_cert_key);
+        self.client_chain_cert_sig.merge(client_chain_cert_sig);
+        self.server_cert_parsing_failure += server_cert_parsing_failure;
+        self.client_cert_parsing_failure += client_cert_parsing_failure;
+        self.compatibility_general20251201 += compatibility_general20251201;
+        self.compatibility_fips20251201 += compatibility_fips20251201;
+        self.compatibility_cnsa1 += compatibility_cnsa1;
+        self.compatibility_cnsa2 += compatibility_cnsa2;
+        self.compatibility_profiles.merge(compatibility_profiles);
+        self.client_issues.merge(client_issues);
+        self.resumption.merge(resumption);
+        self.hello_retry_request += hello_retry_request;
+        self.early_data.merge(early_data);
+        self.client_hello_ja4.merge(client_hello_ja4);
+        self.handshake_duration_us += handshake_duration_us;
+        self.handshake_compute_us += handshake_compute_us;
+        self.handshake_duration_histogram
+            .merge(handshake_duration_histogram);
+        self.handshake_compute_histogram
+            .merge(handshake_compute_histogram);
+        self.handshake_message_histograms
+            .merge(handshake_message_histograms);
+        self.synthetic_traffic_count += synthetic_traffic_count;
+        self.internal_failure += internal_failure;
+    }
+}
+
+/// How the values of a metric from different records can be combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricKind {
+    /// A count or a sum over the handshakes in a record. Values from different
+    /// records can be added together.
+    Counter,
+    /// A value describing the record as a whole, like a percentile or the
+    /// presence of a security policy. Values from different records can't be
+    /// added together.
+    Gauge,
+}
+
+impl FrozenHandshakeRecord {
+    /// Call `visit` with the name, value, and kind of each metric in the record.
+    ///
+    /// This is the set of metrics written by the `metrique_writer::Entry` impl,
+    /// in the same order.
+    pub fn visit_metrics(&self, mut visit: impl FnMut(Cow<'static, str>, u64, MetricKind)) {
+        use MetricKind::{Counter, Gauge};
+
         match &self.security_policies {
             FrozenBoundedStringSet::TooMany => {
-                writer.value(names::SECURITY_POLICY_TOO_MANY, &1_u64)
+                visit(names::SECURITY_POLICY_TOO_MANY.into(), 1, Gauge)
             }
             FrozenBoundedStringSet::Entries(hash_set) => {
                 for policy in hash_set {
-                    writer.value(names::security_policy_name(policy), &1_u64);
+                    visit(names::security_policy_name(policy).into(), 1, Gauge);
                 }
             }
         }
-        writer.timestamp(self.freeze_time);
 
-        fn write_counter<'a, const N: usize, T, W>(
-            counter: &'a FrozenCounter<N, T>,
+        let mut counter = |name: &'static str, value: u64| visit(name.into(), value, Counter);
+
+        fn visit_counter<const N: usize, T>(
+            counter: &FrozenCounter<N, T>,
             group: &CounterGroup,
-            writer: &mut W,
+            visit: &mut impl FnMut(&'static str, u64),
         ) where
             T: FiniteCounter<N> + std::fmt::Display,
-            W: metrique_writer::EntryWriter<'a>,
         {
             for (slot, element, count) in counter.iter_non_zero() {
-                writer.value(group.metric_name_for(slot, element), &count);
+                visit(group.metric_name_for(slot, element), count);
             }
         }
 
-        write_counter(&self.negotiated_protocols, &negotiated::VERSIONS, writer);
-        write_counter(&self.negotiated_ciphers, &negotiated::CIPHERS, writer);
-        write_counter(&self.negotiated_groups, &negotiated::GROUPS, writer);
-        write_counter(&self.negotiated_signatures, &negotiated::SIGNATURES, writer);
-        write_counter(&self.supported_protocols, &supported::VERSIONS, writer);
-        write_counter(&self.supported_ciphers, &supported::CIPHERS, writer);
-        write_counter(&self.supported_groups, &supported::GROUPS, writer);
-        write_counter(&self.supported_signatures, &supported::SIGNATURES, writer);
-        write_counter(
+        visit_counter(
+            &self.negotiated_protocols,
+            &negotiated::VERSIONS,
+            &mut counter,
+        );
+        visit_counter(&self.negotiated_ciphers, &negotiated::CIPHERS, &mut counter);
+        visit_counter(&self.negotiated_groups, &negotiated::GROUPS, &mut counter);
+        visit_counter(
+            &self.negotiated_signatures,
+            &negotiated::SIGNATURES,
+            &mut counter,
+        );
+        visit_counter(
+            &self.supported_protocols,
+            &supported::VERSIONS,
+            &mut counter,
+        );
+        visit_counter(&self.supported_ciphers, &supported::CIPHERS, &mut counter);
+        visit_counter(&self.supported_groups, &supported::GROUPS, &mut counter);
+        visit_counter(
+            &self.supported_signatures,
+            &supported::SIGNATURES,
+            &mut counter,
+        );
+        visit_counter(
             &self.server_leaf_cert_key,
             &names::cert::SERVER_LEAF_KEY,
-            writer,
+            &mut counter,
         );
-        write_counter(
+        visit_counter(
             &self.server_leaf_cert_sig,
             &names::cert::SERVER_LEAF_SIG,
-            writer,
+            &mut counter,
         );
-        write_counter(
+        visit_counter(
             &self.server_chain_cert_key,
             &names::cert::SERVER_CHAIN_KEY,
-            writer,
+            &mut counter,
         );
-        write_counter(
+        visit_counter(
             &self.server_chain_cert_sig,
             &names::cert::SERVER_CHAIN_SIG,
-            writer,
+            &mut counter,
         );
-        write_counter(
+        visit_counter(
             &self.client_leaf_cert_key,
             &names::cert::CLIENT_LEAF_KEY,
-            writer,
+            &mut counter,
         );
-        write_counter(
+        visit_counter(
             &self.client_leaf_cert_sig,
             &names::cert::CLIENT_LEAF_SIG,
-            writer,
+            &mut counter,
         );
-        write_counter(
+        visit_counter(
             &self.client_chain_cert_key,
             &names::cert::CLIENT_CHAIN_KEY,
-            writer,
+            &mut counter,
         );
-        write_counter(
+        visit_counter(
             &self.client_chain_cert_sig,
             &names::cert::CLIENT_CHAIN_SIG,
-            writer,
+            &mut counter,
         );
-        writer.value(
+        counter(
             names::SERVER_CERT_PARSE_FAILURE,
-            &self.server_cert_parsing_failure,
+            self.server_cert_parsing_failure,
         );
-        writer.value(
+        counter(
             names::CLIENT_CERT_PARSE_FAILURE,
-            &self.client_cert_parsing_failure,
+            self.client_cert_parsing_failure,
         );
 
-        writer.value(
+        counter(
             names::COMPATIBILITY_GENERAL20251201,
-            &self.compatibility_general20251201,
+            self.compatibility_general20251201,
         );
-        writer.value(
+        counter(
             names::COMPATIBILITY_FIPS20251201,
-            &self.compatibility_fips20251201,
+            self.compatibility_fips20251201,
         );
-        writer.value(names::COMPATIBILITY_CNSA1, &self.compatibility_cnsa1);
-        writer.value(names::COMPATIBILITY_CNSA2, &self.compatibility_cnsa2);
-
-        write_counter(&self.client_issues, &names::CLIENT_ISSUES, writer);
-
-        writer.value(names::SSLV2_CLIENT_HELLO, &self.sslv2_client_hello);
-        writer.value(
-            names::HANDSHAKE_SUCCESS_COUNT,
-            &self.handshake_success_count,
+        counter(names::COMPATIBILITY_CNSA1, self.compatibility_cnsa1);
+        counter(names::COMPATIBILITY_CNSA2, self.compatibility_cnsa2);
+        for (profile, count) in &self.compatibility_profiles.entries {
+            visit(
+                names::compatibility_profile_name(profile).into(),
+                *count,
+                Counter,
+            );
+        }
+        if self.compatibility_profiles.overflow > 0 {
+            visit(
+                names::COMPATIBILITY_PROFILE_TOO_MANY.into(),
+                self.compatibility_profiles.overflow,
+                Counter,
+            );
+        }
+        let mut counter = |name: &'static str, value: u64| visit(name.into(), value, Counter);
+
+        visit_counter(&self.client_issues, &names::CLIENT_ISSUES, &mut counter);
+        visit_counter(&self.resumption, &names::RESUMPTION, &mut counter);
+        counter(names::HELLO_RETRY_REQUEST, self.hello_retry_request);
+        visit_counter(&self.early_data, &names::EARLY_DATA, &mut counter);
+
+        counter(names::SSLV2_CLIENT_HELLO, self.sslv2_client_hello);
+        counter(names::HANDSHAKE_SUCCESS_COUNT, self.handshake_success_count);
+        counter(names::HANDSHAKE_FAILURE_COUNT, self.handshake_failure_count);
+        visit_counter(&self.alerts, &names::ALERTS, &mut counter);
+        for (error, count) in &self.failure_errors.entries {
+            visit(
+                names::handshake_failure_error_name(error).into(),
+                *count,
+                Counter,
+            );
+        }
+        let mut counter = |name: &'static str, value: u64| visit(name.into(), value, Counter);
+        if self.failure_errors.overflow > 0 {
+            counter(
+                names::HANDSHAKE_FAILURE_ERROR_TOO_MANY,
+                self.failure_errors.overflow,
+            );
+        }
+        visit_counter(
+            &self.failure_error_types,
+            &names::failure::ERROR_TYPES,
+            &mut counter,
         );
-        writer.value(
-            names::HANDSHAKE_FAILURE_COUNT,
-            &self.handshake_failure_count,
+        visit_counter(
+            &self.failure_messages,
+            &names::failure::MESSAGES,
+            &mut counter,
         );
-        write_counter(&self.alerts, &names::ALERTS, writer);
-        writer.value(names::HANDSHAKE_DURATION_US, &self.handshake_duration_us);
-        writer.value(names::HANDSHAKE_COMPUTE_US, &self.handshake_compute_us);
-        writer.value(
-            names::SYNTHETIC_TRAFFIC_COUNT,
-            &self.synthetic_traffic_count,
+        counter(names::HANDSHAKE_DURATION_US, self.handshake_duration_us);
+        counter(names::HANDSHAKE_COMPUTE_US, self.handshake_compute_us);
+
+        // Empty histograms have no percentiles, so nothing is written for them.
+        let mut percentiles =
+            |histogram: &FrozenHistogram, (p50_name, p99_name): (&'static str, &'static str)| {
+                if let (Some(p50), Some(p99)) = (histogram.quantile(0.5), histogram.quantile(0.99))
+                {
+                    visit(p50_name.into(), p50, Gauge);
+                    visit(p99_name.into(), p99, Gauge);
+                }
+            };
+
+        percentiles(
+            &self.handshake_duration_histogram,
+            (
+                names::HANDSHAKE_DURATION_US_P50,
+                names::HANDSHAKE_DURATION_US_P99,
+            ),
         );
-        writer.value(names::INTERNAL_FAILURE, &self.internal_failure);
+        percentiles(
+            &self.handshake_compute_histogram,
+            (
+                names::HANDSHAKE_COMPUTE_US_P50,
+                names::HANDSHAKE_COMPUTE_US_P99,
+            ),
+        );
+        for (slot, message, histogram) in self.handshake_message_histograms.iter_non_empty() {
+            percentiles(
+                histogram,
+                (
+                    names::handshake_message::P50.metric_name_for(slot, message),
+                    names::handshake_message::P99.metric_name_for(slot, message),
+                ),
+            );
+        }
+        for (fingerprint, count) in &self.client_hello_ja4.entries {
+            visit(
+                names::client_hello_ja4_name(fingerprint).into(),
+                *count,
+                Counter,
+            );
+        }
+        if self.client_hello_ja4.overflow > 0 {
+            visit(
+                names::CLIENT_HELLO_JA4_OTHER.into(),
+                self.client_hello_ja4.overflow,
+                Counter,
+            );
+        }
+        visit(
+            names::SYNTHETIC_TRAFFIC_COUNT.into(),
+            self.synthetic_traffic_count,
+            Counter,
+        );
+        visit(
+            names::INTERNAL_FAILURE.into(),
+            self.internal_failure,
+            Counter,
+        );
+    }
+}
+
+impl metrique_writer::Entry for FrozenHandshakeRecord {
+    fn write<'a>(&'a self, writer: &mut impl metrique_writer::EntryWriter<'a>) {
+        writer.timestamp(self.freeze_time);
+        self.visit_metrics(|name, value, _| writer.value(name, &value));
     }
 }
 
@@ -308,6 +590,9 @@ mod tests {
         assert_eq!(record.negotiated_groups, FrozenCounter::default());
         assert_eq!(record.negotiated_signatures, FrozenCounter::default());
         assert_eq!(record.alerts, FrozenCounter::default());
+        assert_eq!(record.failure_errors, FrozenBoundedStringCounter::default());
+        assert_eq!(record.failure_error_types, FrozenCounter::default());
+        assert_eq!(record.failure_messages, FrozenCounter::default());
         assert_eq!(record.supported_protocols, FrozenCounter::default());
         assert_eq!(record.supported_ciphers, FrozenCounter::default());
         assert_eq!(record.supported_groups, FrozenCounter::default());
@@ -318,8 +603,106 @@ mod tests {
         assert_eq!(record.compatibility_fips20251201, 0);
         assert_eq!(record.compatibility_cnsa1, 0);
         assert_eq!(record.compatibility_cnsa2, 0);
+        assert_eq!(
+            record.compatibility_profiles,
+            FrozenBoundedStringCounter::default()
+        );
+        assert_eq!(record.resumption, FrozenCounter::default());
+        assert_eq!(record.hello_retry_request, 0);
+        assert_eq!(record.early_data, FrozenCounter::default());
+        assert_eq!(
+            record.client_hello_ja4,
+            FrozenBoundedStringCounter::default()
+        );
         assert_eq!(record.handshake_duration_us, 0);
         assert_eq!(record.handshake_compute_us, 0);
+        assert_eq!(
+            record.handshake_duration_histogram,
+            FrozenHistogram::default()
+        );
+        assert_eq!(
+            record.handshake_compute_histogram,
+            FrozenHistogram::default()
+        );
+        assert_eq!(
+            record.handshake_message_histograms,
+            FrozenHistograms::default()
+        );
         assert_eq!(record.synthetic_traffic_count, 0);
     }
+
+    #[test]
+    fn visit_metrics_kinds() {
+        let record = FrozenHandshakeRecord {
+            handshake_success_count: 3,
+            handshake_duration_histogram: FrozenHistogram::from_buckets(std::array::from_fn(|i| {
+                if i == 0 { 3 } else { 0 }
+            })),
+            ..Default::default()
+        };
+
+        let mut visited = Vec::new();
+        record.visit_metrics(|name, value, kind| visited.push((name, value, kind)));
+
+        assert!(visited.contains(&(
+            names::HANDSHAKE_SUCCESS_COUNT.into(),
+            3,
+            MetricKind::Counter
+        )));
+        assert!(
+            visited
+                .iter()
+                .any(|(name, _, kind)| name == names::HANDSHAKE_DURATION_US_P50
+                    && *kind == MetricKind::Gauge)
+        );
+        // empty histograms have no percentiles
+        assert!(
+            !visited
+                .iter()
+                .any(|(name, _, _)| name == names::HANDSHAKE_COMPUTE_US_P50)
+        );
+    }
+
+    #[test]
+    fn merge_combines_records() {
+        let mut merged = FrozenHandshakeRecord {
+            freeze_time: SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(60),
+            security_policies: FrozenBoundedStringSet::Entries(["a".to_owned()].into()),
+            handshake_success_count: 3,
+            handshake_duration_us: 300,
+            handshake_duration_histogram: FrozenHistogram::from_buckets(std::array::from_fn(|i| {
+                if i == 0 { 3 } else { 0 }
+            })),
+            ..Default::default()
+        };
+        let other = FrozenHandshakeRecord {
+            freeze_time: SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(120),
+            security_policies: FrozenBoundedStringSet::Entries(["b".to_owned()].into()),
+            handshake_success_count: 2,
+            handshake_failure_count: 1,
+            handshake_duration_us: 200,
+            handshake_duration_histogram: FrozenHistogram::from_buckets(std::array::from_fn(|i| {
+                if i == 1 { 2 } else { 0 }
+            })),
+            ..Default::default()
+        };
+
+        merged.merge(&other);
+
+        assert_eq!(merged.freeze_time, other.freeze_time);
+        assert_eq!(
+            merged.security_policies,
+            FrozenBoundedStringSet::Entries(["a".to_owned(), "b".to_owned()].into())
+        );
+        assert_eq!(merged.handshake_success_count, 5);
+        assert_eq!(merged.handshake_failure_count, 1);
+        assert_eq!(merged.handshake_duration_us, 500);
+        assert_eq!(merged.handshake_duration_histogram.count(), 5);
+
+        merged.merge(&FrozenHandshakeRecord {
+            security_policies: FrozenBoundedStringSet::TooMany,
+            ..Default::default()
+        });
+        assert_eq!(merged.security_policies, FrozenBoundedStringSet::TooMany);
+    }
 }
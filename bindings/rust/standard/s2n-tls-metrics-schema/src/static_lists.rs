@@ -683,6 +683,216 @@ impl FiniteCounter<{ ClientIssue::COUNT }> for ClientIssue {
     const ELEMENTS: [Self; ClientIssue::COUNT] = ClientIssue::MEMBERS;
 }
 
+/// A TLS handshake message, as named by s2n-tls timing checkpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum HandshakeMessage {
+    ClientHello,
+    ServerHello,
+    EncryptedExtensions,
+    ServerNewSessionTicket,
+    ServerCert,
+    ServerCertStatus,
+    ServerCertVerify,
+    ServerKey,
+    ServerCertReq,
+    ServerHelloDone,
+    ClientCert,
+    ClientKey,
+    ClientCertVerify,
+    ClientChangeCipherSpec,
+    ClientFinished,
+    ServerChangeCipherSpec,
+    ServerFinished,
+    HelloRetryMsg,
+    EndOfEarlyData,
+    ClientNpn,
+}
+
+impl HandshakeMessage {
+    pub const COUNT: usize = Self::MEMBERS.len();
+    pub const MEMBERS: [HandshakeMessage; 20] = [
+        HandshakeMessage::ClientHello,
+        HandshakeMessage::ServerHello,
+        HandshakeMessage::EncryptedExtensions,
+        HandshakeMessage::ServerNewSessionTicket,
+        HandshakeMessage::ServerCert,
+        HandshakeMessage::ServerCertStatus,
+        HandshakeMessage::ServerCertVerify,
+        HandshakeMessage::ServerKey,
+        HandshakeMessage::ServerCertReq,
+        HandshakeMessage::ServerHelloDone,
+        HandshakeMessage::ClientCert,
+        HandshakeMessage::ClientKey,
+        HandshakeMessage::ClientCertVerify,
+        HandshakeMessage::ClientChangeCipherSpec,
+        HandshakeMessage::ClientFinished,
+        HandshakeMessage::ServerChangeCipherSpec,
+        HandshakeMessage::ServerFinished,
+        HandshakeMessage::HelloRetryMsg,
+        HandshakeMessage::EndOfEarlyData,
+        HandshakeMessage::ClientNpn,
+    ];
+
+    /// The name s2n-tls uses for this message, e.g. "CLIENT_HELLO".
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::ClientHello => "CLIENT_HELLO",
+            Self::ServerHello => "SERVER_HELLO",
+            Self::EncryptedExtensions => "ENCRYPTED_EXTENSIONS",
+            Self::ServerNewSessionTicket => "SERVER_NEW_SESSION_TICKET",
+            Self::ServerCert => "SERVER_CERT",
+            Self::ServerCertStatus => "SERVER_CERT_STATUS",
+            Self::ServerCertVerify => "SERVER_CERT_VERIFY",
+            Self::ServerKey => "SERVER_KEY",
+            Self::ServerCertReq => "SERVER_CERT_REQ",
+            Self::ServerHelloDone => "SERVER_HELLO_DONE",
+            Self::ClientCert => "CLIENT_CERT",
+            Self::ClientKey => "CLIENT_KEY",
+            Self::ClientCertVerify => "CLIENT_CERT_VERIFY",
+            Self::ClientChangeCipherSpec => "CLIENT_CHANGE_CIPHER_SPEC",
+            Self::ClientFinished => "CLIENT_FINISHED",
+            Self::ServerChangeCipherSpec => "SERVER_CHANGE_CIPHER_SPEC",
+            Self::ServerFinished => "SERVER_FINISHED",
+            Self::HelloRetryMsg => "HELLO_RETRY_MSG",
+            Self::EndOfEarlyData => "END_OF_EARLY_DATA",
+            Self::ClientNpn => "CLIENT_NPN",
+        }
+    }
+
+    /// Look up a message by its s2n-tls name. Returns `None` for checkpoints
+    /// which aren't handshake messages, like "NEGOTIATE_START" or "RECORD_READ".
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::MEMBERS
+            .into_iter()
+            .find(|message| message.name() == name)
+    }
+}
+
+impl Display for HandshakeMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FiniteCounter<{ HandshakeMessage::COUNT }> for HandshakeMessage {
+    const ELEMENTS: [Self; HandshakeMessage::COUNT] = HandshakeMessage::MEMBERS;
+}
+
+/// How the keys for a successful handshake were established.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Resumption {
+    /// A full handshake, without resumption.
+    Full,
+    /// Resumed using a session ticket. All TLS 1.3 resumptions use tickets.
+    SessionTicket,
+    /// A TLS 1.2 resumption using the session ID cache.
+    SessionId,
+    /// A TLS 1.3 handshake using an external PSK.
+    ExternalPsk,
+    /// Resumed, but the mechanism is not known. Clients can't distinguish
+    /// TLS 1.2 ticket resumption from session ID resumption.
+    Unknown,
+}
+
+impl Resumption {
+    pub const COUNT: usize = Self::MEMBERS.len();
+    pub const MEMBERS: [Resumption; 5] = [
+        Resumption::Full,
+        Resumption::SessionTicket,
+        Resumption::SessionId,
+        Resumption::ExternalPsk,
+        Resumption::Unknown,
+    ];
+}
+
+impl Display for Resumption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Full => f.write_str("full"),
+            Self::SessionTicket => f.write_str("session_ticket"),
+            Self::SessionId => f.write_str("session_id"),
+            Self::ExternalPsk => f.write_str("external_psk"),
+            Self::Unknown => f.write_str("unknown"),
+        }
+    }
+}
+
+impl FiniteCounter<{ Resumption::COUNT }> for Resumption {
+    const ELEMENTS: [Self; Resumption::COUNT] = Resumption::MEMBERS;
+}
+
+/// Whether the client requested TLS 1.3 early data, and whether the server
+/// accepted it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EarlyData {
+    NotRequested,
+    Accepted,
+    Rejected,
+}
+
+impl EarlyData {
+    pub const COUNT: usize = Self::MEMBERS.len();
+    pub const MEMBERS: [EarlyData; 3] = [
+        EarlyData::NotRequested,
+        EarlyData::Accepted,
+        EarlyData::Rejected,
+    ];
+}
+
+impl Display for EarlyData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotRequested => f.write_str("not_requested"),
+            Self::Accepted => f.write_str("accepted"),
+            Self::Rejected => f.write_str("rejected"),
+        }
+    }
+}
+
+impl FiniteCounter<{ EarlyData::COUNT }> for EarlyData {
+    const ELEMENTS: [Self; EarlyData::COUNT] = EarlyData::MEMBERS;
+}
+
+/// The category of an s2n-tls error, corresponding to `s2n_error_type`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ErrorType {
+    IOError,
+    ConnectionClosed,
+    Blocked,
+    Alert,
+    ProtocolError,
+    InternalError,
+    UsageError,
+    Application,
+    Unknown,
+}
+
+impl ErrorType {
+    pub const COUNT: usize = Self::MEMBERS.len();
+    pub const MEMBERS: [ErrorType; 9] = [
+        ErrorType::IOError,
+        ErrorType::ConnectionClosed,
+        ErrorType::Blocked,
+        ErrorType::Alert,
+        ErrorType::ProtocolError,
+        ErrorType::InternalError,
+        ErrorType::UsageError,
+        ErrorType::Application,
+        ErrorType::Unknown,
+    ];
+}
+
+impl Display for ErrorType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl FiniteCounter<{ ErrorType::COUNT }> for ErrorType {
+    const ELEMENTS: [Self; ErrorType::COUNT] = ErrorType::MEMBERS;
+}
+
 // Unfortunately, CertSignatures are _not_ the same as TLS SignatureSchemes.
 // TLS SignatureSchemes also encode information about the issuing public key (e.g. secp384, RSAE)
 // but that is not present at the actual certificate signature level.
@@ -810,4 +1020,14 @@ mod tests {
             assert_eq!(constant.known_description().unwrap(), *expected_name);
         }
     }
+
+    #[test]
+    fn handshake_message_names_match_serde() {
+        for message in HandshakeMessage::MEMBERS {
+            let json = serde_json::to_value(message).unwrap();
+            assert_eq!(json, serde_json::json!(message.name()));
+            assert_eq!(HandshakeMessage::from_name(message.name()), Some(message));
+        }
+        assert_eq!(HandshakeMessage::from_name("NEGOTIATE_START"), None);
+    }
 }
@@ -9,10 +9,11 @@
 use std::{borrow::Cow, collections::HashSet, time::SystemTime};
 
 use s2n_tls_metrics_schema::{
-    metric_names::{self as names, cert, negotiated, supported},
+    metric_names::{self as names, cert, failure, handshake_message, negotiated, supported},
     static_lists::{
-        CERT_KEY_COUNT, CERT_SIG_COUNT, CIPHER_COUNT, Cipher, DEFINED_ALERTS_COUNT, FiniteCounter,
-        GROUP_COUNT, Group, PROTOCOL_COUNT, SIGNATURE_COUNT, Signature, Version,
+        CERT_KEY_COUNT, CERT_SIG_COUNT, CIPHER_COUNT, Cipher, DEFINED_ALERTS_COUNT, EarlyData,
+        ErrorType, FiniteCounter, GROUP_COUNT, Group, HandshakeMessage, PROTOCOL_COUNT, Resumption,
+        SIGNATURE_COUNT, Signature, Version,
     },
 };
 
@@ -39,7 +40,8 @@ fn sample_schema_record() -> s2n_tls_metrics_schema::record::MetricRecord {
             "compatibility_cnsa1": 100,
             "compatibility_cnsa2": 0,
             "handshake_duration_us": 50000,
-            "handshake_compute_us": 25000
+            "handshake_compute_us": 25000,
+            "handshake_duration_histogram": [[40960, 600], [57344, 400]]
         }
     });
     serde_json::from_value(json).unwrap()
@@ -94,6 +96,12 @@ fn schema_field_access() {
     assert_eq!(record.handshake.handshake_duration_us, 50000);
     assert_eq!(record.handshake.handshake_compute_us, 25000);
 
+    let histogram = &record.handshake.handshake_duration_histogram;
+    assert_eq!(histogram.count(), 1000);
+    assert_eq!(histogram.quantile(0.5), Some(49151));
+    assert_eq!(histogram.quantile(0.99), Some(65535));
+    assert!(record.handshake.handshake_compute_histogram.is_empty());
+
     let dur = record
         .handshake
         .freeze_time
@@ -323,7 +331,10 @@ impl<'a> metrique_writer::EntryWriter<'a> for NameCollector {
 fn entry_writes_match_metric_names_catalog() {
     use metrique_writer::Entry;
     use s2n_tls_metrics_schema::{
-        bounded_set::FrozenBoundedStringSet, counter::FrozenCounter, record::FrozenHandshakeRecord,
+        bounded_set::{FrozenBoundedStringCounter, FrozenBoundedStringSet},
+        counter::FrozenCounter,
+        histogram::{FrozenHistogram, FrozenHistograms, HISTOGRAM_BUCKETS},
+        record::FrozenHandshakeRecord,
     };
 
     // Build a record with every counter slot populated so all names are emitted.
@@ -339,6 +350,12 @@ fn entry_writes_match_metric_names_catalog() {
         supported_groups: FrozenCounter::from_slots([1; GROUP_COUNT]),
         supported_signatures: FrozenCounter::from_slots([1; SIGNATURE_COUNT]),
         alerts: FrozenCounter::from_slots([1; DEFINED_ALERTS_COUNT]),
+        failure_errors: FrozenBoundedStringCounter {
+            entries: [("S2N_ERR_CERT_UNTRUSTED".to_owned(), 1)].into(),
+            overflow: 1,
+        },
+        failure_error_types: FrozenCounter::from_slots([1; ErrorType::COUNT]),
+        failure_messages: FrozenCounter::from_slots([1; HandshakeMessage::COUNT]),
         server_leaf_cert_key: FrozenCounter::from_slots([1; CERT_KEY_COUNT]),
         server_leaf_cert_sig: FrozenCounter::from_slots([1; CERT_SIG_COUNT]),
         server_chain_cert_key: FrozenCounter::from_slots([1; CERT_KEY_COUNT]),
@@ -356,9 +373,25 @@ fn entry_writes_match_metric_names_catalog() {
         compatibility_fips20251201: 1,
         compatibility_cnsa1: 1,
         compatibility_cnsa2: 1,
+        compatibility_profiles: FrozenBoundedStringCounter {
+            entries: [("my-policy".to_owned(), 1)].into(),
+            overflow: 1,
+        },
         sslv2_client_hello: 1,
         handshake_duration_us: 1,
         handshake_compute_us: 1,
+        handshake_duration_histogram: FrozenHistogram::from_buckets([1; HISTOGRAM_BUCKETS]),
+        handshake_compute_histogram: FrozenHistogram::from_buckets([1; HISTOGRAM_BUCKETS]),
+        handshake_message_histograms: FrozenHistograms::from_histograms(std::array::from_fn(
+            |_| FrozenHistogram::from_buckets([1; HISTOGRAM_BUCKETS]),
+        )),
+        resumption: FrozenCounter::from_slots([1; Resumption::COUNT]),
+        hello_retry_request: 1,
+        early_data: FrozenCounter::from_slots([1; EarlyData::COUNT]),
+        client_hello_ja4: FrozenBoundedStringCounter {
+            entries: [("t13d1516h2_8daaf6152771_02713d6af862".to_owned(), 1)].into(),
+            overflow: 1,
+        },
         synthetic_traffic_count: 1,
         ..Default::default()
     };
@@ -375,8 +408,10 @@ fn entry_writes_match_metric_names_catalog() {
             expected.insert(group.metric_name(slot).to_string());
         }
     }
-    for slot in 0..names::ALERTS.count {
-        expected.insert(names::ALERTS.metric_name(slot).to_string());
+    for group in [&names::ALERTS, &names::RESUMPTION, &names::EARLY_DATA] {
+        for slot in 0..group.count {
+            expected.insert(group.metric_name(slot).to_string());
+        }
     }
     let cert_groups: &[&names::CounterGroup] = &[
         &cert::SERVER_LEAF_KEY,
@@ -393,10 +428,33 @@ fn entry_writes_match_metric_names_catalog() {
             expected.insert(group.metric_name(slot).to_string());
         }
     }
+    for group in failure::ALL {
+        for slot in 0..group.count {
+            expected.insert(group.metric_name(slot).to_string());
+        }
+    }
+    for group in handshake_message::ALL {
+        for slot in 0..group.count {
+            expected.insert(group.metric_name(slot).to_string());
+        }
+    }
     for &name in names::ALL_SCALARS {
         expected.insert(name.to_string());
     }
+    for &name in names::ALL_LATENCY_PERCENTILES {
+        expected.insert(name.to_string());
+    }
     expected.insert(names::security_policy_name("TestPolicy"));
+    expected.insert(names::handshake_failure_error_name(
+        "S2N_ERR_CERT_UNTRUSTED",
+    ));
+    expected.insert(names::HANDSHAKE_FAILURE_ERROR_TOO_MANY.to_string());
+    expected.insert(names::client_hello_ja4_name(
+        "t13d1516h2_8daaf6152771_02713d6af862",
+    ));
+    expected.insert(names::CLIENT_HELLO_JA4_OTHER.to_string());
+    expected.insert(names::compatibility_profile_name("my-policy"));
+    expected.insert(names::COMPATIBILITY_PROFILE_TOO_MANY.to_string());
 
     // Guard against a new counter group being added to `write()` without
     // updating this test and the catalog. If both sides silently omit the new
@@ -406,10 +464,19 @@ fn entry_writes_match_metric_names_catalog() {
         + GROUP_COUNT * 2
         + SIGNATURE_COUNT * 2
         + DEFINED_ALERTS_COUNT
+        + Resumption::COUNT
+        + EarlyData::COUNT
         + CERT_KEY_COUNT * 4
         + CERT_SIG_COUNT * 4
+        + HandshakeMessage::COUNT * 2
+        + ErrorType::COUNT
+        + HandshakeMessage::COUNT
         + names::ALL_SCALARS.len()
-        + 1; // security_policies: 1 test policy entry
+        + names::ALL_LATENCY_PERCENTILES.len()
+        + 1 // security_policies: 1 test policy entry
+        + 2 // failure_errors: 1 error name entry and the overflow
+        + 2 // client_hello_ja4: 1 fingerprint entry and the overflow
+        + 2; // compatibility_profiles: 1 profile entry and the overflow
     assert_eq!(
         expected.len(),
         expected_count,
@@ -2,14 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     sync::{
         RwLock,
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicU64, Ordering},
     },
 };
 
-use s2n_tls_metrics_schema::bounded_set::FrozenBoundedStringSet;
+use s2n_tls_metrics_schema::bounded_set::{FrozenBoundedStringCounter, FrozenBoundedStringSet};
 
 #[derive(Debug)]
 pub(crate) struct BoundedStringSet {
@@ -64,3 +64,201 @@ impl BoundedStringSet {
         }
     }
 }
+
+/// Counts occurrences of up to [`BoundedStringCounter::MAX_STORAGE`] distinct
+/// strings. Occurrences of any other string are counted as overflow.
+#[derive(Debug, Default)]
+pub(crate) struct BoundedStringCounter {
+    // not preallocated, because this is only written to on the failure path
+    storage: RwLock<HashMap<String, AtomicU64>>,
+    overflow: AtomicU64,
+}
+
+impl BoundedStringCounter {
+    pub(crate) const MAX_STORAGE: usize = 32;
+
+    pub fn increment(&self, item: &str) {
+        let storage = self.storage.read().unwrap();
+        if let Some(count) = storage.get(item) {
+            count.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        if storage.len() >= Self::MAX_STORAGE {
+            self.overflow.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        drop(storage);
+
+        // acquire write lock
+        let mut write_map = self.storage.write().unwrap();
+        if !write_map.contains_key(item) && write_map.len() >= Self::MAX_STORAGE {
+            self.overflow.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        write_map
+            .entry(item.to_owned())
+            .or_default()
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// `&mut self` guarantees no concurrent writer, so relaxed loads are
+    /// sufficient.
+    pub fn freeze(&mut self) -> FrozenBoundedStringCounter {
+        let storage = self.storage.get_mut().unwrap();
+        FrozenBoundedStringCounter {
+            entries: storage
+                .iter_mut()
+                .map(|(item, count)| (item.clone(), *count.get_mut()))
+                .collect(),
+            overflow: *self.overflow.get_mut(),
+        }
+    }
+}
+
+/// Counts occurrences of the most frequent strings, keeping at most
+/// [`TopKStringCounter::MAX_STORAGE`] distinct strings.
+///
+/// This is the Space-Saving algorithm: when a new string is seen while storage
+/// is full, the entry with the lowest count is evicted, and the new string
+/// inherits that count plus one. The inherited count is kept as the entry's
+/// error bound, since any number of those occurrences may belong to other
+/// strings. A string which occurs steadily therefore accumulates a count which
+/// outlasts rare strings, even when it is first seen after storage has filled.
+#[derive(Debug, Default)]
+pub(crate) struct TopKStringCounter {
+    // not preallocated, because this is only written to when enabled
+    storage: RwLock<HashMap<String, TopKEntry>>,
+}
+
+#[derive(Debug, Default)]
+struct TopKEntry {
+    /// An upper bound of the occurrences of the string.
+    count: AtomicU64,
+    /// The count inherited when the string was inserted. The string occurred
+    /// at least `count - error` times.
+    error: u64,
+}
+
+impl TopKStringCounter {
+    pub(crate) const MAX_STORAGE: usize = 16;
+
+    pub fn increment(&self, item: &str) {
+        let storage = self.storage.read().unwrap();
+        if let Some(entry) = storage.get(item) {
+            entry.count.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        drop(storage);
+
+        // acquire write lock
+        let mut write_map = self.storage.write().unwrap();
+        if let Some(entry) = write_map.get(item) {
+            entry.count.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        let mut error = 0;
+        if write_map.len() >= Self::MAX_STORAGE {
+            let least_frequent = write_map
+                .iter()
+                .min_by_key(|(_, entry)| entry.count.load(Ordering::Relaxed))
+                .map(|(item, _)| item.clone());
+            if let Some(evicted) = least_frequent.and_then(|item| write_map.remove(&item)) {
+                error = evicted.count.into_inner();
+            }
+        }
+        write_map.insert(
+            item.to_owned(),
+            TopKEntry {
+                count: AtomicU64::new(error + 1),
+                error,
+            },
+        );
+    }
+
+    /// Each entry is frozen with the occurrences it is guaranteed to have,
+    /// `count - error`. The error bounds account for every other occurrence,
+    /// so their sum is reported as overflow.
+    ///
+    /// `&mut self` guarantees no concurrent writer, so relaxed loads are
+    /// sufficient.
+    pub fn freeze(&mut self) -> FrozenBoundedStringCounter {
+        let storage = self.storage.get_mut().unwrap();
+        FrozenBoundedStringCounter {
+            entries: storage
+                .iter_mut()
+                .map(|(item, entry)| (item.clone(), *entry.count.get_mut() - entry.error))
+                .collect(),
+            overflow: storage.values().map(|entry| entry.error).sum(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn string_counter_overflow() {
+        let mut counter = BoundedStringCounter::default();
+        for i in 0..BoundedStringCounter::MAX_STORAGE {
+            counter.increment(&i.to_string());
+        }
+        counter.increment("0");
+        counter.increment("extra");
+        counter.increment("extra");
+
+        let frozen = counter.freeze();
+        assert_eq!(frozen.entries.len(), BoundedStringCounter::MAX_STORAGE);
+        assert_eq!(frozen.get("0"), 2);
+        assert_eq!(frozen.get("1"), 1);
+        assert_eq!(frozen.get("extra"), 0);
+        assert_eq!(frozen.overflow, 2);
+        assert_eq!(frozen.total(), BoundedStringCounter::MAX_STORAGE as u64 + 3);
+    }
+
+    #[test]
+    fn top_k_evicts_least_frequent() {
+        let mut counter = TopKStringCounter::default();
+        for i in 0..TopKStringCounter::MAX_STORAGE {
+            counter.increment(&i.to_string());
+            counter.increment(&i.to_string());
+        }
+        // "0" is now the only entry with more than two occurrences
+        counter.increment("0");
+        // each evicts an entry with two occurrences, and inherits its count
+        counter.increment("rare");
+        counter.increment("another");
+        counter.increment("another");
+
+        let frozen = counter.freeze();
+        assert_eq!(frozen.entries.len(), TopKStringCounter::MAX_STORAGE);
+        assert_eq!(frozen.get("0"), 3);
+        assert_eq!(frozen.get("rare"), 1);
+        assert_eq!(frozen.get("another"), 2);
+        // the inherited counts of "rare" and "another"
+        assert_eq!(frozen.overflow, 4);
+        assert_eq!(
+            frozen.total(),
+            2 * TopKStringCounter::MAX_STORAGE as u64 + 4
+        );
+    }
+
+    /// A string which is first seen after storage has filled still makes it
+    /// into the top-K if it occurs more often than the rest.
+    #[test]
+    fn top_k_steady_newcomer() {
+        let mut counter = TopKStringCounter::default();
+        for i in 0..100 {
+            counter.increment(&format!("noise-{i}"));
+        }
+        for i in 100..200 {
+            counter.increment("steady");
+            counter.increment(&format!("noise-{i}"));
+        }
+
+        let frozen = counter.freeze();
+        assert_eq!(frozen.entries.len(), TopKStringCounter::MAX_STORAGE);
+        assert_eq!(frozen.get("steady"), 100);
+        assert_eq!(frozen.total(), 300);
+    }
+}
@@ -0,0 +1,152 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{collections::HashMap, sync::Mutex};
+
+use s2n_tls::{connection::Connection, events::TimingCheckpoint};
+
+/// The first checkpoint of each handshake.
+const NEGOTIATE_START: &str = "NEGOTIATE_START";
+
+/// The last checkpoint of each successful handshake, emitted after the
+/// handshake event.
+const NEGOTIATE_END: &str = "NEGOTIATE_END";
+
+/// The states are split across `1 << SHARD_BITS` maps, so that concurrent
+/// handshakes rarely contend for the same lock.
+const SHARD_BITS: u32 = 4;
+
+/// State derived from the timing checkpoints of each connection's handshake,
+/// keyed by [`Connection::id`].
+///
+/// Timing checkpoints and the handshake event are reported separately, so the
+/// state of a connection is kept from its `NEGOTIATE_START` checkpoint until
+/// [`HandshakeCheckpoints::take`] is called. Subscribers take the state when
+/// the handshake event is reported, and when the connection is closed, in case
+/// the handshake never finished.
+///
+/// If a ClientHello callback switches the connection to a config without this
+/// subscriber, the `NEGOTIATE_START` checkpoint is recorded here but the
+/// handshake event and the close are reported to the new config's subscriber,
+/// so the state is never taken. It is only discarded when another connection
+/// with the same identifier starts a handshake on this subscriber, so such
+/// applications should install the same subscriber on every config.
+#[derive(Debug)]
+pub(crate) struct HandshakeCheckpoints<T> {
+    shards: [Mutex<HashMap<usize, T>>; 1 << SHARD_BITS],
+}
+
+impl<T> Default for HandshakeCheckpoints<T> {
+    fn default() -> Self {
+        Self {
+            shards: std::array::from_fn(|_| Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl<T> HandshakeCheckpoints<T> {
+    fn shard(&self, id: usize) -> &Mutex<HashMap<usize, T>> {
+        // Connection ids are addresses, so their low bits are mostly the same.
+        // Fibonacci hashing spreads every bit of the id into the top bits.
+        let hash = (id as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        &self.shards[(hash >> (u64::BITS - SHARD_BITS)) as usize]
+    }
+}
+
+impl<T: Default> HandshakeCheckpoints<T> {
+    /// Applies `update` to the state of `connection` for `checkpoint`.
+    ///
+    /// `NEGOTIATE_START` resets the state, discarding anything left over from
+    /// an abandoned handshake. `NEGOTIATE_END` follows the handshake event, so
+    /// it is ignored and `None` is returned.
+    pub(crate) fn record<R>(
+        &self,
+        connection: &Connection,
+        checkpoint: &TimingCheckpoint,
+        update: impl FnOnce(&mut T) -> R,
+    ) -> Option<R> {
+        self.record_by_id(connection.id(), checkpoint.name(), update)
+    }
+
+    fn record_by_id<R>(
+        &self,
+        id: usize,
+        name: &str,
+        update: impl FnOnce(&mut T) -> R,
+    ) -> Option<R> {
+        if name == NEGOTIATE_END {
+            return None;
+        }
+        let mut connections = self.shard(id).lock().unwrap();
+        let state = connections.entry(id).or_default();
+        if name == NEGOTIATE_START {
+            *state = T::default();
+        }
+        Some(update(state))
+    }
+
+    /// Removes and returns the state of `connection`.
+    pub(crate) fn take(&self, connection: &Connection) -> Option<T> {
+        self.take_by_id(connection.id())
+    }
+
+    fn take_by_id(&self, id: usize) -> Option<T> {
+        self.shard(id).lock().unwrap().remove(&id)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.shards
+            .iter()
+            .all(|shard| shard.lock().unwrap().is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(checkpoints: &HandshakeCheckpoints<Vec<u64>>, id: usize, name: &str, ts: u64) {
+        checkpoints.record_by_id(id, name, |state| state.push(ts));
+    }
+
+    /// The checkpoints of interleaved handshakes are kept apart.
+    #[test]
+    fn interleaved_connections() {
+        let checkpoints = HandshakeCheckpoints::default();
+        record(&checkpoints, 1, NEGOTIATE_START, 100);
+        record(&checkpoints, 2, NEGOTIATE_START, 200);
+        record(&checkpoints, 1, "CLIENT_HELLO", 300);
+        record(&checkpoints, 2, "CLIENT_HELLO", 400);
+
+        assert_eq!(checkpoints.take_by_id(1), Some(vec![100, 300]));
+        assert_eq!(checkpoints.take_by_id(2), Some(vec![200, 400]));
+        assert!(checkpoints.is_empty());
+    }
+
+    /// Aligned ids, like the addresses of connections, use every shard.
+    #[test]
+    fn aligned_ids_spread_across_shards() {
+        let checkpoints = HandshakeCheckpoints::<Vec<u64>>::default();
+        for id in (0..64).map(|i| 0x7f00_0000 + i * 4096) {
+            record(&checkpoints, id, NEGOTIATE_START, 0);
+        }
+        for shard in &checkpoints.shards {
+            assert!(!shard.lock().unwrap().is_empty());
+        }
+    }
+
+    /// A new handshake discards the state of an abandoned one, and the
+    /// checkpoint after the handshake event doesn't recreate any state.
+    #[test]
+    fn handshake_boundaries() {
+        let checkpoints = HandshakeCheckpoints::default();
+        record(&checkpoints, 1, NEGOTIATE_START, 100);
+        record(&checkpoints, 1, "CLIENT_HELLO", 200);
+        record(&checkpoints, 1, NEGOTIATE_START, 300);
+        assert_eq!(checkpoints.take_by_id(1), Some(vec![300]));
+
+        record(&checkpoints, 1, NEGOTIATE_END, 400);
+        assert!(checkpoints.is_empty());
+    }
+}
@@ -4,9 +4,58 @@
 //! This module holds utilities for checking when a client is compatible with some
 //! particular TLS Profile.
 
+use std::{
+    ffi::{c_int, c_void},
+    task::Poll,
+};
+
 use crate::parsing::ClientHelloSupportedParameters;
+use s2n_tls::{
+    client_hello::ClientHello as S2NClientHello, connection::Connection, enums::Blinding,
+    security::Policy,
+};
 use s2n_tls_metrics_schema::static_lists::{Cipher, Group, Signature, Version};
 
+/// returns true if a client could handshake with a server which only allows
+/// the given parameters
+fn supports(
+    client_hello: &ClientHelloSupportedParameters,
+    allowed_versions: &[Version],
+    allowed_ciphers: &[Cipher],
+    allowed_groups: &[Group],
+    allowed_signatures: &[Signature],
+) -> bool {
+    let supported_version = client_hello
+        .supported_versions()
+        .iter()
+        .any(|client_version| allowed_versions.contains(client_version));
+
+    let supported_cipher = client_hello
+        .supported_ciphers()
+        .iter()
+        .any(|client_cipher| allowed_ciphers.contains(client_cipher));
+
+    let supported_signature = client_hello
+        .supported_signatures()
+        .map(|client_signatures| {
+            client_signatures
+                .iter()
+                .any(|client_signature| allowed_signatures.contains(client_signature))
+        })
+        .unwrap_or(false);
+
+    let supported_group = client_hello
+        .supported_groups()
+        .map(|client_groups| {
+            client_groups
+                .iter()
+                .any(|client_group| allowed_groups.contains(client_group))
+        })
+        .unwrap_or(false);
+
+    supported_version && supported_cipher && supported_group && supported_signature
+}
+
 pub(crate) trait TlsProfile {
     const ALLOWED_VERSIONS: &[Version];
     const ALLOWED_CIPHERS: &[Cipher];
@@ -15,36 +64,152 @@ pub(crate) trait TlsProfile {
 
     /// returns true if a client could handshake with this [`TlsProfile`]
     fn supported(client_hello: &ClientHelloSupportedParameters) -> bool {
-        let supported_version = client_hello
-            .supported_versions()
-            .iter()
-            .any(|client_version| Self::ALLOWED_VERSIONS.contains(client_version));
-
-        let supported_cipher = client_hello
-            .supported_ciphers()
-            .iter()
-            .any(|client_cipher| Self::ALLOWED_CIPHERS.contains(client_cipher));
-
-        let supported_signature = client_hello
-            .supported_signatures()
-            .map(|client_signatures| {
-                client_signatures
-                    .iter()
-                    .any(|client_signature| Self::ALLOWED_SIGNATURES.contains(client_signature))
-            })
-            .unwrap_or(false);
-
-        let supported_group = client_hello
-            .supported_groups()
-            .map(|client_groups| {
-                client_groups
-                    .iter()
-                    .any(|client_group| Self::ALLOWED_GROUPS.contains(client_group))
-            })
-            .unwrap_or(false);
-
-        supported_version && supported_cipher && supported_group && supported_signature
+        supports(
+            client_hello,
+            Self::ALLOWED_VERSIONS,
+            Self::ALLOWED_CIPHERS,
+            Self::ALLOWED_GROUPS,
+            Self::ALLOWED_SIGNATURES,
+        )
+    }
+}
+
+/// A named set of TLS parameters, registered at runtime with
+/// [`AggregatedMetricsSubscriber::with_compatibility_profiles`].
+///
+/// For each handshake, the subscriber checks whether the client hello offered
+/// at least one of the profile's versions, ciphers, groups, and signatures. The
+/// number of compatible handshakes is reported as
+/// `compatibility.profile.<name>`, which can be used to estimate how many
+/// clients would be able to connect if the server switched to that profile.
+///
+/// [`AggregatedMetricsSubscriber::with_compatibility_profiles`]: crate::AggregatedMetricsSubscriber::with_compatibility_profiles
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompatibilityProfile {
+    name: String,
+    versions: Vec<Version>,
+    ciphers: Vec<Cipher>,
+    groups: Vec<Group>,
+    signatures: Vec<Signature>,
+}
+
+impl CompatibilityProfile {
+    pub fn new(
+        name: impl Into<String>,
+        versions: Vec<Version>,
+        ciphers: Vec<Cipher>,
+        groups: Vec<Group>,
+        signatures: Vec<Signature>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            versions,
+            ciphers,
+            groups,
+            signatures,
+        }
+    }
+
+    /// Derive a profile from the parameters that `policy` offers in a client
+    /// hello.
+    ///
+    /// s2n-tls doesn't expose the contents of a security policy, so this
+    /// generates a client hello with `policy` and parses it. A server using
+    /// `policy` accepts the same parameters that a client using it offers.
+    pub fn from_policy(
+        name: impl Into<String>,
+        policy: &Policy,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let config = {
+            let mut builder = s2n_tls::config::Builder::new();
+            builder.set_security_policy(policy)?;
+            builder.with_system_certs(false)?;
+            builder.build()?
+        };
+        // The client's first flight is the client hello. Nothing is ever
+        // received, so the handshake then fails with S2N_ERR_CLOSED.
+        let mut records = Vec::<u8>::new();
+        let error = {
+            let mut client = Connection::new_client();
+            client
+                .set_config(config)?
+                .set_blinding(Blinding::SelfService)?
+                .set_send_callback(Some(capture_send))?
+                .set_receive_callback(Some(closed_recv))?;
+            // SAFETY: `records` outlives `client`, which is dropped at the end
+            // of this block.
+            unsafe { client.set_send_context(&mut records as *mut Vec<u8> as *mut c_void)? };
+            match client.poll_negotiate() {
+                Poll::Ready(Err(e)) => Some(e),
+                _ => None,
+            }
+        };
+        if records.is_empty() {
+            return Err(error.map_or_else(|| "no client hello was sent".into(), Into::into));
+        }
+        let client_hello = S2NClientHello::parse_client_hello(&handshake_messages(&records)?)?;
+        let parameters = ClientHelloSupportedParameters::new(&client_hello)?;
+        Ok(Self::new(
+            name,
+            parameters.supported_versions().to_vec(),
+            parameters.supported_ciphers().to_vec(),
+            parameters.supported_groups().unwrap_or_default().to_vec(),
+            parameters
+                .supported_signatures()
+                .unwrap_or_default()
+                .to_vec(),
+        ))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// returns true if a client could handshake with this profile
+    pub(crate) fn supported(&self, client_hello: &ClientHelloSupportedParameters) -> bool {
+        supports(
+            client_hello,
+            &self.versions,
+            &self.ciphers,
+            &self.groups,
+            &self.signatures,
+        )
+    }
+}
+
+/// An s2n-tls send callback which appends the data to the `Vec<u8>` context.
+unsafe extern "C" fn capture_send(context: *mut c_void, data: *const u8, len: u32) -> c_int {
+    let records = unsafe { &mut *(context as *mut Vec<u8>) };
+    records.extend_from_slice(unsafe { std::slice::from_raw_parts(data, len as usize) });
+    len as c_int
+}
+
+/// An s2n-tls receive callback for a peer which has closed the connection.
+unsafe extern "C" fn closed_recv(_context: *mut c_void, _data: *mut u8, _len: u32) -> c_int {
+    0
+}
+
+/// Concatenate the handshake messages in a sequence of plaintext TLS records,
+/// which is how a client hello that doesn't fit in a single record is sent.
+fn handshake_messages(mut records: &[u8]) -> Result<Vec<u8>, &'static str> {
+    const HANDSHAKE_CONTENT_TYPE: u8 = 22;
+    const RECORD_HEADER_LEN: usize = 5;
+
+    let mut messages = Vec::new();
+    while !records.is_empty() {
+        let header = records
+            .get(..RECORD_HEADER_LEN)
+            .ok_or("truncated record header")?;
+        let length = u16::from_be_bytes([header[3], header[4]]) as usize;
+        let fragment = records
+            .get(RECORD_HEADER_LEN..RECORD_HEADER_LEN + length)
+            .ok_or("truncated record")?;
+        if header[0] == HANDSHAKE_CONTENT_TYPE {
+            messages.extend_from_slice(fragment);
+        }
+        records = &records[RECORD_HEADER_LEN + length..];
     }
+    Ok(messages)
 }
 
 pub(crate) struct General20251201;
@@ -291,4 +456,38 @@ mod tests {
         assert!(Cnsa1::supported(&supported_parameters));
         assert!(Cnsa2::supported(&supported_parameters));
     }
+
+    #[test]
+    fn profile_from_policy() {
+        let default_profile =
+            CompatibilityProfile::from_policy("default", &Policy::from_version("default").unwrap())
+                .unwrap();
+        let cnsa2_profile =
+            CompatibilityProfile::from_policy("cnsa_2", &Policy::from_version("cnsa_2").unwrap())
+                .unwrap();
+        assert_eq!(default_profile.name(), "default");
+
+        let server = handshake_with_policy("default", &default_cert());
+        let ch = server.client_hello().unwrap();
+        let supported_parameters = ClientHelloSupportedParameters::new(ch).unwrap();
+        assert!(default_profile.supported(&supported_parameters));
+        // doesn't support required groups/signatures
+        assert!(!cnsa2_profile.supported(&supported_parameters));
+    }
+
+    #[test]
+    fn handshake_messages_across_records() {
+        let records = [
+            &[22, 3, 1, 0, 2][..],
+            &[1, 0],
+            // not a handshake record
+            &[21, 3, 3, 0, 1],
+            &[9],
+            &[22, 3, 1, 0, 1],
+            &[7],
+        ]
+        .concat();
+        assert_eq!(handshake_messages(&records).unwrap(), vec![1, 0, 7]);
+        assert!(handshake_messages(&records[..records.len() - 1]).is_err());
+    }
 }
@@ -12,12 +12,18 @@
 //! `handshake_success_count`) is left untouched, so each metric can be read directly
 //! as a real traffic figure.
 //!
+//! Detectors for common filters are provided: [`FingerprintDetector`] matches
+//! JA3 or JA4 fingerprints, [`ServerNameDetector`] matches server names such
+//! as health-check hostnames, and [`AnyOf`] and [`AllOf`] combine detectors.
+//!
 //! [`ClientHello`]: s2n_tls::client_hello::ClientHello
 //! [`AggregatedMetricsSubscriber`]: crate::AggregatedMetricsSubscriber
 
-use std::fmt::Debug;
+use std::{collections::HashSet, fmt::Debug};
+
+use s2n_tls::{client_hello::ClientHello, fingerprint::FingerprintType};
 
-use s2n_tls::client_hello::ClientHello;
+use crate::fingerprint::{fingerprint_type_name, with_fingerprint};
 
 /// Returns `true` for handshakes whose [`ClientHello`] should be counted as
 /// synthetic traffic.
@@ -27,3 +33,276 @@ use s2n_tls::client_hello::ClientHello;
 pub trait SyntheticTrafficDetector: Debug + Send + Sync + 'static {
     fn is_synthetic(&self, client_hello: &ClientHello) -> bool;
 }
+
+/// Flags handshakes by the fingerprint of their [`ClientHello`].
+///
+/// Fingerprints are compared against the fingerprint hash, e.g. the 32
+/// character hex MD5 hash for JA3, or the `t13d1516h2_...` string for JA4.
+/// If the client hello can't be fingerprinted (e.g. an SSLv2 client hello), the
+/// handshake isn't flagged.
+pub struct FingerprintDetector {
+    fingerprint_type: FingerprintType,
+    fingerprints: HashSet<String>,
+    /// whether listed fingerprints are synthetic (a deny list) or unlisted
+    /// fingerprints are synthetic (an allow list)
+    listed_are_synthetic: bool,
+}
+
+impl FingerprintDetector {
+    /// Flag handshakes whose fingerprint is in `fingerprints`, e.g. the
+    /// fingerprints of known scanners.
+    pub fn deny_list(
+        fingerprint_type: FingerprintType,
+        fingerprints: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self {
+            fingerprint_type,
+            fingerprints: fingerprints.into_iter().map(Into::into).collect(),
+            listed_are_synthetic: true,
+        }
+    }
+
+    /// Flag handshakes whose fingerprint is *not* in `fingerprints`, e.g. for
+    /// an endpoint where every real client is known.
+    pub fn allow_list(
+        fingerprint_type: FingerprintType,
+        fingerprints: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self {
+            listed_are_synthetic: false,
+            ..Self::deny_list(fingerprint_type, fingerprints)
+        }
+    }
+}
+
+impl Debug for FingerprintDetector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FingerprintDetector")
+            .field(
+                "fingerprint_type",
+                &fingerprint_type_name(self.fingerprint_type),
+            )
+            .field("fingerprints", &self.fingerprints)
+            .field("listed_are_synthetic", &self.listed_are_synthetic)
+            .finish()
+    }
+}
+
+impl SyntheticTrafficDetector for FingerprintDetector {
+    fn is_synthetic(&self, client_hello: &ClientHello) -> bool {
+        let listed = with_fingerprint(self.fingerprint_type, client_hello, |fingerprint| {
+            self.fingerprints.contains(fingerprint)
+        });
+        match listed {
+            Ok(listed) => listed == self.listed_are_synthetic,
+            Err(e) => {
+                tracing::debug!("failed to fingerprint client hello: {e}");
+                false
+            }
+        }
+    }
+}
+
+/// Flags handshakes by their server name (SNI), e.g. the hostnames used by
+/// load balancer health checks.
+///
+/// Patterns are matched against the whole server name, ignoring ASCII case. A
+/// `*` in a pattern matches any sequence of characters, including `.`, so
+/// `*.canary.example.com` matches `a.b.canary.example.com`.
+#[derive(Debug, Clone)]
+pub struct ServerNameDetector {
+    patterns: Vec<String>,
+    missing_is_synthetic: bool,
+}
+
+impl ServerNameDetector {
+    pub fn new(patterns: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            patterns: patterns.into_iter().map(Into::into).collect(),
+            missing_is_synthetic: false,
+        }
+    }
+
+    /// Also flag handshakes without a server name, e.g. health checks that
+    /// connect directly to an IP address.
+    pub fn with_missing_server_name(mut self) -> Self {
+        self.missing_is_synthetic = true;
+        self
+    }
+}
+
+impl SyntheticTrafficDetector for ServerNameDetector {
+    fn is_synthetic(&self, client_hello: &ClientHello) -> bool {
+        match client_hello.server_name() {
+            Ok(server_name) if !server_name.is_empty() => self
+                .patterns
+                .iter()
+                .any(|pattern| matches_pattern(pattern.as_bytes(), &server_name)),
+            _ => self.missing_is_synthetic,
+        }
+    }
+}
+
+/// Whether `pattern` matches all of `name`, where `*` matches any sequence of
+/// bytes. Other bytes are compared ignoring ASCII case.
+fn matches_pattern(pattern: &[u8], name: &[u8]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // the position of the most recent `*` in `pattern`, and the position in
+    // `name` that it has been matched up to
+    let mut backtrack = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(c) if c.eq_ignore_ascii_case(&name[n]) => {
+                p += 1;
+                n += 1;
+            }
+            // let the most recent `*` consume one more byte
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    backtrack = Some((star, matched + 1));
+                    p = star + 1;
+                    n = matched + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+/// Flags handshakes which any of the detectors flag. Detectors are called in
+/// order, stopping at the first match.
+///
+/// An empty `AnyOf` never flags a handshake.
+#[derive(Debug)]
+pub struct AnyOf(pub Vec<Box<dyn SyntheticTrafficDetector>>);
+
+impl SyntheticTrafficDetector for AnyOf {
+    fn is_synthetic(&self, client_hello: &ClientHello) -> bool {
+        self.0
+            .iter()
+            .any(|detector| detector.is_synthetic(client_hello))
+    }
+}
+
+/// Flags handshakes which all of the detectors flag. Detectors are called in
+/// order, stopping at the first which doesn't match.
+///
+/// An empty `AllOf` flags every handshake.
+#[derive(Debug)]
+pub struct AllOf(pub Vec<Box<dyn SyntheticTrafficDetector>>);
+
+impl SyntheticTrafficDetector for AllOf {
+    fn is_synthetic(&self, client_hello: &ClientHello) -> bool {
+        self.0
+            .iter()
+            .all(|detector| detector.is_synthetic(client_hello))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use s2n_tls::{
+        connection::Connection,
+        security::DEFAULT_TLS13,
+        testing::{TestPair, build_config},
+    };
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct Constant(bool);
+
+    impl SyntheticTrafficDetector for Constant {
+        fn is_synthetic(&self, _client_hello: &ClientHello) -> bool {
+            self.0
+        }
+    }
+
+    /// Returns the server connection, which holds the client hello.
+    fn handshake(server_name: Option<&str>) -> Connection {
+        let config = build_config(&DEFAULT_TLS13).unwrap();
+        let mut pair = TestPair::from_configs(&config, &config);
+        if let Some(server_name) = server_name {
+            pair.client.set_server_name(server_name).unwrap();
+        }
+        pair.handshake().unwrap();
+        pair.server
+    }
+
+    #[test]
+    fn pattern_matching() {
+        assert!(matches_pattern(
+            b"health.example.com",
+            b"HEALTH.example.com"
+        ));
+        assert!(matches_pattern(b"*.example.com", b"a.b.example.com"));
+        assert!(matches_pattern(b"health*", b"health"));
+        assert!(matches_pattern(b"*check*", b"healthcheck.example.com"));
+        assert!(matches_pattern(b"*", b"anything"));
+
+        assert!(!matches_pattern(b"*.example.com", b"example.com"));
+        assert!(!matches_pattern(b"health", b"health.example.com"));
+        assert!(!matches_pattern(b"a*b", b"ac"));
+    }
+
+    #[test]
+    fn server_name_detector() {
+        let detector = ServerNameDetector::new(["health.*", "*.canary.example.com"]);
+
+        let conn = handshake(Some("health.example.com"));
+        assert!(detector.is_synthetic(conn.client_hello().unwrap()));
+        let conn = handshake(Some("a.canary.example.com"));
+        assert!(detector.is_synthetic(conn.client_hello().unwrap()));
+        let conn = handshake(Some("www.example.com"));
+        assert!(!detector.is_synthetic(conn.client_hello().unwrap()));
+
+        let conn = handshake(None);
+        assert!(!detector.is_synthetic(conn.client_hello().unwrap()));
+        let detector = detector.with_missing_server_name();
+        assert!(detector.is_synthetic(conn.client_hello().unwrap()));
+    }
+
+    #[test]
+    fn fingerprint_detector() {
+        let conn = handshake(None);
+        let client_hello = conn.client_hello().unwrap();
+
+        for fingerprint_type in [FingerprintType::JA3, FingerprintType::JA4] {
+            let fingerprint =
+                with_fingerprint(fingerprint_type, client_hello, str::to_owned).unwrap();
+            let listed = [fingerprint];
+            let unlisted: [&str; 0] = [];
+
+            let detector = FingerprintDetector::deny_list(fingerprint_type, listed.clone());
+            assert!(detector.is_synthetic(client_hello));
+            let detector = FingerprintDetector::deny_list(fingerprint_type, unlisted);
+            assert!(!detector.is_synthetic(client_hello));
+
+            let detector = FingerprintDetector::allow_list(fingerprint_type, listed);
+            assert!(!detector.is_synthetic(client_hello));
+            let detector = FingerprintDetector::allow_list(fingerprint_type, unlisted);
+            assert!(detector.is_synthetic(client_hello));
+        }
+    }
+
+    #[test]
+    fn combinators() {
+        let conn = handshake(None);
+        let client_hello = conn.client_hello().unwrap();
+
+        let any = AnyOf(vec![Box::new(Constant(false)), Box::new(Constant(true))]);
+        assert!(any.is_synthetic(client_hello));
+        assert!(!AnyOf(vec![Box::new(Constant(false))]).is_synthetic(client_hello));
+        assert!(!AnyOf(Vec::new()).is_synthetic(client_hello));
+
+        let all = AllOf(vec![Box::new(Constant(false)), Box::new(Constant(true))]);
+        assert!(!all.is_synthetic(client_hello));
+        assert!(AllOf(vec![Box::new(Constant(true))]).is_synthetic(client_hello));
+        assert!(AllOf(Vec::new()).is_synthetic(client_hello));
+    }
+}
@@ -0,0 +1,57 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{cell::RefCell, thread::LocalKey};
+
+use s2n_tls::{
+    client_hello::ClientHello,
+    error::Error,
+    fingerprint::{Builder, FingerprintType},
+};
+
+type CachedBuilder = RefCell<Option<Builder>>;
+
+thread_local! {
+    /// Fingerprint builders are reusable, so keep one per thread rather than
+    /// allocating a new one for each handshake.
+    static JA3_BUILDER: CachedBuilder = const { RefCell::new(None) };
+    static JA4_BUILDER: CachedBuilder = const { RefCell::new(None) };
+}
+
+/// Compute the `fingerprint_type` fingerprint of `client_hello` and pass its
+/// hash to `action`.
+pub(crate) fn with_fingerprint<T>(
+    fingerprint_type: FingerprintType,
+    client_hello: &ClientHello,
+    action: impl FnOnce(&str) -> T,
+) -> Result<T, Error> {
+    let cached: &'static LocalKey<CachedBuilder> = match fingerprint_type {
+        FingerprintType::JA3 => &JA3_BUILDER,
+        FingerprintType::JA4 => &JA4_BUILDER,
+        // FingerprintType is non-exhaustive. Types added after this crate was
+        // written aren't cached.
+        _ => {
+            let mut builder = Builder::new(fingerprint_type)?;
+            let mut fingerprint = builder.build(client_hello)?;
+            return Ok(action(fingerprint.hash()?));
+        }
+    };
+    cached.with(|builder| {
+        let mut builder = builder.borrow_mut();
+        let builder = match &mut *builder {
+            Some(builder) => builder,
+            None => builder.insert(Builder::new(fingerprint_type)?),
+        };
+        let mut fingerprint = builder.build(client_hello)?;
+        Ok(action(fingerprint.hash()?))
+    })
+}
+
+/// The name of `fingerprint_type`, which doesn't implement `Debug`.
+pub(crate) fn fingerprint_type_name(fingerprint_type: FingerprintType) -> &'static str {
+    match fingerprint_type {
+        FingerprintType::JA3 => "JA3",
+        FingerprintType::JA4 => "JA4",
+        _ => "unknown",
+    }
+}
@@ -0,0 +1,136 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    marker::PhantomData,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use s2n_tls_metrics_schema::{
+    histogram::{FrozenHistogram, FrozenHistograms, HISTOGRAM_BUCKETS, bucket_index},
+    static_lists::FiniteCounter,
+};
+
+/// Atomic-backed latency histogram.
+///
+/// Uses the fixed bucket layout from [`s2n_tls_metrics_schema::histogram`].
+/// Hot path is one relaxed `fetch_add` on the bucket for the value.
+pub(crate) struct Histogram {
+    buckets: [AtomicU64; HISTOGRAM_BUCKETS],
+}
+
+impl Histogram {
+    pub(crate) fn new() -> Self {
+        Self {
+            buckets: [0u64; HISTOGRAM_BUCKETS].map(AtomicU64::new),
+        }
+    }
+
+    pub(crate) fn record(&self, duration: Duration) {
+        // accuracy: as long as the duration is less than 500,000 years this
+        // cast will not truncate.
+        let index = bucket_index(duration.as_micros() as u64);
+        self.buckets[index].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Snapshot current values. `&mut self` guarantees no concurrent writer,
+    /// so relaxed loads are sufficient.
+    pub(crate) fn freeze(&mut self) -> FrozenHistogram {
+        FrozenHistogram::from_buckets(std::array::from_fn(|i| {
+            self.buckets[i].load(Ordering::Relaxed)
+        }))
+    }
+}
+
+impl std::fmt::Debug for Histogram {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut dbg = f.debug_struct("Histogram");
+        for (index, atomic) in self.buckets.iter().enumerate() {
+            let value = atomic.load(Ordering::Relaxed);
+            if value > 0 {
+                dbg.field(&index.to_string(), &value);
+            }
+        }
+        dbg.finish()
+    }
+}
+
+/// One [`Histogram`] for each value of `T`.
+pub(crate) struct Histograms<const N: usize, T: FiniteCounter<N>> {
+    histograms: [Histogram; N],
+    element: PhantomData<T>,
+}
+
+impl<const N: usize, T: FiniteCounter<N>> Histograms<N, T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            histograms: std::array::from_fn(|_| Histogram::new()),
+            element: PhantomData,
+        }
+    }
+
+    /// Record `duration` in the histogram for `element`. No-op if `element` is
+    /// not in [`FiniteCounter::ELEMENTS`].
+    pub(crate) fn record(&self, element: &T, duration: Duration) {
+        if let Some(histogram) = element
+            .slot_from_key()
+            .and_then(|slot| self.histograms.get(slot))
+        {
+            histogram.record(duration);
+        }
+    }
+
+    pub(crate) fn freeze(&mut self) -> FrozenHistograms<N, T> {
+        FrozenHistograms::from_histograms(std::array::from_fn(|i| self.histograms[i].freeze()))
+    }
+}
+
+impl<const N: usize, T: FiniteCounter<N>> std::fmt::Debug for Histograms<N, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = format!("Histograms<{}>", std::any::type_name::<T>());
+        f.debug_struct(&name)
+            .field("histograms", &self.histograms)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use s2n_tls_metrics_schema::static_lists::HandshakeMessage;
+
+    #[test]
+    fn histogram_freeze_snapshots_buckets() {
+        let mut histogram = Histogram::new();
+        histogram.record(Duration::from_micros(10));
+        histogram.record(Duration::from_micros(10));
+        histogram.record(Duration::from_millis(5));
+
+        let frozen = histogram.freeze();
+        assert_eq!(frozen.count(), 3);
+        assert_eq!(frozen.buckets()[bucket_index(10)], 2);
+        assert_eq!(frozen.buckets()[bucket_index(5_000)], 1);
+    }
+
+    #[test]
+    fn histograms_record_by_element() {
+        let mut histograms = Histograms::<{ HandshakeMessage::COUNT }, HandshakeMessage>::new();
+        histograms.record(&HandshakeMessage::ServerCert, Duration::from_micros(100));
+        histograms.record(&HandshakeMessage::ServerCert, Duration::from_micros(200));
+        histograms.record(&HandshakeMessage::ClientHello, Duration::from_micros(50));
+
+        let frozen = histograms.freeze();
+        let counts: Vec<(HandshakeMessage, u64)> = frozen
+            .iter_non_empty()
+            .map(|(_, message, histogram)| (message, histogram.count()))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                (HandshakeMessage::ClientHello, 1),
+                (HandshakeMessage::ServerCert, 2)
+            ]
+        );
+    }
+}
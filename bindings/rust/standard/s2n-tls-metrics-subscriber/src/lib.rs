@@ -2,10 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod bounded_set;
+mod checkpoints;
 mod client_issue;
 mod compatibility;
 pub(crate) mod counter;
 pub mod detector;
+mod fingerprint;
+mod histogram;
+pub mod openmetrics;
 #[cfg(feature = "fuzzing")]
 pub mod parsing;
 #[cfg(not(feature = "fuzzing"))]
@@ -15,10 +19,14 @@ pub mod subscriber;
 pub mod telemetry_sink;
 #[cfg(test)]
 mod test_utils;
+pub mod trace;
 
+pub use compatibility::CompatibilityProfile;
 pub use detector::SyntheticTrafficDetector;
+pub use openmetrics::OpenMetricsSink;
 pub use subscriber::AggregatedMetricsSubscriber;
 pub use telemetry_sink::TelemetrySink;
+pub use trace::TracingSubscriber;
 
 /// Identifies the source of a metric record by service and resource.
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -59,7 +67,6 @@ impl MetricRecord {
         Self(inner)
     }
 
-    #[cfg(test)]
     pub(crate) fn as_schema(&self) -> &s2n_tls_metrics_schema::record::MetricRecord {
         &self.0
     }
@@ -0,0 +1,507 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A [`TelemetrySink`] that serves cumulative metrics in the OpenMetrics text
+//! format.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use s2n_tls_metrics_schema::{metric_names as names, record::MetricKind};
+
+use crate::{MetricRecord, telemetry_sink::TelemetrySink};
+
+/// Prefix added to every exposed metric name.
+const NAME_PREFIX: &str = "s2n_tls_";
+
+const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// How long [`OpenMetricsSink::serve`] waits on a single read or write, so that
+/// a stalled client can't block the scrapes behind it.
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The most distinct values of a labelled family that are exposed for each
+/// attribution. Any further values are counted in the [`OTHER_LABEL_VALUE`]
+/// sample, so that a scrape stays bounded no matter what clients send.
+const MAX_LABEL_VALUES: usize = 100;
+
+/// The label value of the sample for values that aren't exposed individually.
+const OTHER_LABEL_VALUE: &str = "other";
+
+/// Metrics which are keyed by an arbitrary string, as `(name prefix, label,
+/// name of the values which weren't recorded individually)`. Each is exposed
+/// as a single family, with the string as the label value.
+const LABELLED_FAMILIES: [(&str, &str, &str); 4] = [
+    (
+        names::SECURITY_POLICY_PREFIX,
+        "policy",
+        names::SECURITY_POLICY_TOO_MANY,
+    ),
+    (
+        names::HANDSHAKE_FAILURE_ERROR_PREFIX,
+        "error",
+        names::HANDSHAKE_FAILURE_ERROR_TOO_MANY,
+    ),
+    (
+        names::COMPATIBILITY_PROFILE_PREFIX,
+        "profile",
+        names::COMPATIBILITY_PROFILE_TOO_MANY,
+    ),
+    (
+        names::CLIENT_HELLO_JA4_PREFIX,
+        "fingerprint",
+        names::CLIENT_HELLO_JA4_OTHER,
+    ),
+];
+
+/// The `service`, `resource`, and `component` labels of a sample.
+type Labels = (String, String, String);
+
+#[derive(Debug)]
+struct Family {
+    kind: MetricKind,
+    /// the name of the label which distinguishes the samples of each attribution,
+    /// if there is one
+    label: Option<&'static str>,
+    samples: BTreeMap<Labels, BTreeMap<Option<String>, u64>>,
+}
+
+/// A [`TelemetrySink`] which accumulates every exported record and exposes the
+/// totals in the [OpenMetrics](https://openmetrics.io) text format.
+///
+/// Counter metrics are summed across records, so they only ever increase.
+/// Gauge metrics (percentiles and security policies) hold the values from the
+/// most recent record of each attribution, and are removed if that record
+/// didn't contain them. Samples are labelled with the `service`, `resource`,
+/// and `component` of the record's attribution.
+///
+/// Metric names are the stable names from
+/// [`s2n_tls_metrics_schema::metric_names`], prefixed with `s2n_tls_`, with
+/// any character that isn't valid in an OpenMetrics name replaced by `_`.
+///
+/// Metrics keyed by an arbitrary string (security policies, failure errors,
+/// compatibility profiles, and JA4 fingerprints) are instead exposed as one
+/// family per prefix, with the string as the `policy`, `error`, `profile`, or
+/// `fingerprint` label. At most 100 distinct values are exposed for each
+/// family and attribution, and the rest are counted in a sample labelled
+/// `other`.
+///
+/// The sink is cheap to clone, and all clones share the same totals. Pass one
+/// clone to the [`AggregatedMetricsSubscriber`](crate::AggregatedMetricsSubscriber)
+/// and keep another to call [`OpenMetricsSink::render`] or
+/// [`OpenMetricsSink::serve`].
+#[derive(Debug, Clone, Default)]
+pub struct OpenMetricsSink {
+    families: Arc<Mutex<BTreeMap<String, Family>>>,
+}
+
+impl OpenMetricsSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Render the current totals as an OpenMetrics text exposition.
+    pub fn render(&self) -> String {
+        let families = self.families.lock().unwrap();
+        let mut output = String::new();
+        for (name, family) in families.iter() {
+            let (kind, suffix) = match family.kind {
+                MetricKind::Counter => ("counter", "_total"),
+                MetricKind::Gauge => ("gauge", ""),
+            };
+            // writing to a String is infallible
+            let _ = writeln!(output, "# TYPE {name} {kind}");
+            for ((service, resource, component), samples) in &family.samples {
+                for (label_value, value) in samples {
+                    let label = match (family.label, label_value) {
+                        (Some(label), Some(value)) => {
+                            format!(",{label}=\"{}\"", escape_label_value(value))
+                        }
+                        _ => String::new(),
+                    };
+                    let _ = writeln!(
+                        output,
+                        "{name}{suffix}{{service=\"{}\",resource=\"{}\",component=\"{}\"{label}}} {value}",
+                        escape_label_value(service),
+                        escape_label_value(resource),
+                        escape_label_value(component),
+                    );
+                }
+            }
+        }
+        output.push_str("# EOF\n");
+        output
+    }
+
+    /// Serve [`OpenMetricsSink::render`] over HTTP on `listener`.
+    ///
+    /// This is a minimal blocking HTTP/1.0 server intended for scraping
+    /// by a local agent. Every `GET` request is answered with the current
+    /// exposition, regardless of the path. Connections are handled one at a
+    /// time on a dedicated thread, which runs until the listener fails. A
+    /// connection which doesn't send its request or read the response within
+    /// a few seconds is dropped.
+    pub fn serve(&self, listener: TcpListener) -> JoinHandle<()> {
+        let sink = self.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let result = stream.and_then(|stream| sink.respond(stream));
+                if let Err(e) = result {
+                    tracing::debug!("failed to serve OpenMetrics request: {e}");
+                }
+            }
+        })
+    }
+
+    fn respond(&self, mut stream: TcpStream) -> std::io::Result<()> {
+        stream.set_read_timeout(Some(IO_TIMEOUT))?;
+        stream.set_write_timeout(Some(IO_TIMEOUT))?;
+
+        // The request body is ignored, so only the request line matters.
+        let mut request = [0; 1024];
+        let read = stream.read(&mut request)?;
+        let response = if request[..read].starts_with(b"GET ") {
+            let body = self.render();
+            format!(
+                "HTTP/1.0 200 OK\r\nContent-Type: {CONTENT_TYPE}\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            )
+        } else {
+            "HTTP/1.0 405 Method Not Allowed\r\nContent-Length: 0\r\n\r\n".to_owned()
+        };
+        stream.write_all(response.as_bytes())?;
+        stream.flush()
+    }
+
+    fn accumulate(
+        families: &mut BTreeMap<String, Family>,
+        labels: &Labels,
+        name: &str,
+        value: u64,
+        kind: MetricKind,
+    ) {
+        let (name, label) = split_label(name);
+        let family = families.entry(metric_name(name)).or_insert_with(|| Family {
+            kind,
+            label: label.map(|(label, _)| label),
+            samples: BTreeMap::new(),
+        });
+        let samples = family.samples.entry(labels.clone()).or_default();
+        let label_value = label.map(|(_, value)| {
+            let other = Some(OTHER_LABEL_VALUE.to_owned());
+            let exposed = samples.len() - usize::from(samples.contains_key(&other));
+            if exposed < MAX_LABEL_VALUES || samples.contains_key(&Some(value.to_owned())) {
+                value
+            } else {
+                OTHER_LABEL_VALUE
+            }
+        });
+        let sample = samples.entry(label_value.map(str::to_owned)).or_default();
+        match family.kind {
+            MetricKind::Counter => *sample = sample.saturating_add(value),
+            MetricKind::Gauge => *sample = value,
+        }
+    }
+}
+
+impl TelemetrySink for OpenMetricsSink {
+    fn export_record(&self, record: MetricRecord) {
+        let record = record.as_schema();
+        let attribution = &record.attribution;
+        let labels = (
+            attribution.service.clone(),
+            attribution.resource.clone(),
+            attribution.component.clone(),
+        );
+        let mut families = self.families.lock().unwrap();
+        // Gauges describe the latest record, so any gauge which it doesn't
+        // contain is stale.
+        for family in families.values_mut() {
+            if family.kind == MetricKind::Gauge {
+                family.samples.remove(&labels);
+            }
+        }
+        record.handshake.visit_metrics(|name, value, kind| {
+            Self::accumulate(&mut families, &labels, &name, value, kind)
+        });
+        families.retain(|_, family| !family.samples.is_empty());
+    }
+}
+
+/// Split a schema metric name into the name of its family, and the label and
+/// label value which identify it within that family, if it is keyed by a string.
+fn split_label(name: &str) -> (&str, Option<(&'static str, &str)>) {
+    for (prefix, label, other) in LABELLED_FAMILIES {
+        if name == other {
+            return (prefix, Some((label, OTHER_LABEL_VALUE)));
+        }
+        if let Some(value) = name.strip_prefix(prefix).and_then(|n| n.strip_prefix('.')) {
+            return (prefix, Some((label, value)));
+        }
+    }
+    (name, None)
+}
+
+/// Convert a schema metric name into a valid OpenMetrics metric name.
+fn metric_name(name: &str) -> String {
+    let sanitized = name.chars().map(|c| {
+        if c.is_ascii_alphanumeric() || c == '_' || c == ':' {
+            c
+        } else {
+            '_'
+        }
+    });
+    NAME_PREFIX.chars().chain(sanitized).collect()
+}
+
+fn escape_label_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use s2n_tls::{
+        security::DEFAULT_TLS13,
+        testing::{TestPair, build_config, config_builder},
+    };
+    use s2n_tls_metrics_schema::{
+        bounded_set::{FrozenBoundedStringCounter, FrozenBoundedStringSet},
+        record::FrozenHandshakeRecord,
+    };
+
+    use crate::{AggregatedMetricsSubscriber, Attribution};
+
+    use super::*;
+
+    fn handshake_endpoint() -> (
+        AggregatedMetricsSubscriber<OpenMetricsSink>,
+        OpenMetricsSink,
+    ) {
+        let sink = OpenMetricsSink::new();
+        let attribution = Attribution {
+            service: "test_server".to_owned(),
+            resource: "test\"resource".to_owned(),
+            component: String::new(),
+        };
+        let subscriber = AggregatedMetricsSubscriber::new(sink.clone(), attribution);
+        let server_config = {
+            let mut config = config_builder(&DEFAULT_TLS13).unwrap();
+            config.set_event_subscriber(subscriber.clone()).unwrap();
+            config.build().unwrap()
+        };
+        let client_config = build_config(&DEFAULT_TLS13).unwrap();
+        let mut pair = TestPair::from_configs(&client_config, &server_config);
+        pair.handshake().unwrap();
+        (subscriber, sink)
+    }
+
+    const LABELS: &str = r#"service="test_server",resource="test\"resource",component="""#;
+
+    /// Export `handshake` with the same attribution as [`handshake_endpoint`].
+    fn export(sink: &OpenMetricsSink, handshake: FrozenHandshakeRecord) {
+        let attribution = Attribution {
+            service: "test_server".to_owned(),
+            resource: "test\"resource".to_owned(),
+            component: String::new(),
+        };
+        sink.export_record(MetricRecord::new(
+            s2n_tls_metrics_schema::record::MetricRecord {
+                attribution: attribution.into_schema(),
+                handshake,
+            },
+        ));
+    }
+
+    #[test]
+    fn metric_name_sanitized() {
+        assert_eq!(
+            metric_name("negotiated.cipher.TLS_AES_128_GCM_SHA256"),
+            "s2n_tls_negotiated_cipher_TLS_AES_128_GCM_SHA256"
+        );
+        assert_eq!(
+            metric_name("tls_policy.20240503-v2"),
+            "s2n_tls_tls_policy_20240503_v2"
+        );
+    }
+
+    #[test]
+    fn counters_accumulate_across_records() {
+        let (subscriber, sink) = handshake_endpoint();
+        subscriber.finish_record();
+        let p50 = "# TYPE s2n_tls_handshake_duration_us_p50 gauge\n";
+        assert!(sink.render().contains(p50));
+        // an empty record still contributes zero-valued counters
+        subscriber.finish_record();
+
+        let exposition = sink.render();
+        assert!(exposition.contains("# TYPE s2n_tls_handshake_success_count counter\n"));
+        assert!(exposition.contains(&format!(
+            "s2n_tls_handshake_success_count_total{{{LABELS}}} 1\n"
+        )));
+        assert!(exposition.contains(&format!(
+            "s2n_tls_handshake_failure_count_total{{{LABELS}}} 0\n"
+        )));
+        // but it has no percentiles, so those of the previous record are removed
+        assert!(!exposition.contains(p50));
+        assert!(exposition.ends_with("# EOF\n"));
+    }
+
+    #[test]
+    fn string_keyed_metrics_labelled() {
+        let fingerprint = "t13d1516h2_8daaf6152771_02713d6af862";
+        let sink = OpenMetricsSink::new();
+        export(
+            &sink,
+            FrozenHandshakeRecord {
+                security_policies: FrozenBoundedStringSet::Entries(
+                    ["default_tls13".to_owned()].into(),
+                ),
+                failure_errors: FrozenBoundedStringCounter {
+                    entries: [("S2N_ERR_BAD_MESSAGE".to_owned(), 2)].into(),
+                    overflow: 1,
+                },
+                client_hello_ja4: FrozenBoundedStringCounter {
+                    entries: [(fingerprint.to_owned(), 3)].into(),
+                    overflow: 0,
+                },
+                ..Default::default()
+            },
+        );
+
+        let exposition = sink.render();
+        assert!(exposition.contains("# TYPE s2n_tls_tls_policy gauge\n"));
+        assert!(exposition.contains("# TYPE s2n_tls_client_hello_ja4 counter\n"));
+        for sample in [
+            format!(r#"s2n_tls_tls_policy{{{LABELS},policy="default_tls13"}} 1"#),
+            format!(
+                r#"s2n_tls_handshake_failure_error_total{{{LABELS},error="S2N_ERR_BAD_MESSAGE"}} 2"#
+            ),
+            // the errors which the record didn't name individually
+            format!(r#"s2n_tls_handshake_failure_error_total{{{LABELS},error="other"}} 1"#),
+            format!(r#"s2n_tls_client_hello_ja4_total{{{LABELS},fingerprint="{fingerprint}"}} 3"#),
+        ] {
+            assert!(
+                exposition.lines().any(|line| line == sample),
+                "{sample} not in {exposition}"
+            );
+        }
+    }
+
+    #[test]
+    fn label_values_capped() {
+        let fingerprints = |range: std::ops::Range<usize>| FrozenHandshakeRecord {
+            client_hello_ja4: FrozenBoundedStringCounter {
+                entries: range.map(|i| (format!("ja4_{i:03}"), 1)).collect(),
+                overflow: 0,
+            },
+            ..Default::default()
+        };
+        let samples = |exposition: &str| {
+            exposition
+                .lines()
+                .filter(|line| line.starts_with("s2n_tls_client_hello_ja4_total{"))
+                .count()
+        };
+        let sample = |fingerprint: &str, value: u64| {
+            format!(r#"s2n_tls_client_hello_ja4_total{{{LABELS},fingerprint="{fingerprint}"}} {value}"#)
+        };
+
+        let sink = OpenMetricsSink::new();
+        export(&sink, fingerprints(0..MAX_LABEL_VALUES + 5));
+        let exposition = sink.render();
+        assert_eq!(samples(&exposition), MAX_LABEL_VALUES + 1);
+        assert!(exposition.lines().any(|line| line == sample("other", 5)));
+
+        // Values which are already exposed keep accumulating, but new values
+        // are still counted in the other sample.
+        export(&sink, fingerprints(0..1));
+        export(&sink, fingerprints(MAX_LABEL_VALUES + 5..MAX_LABEL_VALUES + 10));
+        let exposition = sink.render();
+        assert_eq!(samples(&exposition), MAX_LABEL_VALUES + 1);
+        assert!(exposition.lines().any(|line| line == sample("ja4_000", 2)));
+        assert!(exposition.lines().any(|line| line == sample("other", 10)));
+    }
+
+    #[test]
+    fn gauges_cleared_when_missing() {
+        let policies = |policies: &[&str]| FrozenHandshakeRecord {
+            security_policies: FrozenBoundedStringSet::Entries(
+                policies.iter().map(|&policy| policy.to_owned()).collect(),
+            ),
+            ..Default::default()
+        };
+        let sample = |policy: &str| format!(r#"s2n_tls_tls_policy{{{LABELS},policy="{policy}"}} 1"#);
+
+        let sink = OpenMetricsSink::new();
+        export(&sink, policies(&["a", "b"]));
+        let exposition = sink.render();
+        assert!(exposition.lines().any(|line| line == sample("a")));
+        assert!(exposition.lines().any(|line| line == sample("b")));
+
+        export(&sink, policies(&["b"]));
+        let exposition = sink.render();
+        assert!(!exposition.lines().any(|line| line == sample("a")));
+        assert!(exposition.lines().any(|line| line == sample("b")));
+
+        // A family without any samples isn't exposed at all.
+        export(&sink, policies(&[]));
+        assert!(!sink.render().contains("s2n_tls_tls_policy"));
+    }
+
+    #[test]
+    fn serve_responds_with_exposition() {
+        let (subscriber, sink) = handshake_endpoint();
+        subscriber.finish_record();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        sink.serve(listener);
+
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        let (headers, body) = response.split_once("\r\n\r\n").unwrap();
+        assert!(headers.starts_with("HTTP/1.0 200 OK\r\n"));
+        assert!(headers.contains(CONTENT_TYPE));
+        assert_eq!(body, sink.render());
+    }
+
+    /// A client which never sends its request doesn't block the next scrape
+    /// indefinitely.
+    #[test]
+    fn serve_times_out_stalled_client() {
+        let (_subscriber, sink) = handshake_endpoint();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        sink.serve(listener);
+
+        let _stalled = TcpStream::connect(address).unwrap();
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream.set_read_timeout(Some(IO_TIMEOUT * 2)).unwrap();
+        stream.write_all(b"GET /metrics HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.0 200 OK\r\n"));
+    }
+}
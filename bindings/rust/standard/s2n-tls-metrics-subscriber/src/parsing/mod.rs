@@ -123,10 +123,24 @@ impl ClientHelloSupportedParameters {
     }
 }
 
+/// https://www.iana.org/assignments/tls-extensiontype-values/tls-extensiontype-values.xhtml#tls-extensiontype-values-1
+const SESSION_TICKET_ID: u16 = 35;
+
+/// Whether the client offered a session ticket for resumption. An empty
+/// session ticket extension only indicates support for tickets.
+pub(crate) fn offered_session_ticket(
+    client_hello: &S2NClientHello,
+) -> Result<bool, s2n_tls::error::Error> {
+    Ok(client_hello.get_extension_length(SESSION_TICKET_ID)? > 0)
+}
+
 /// We generally discourage the use of direct extension retrieval so it isn't exposed
 /// in the s2n-tls crate.
 trait S2NClientHelloExtension {
     fn get_extension(&self, extension_id: u16) -> Result<Option<Vec<u8>>, s2n_tls::error::Error>;
+
+    /// The length of the extension_data, which is 0 if the extension is absent.
+    fn get_extension_length(&self, extension_id: u16) -> Result<usize, s2n_tls::error::Error>;
 }
 
 impl S2NClientHelloExtension for s2n_tls::client_hello::ClientHello {
@@ -135,25 +149,13 @@ impl S2NClientHelloExtension for s2n_tls::client_hello::ClientHello {
     /// The extension ID should be one of the values from this list:
     /// https://www.iana.org/assignments/tls-extensiontype-values/tls-extensiontype-values.xhtml#tls-extensiontype-values-1
     fn get_extension(&self, extension_id: u16) -> Result<Option<Vec<u8>>, s2n_tls::error::Error> {
-        // we are depending on an internal implementation detail, where ClientHello
-        // is aliased to the raw s2n_tls_sys type. Below is a compile-time assertion
-        // that this hasn't changed. Even if the change isn't run through
-        // s2n-tls CI, customers will fail to build instead of
-        // encountering a runtime error.
-        static_assertions::assert_eq_size!(
-            s2n_tls::client_hello::ClientHello,
-            s2n_tls_sys::s2n_client_hello
-        );
-
-        let raw_ch =
-            self as *const s2n_tls::client_hello::ClientHello as *mut s2n_tls_sys::s2n_client_hello;
-        let extension_length = unsafe {
-            s2n_client_hello_get_extension_length(raw_ch, extension_id as c_uint).into_result()
-        }?;
+        let extension_length = self.get_extension_length(extension_id)?;
         if extension_length == 0 {
             return Ok(None);
         }
 
+        let raw_ch =
+            self as *const s2n_tls::client_hello::ClientHello as *mut s2n_tls_sys::s2n_client_hello;
         let mut extension_data = vec![0; extension_length];
         let written_length = unsafe {
             s2n_client_hello_get_extension_by_id(
@@ -169,6 +171,24 @@ impl S2NClientHelloExtension for s2n_tls::client_hello::ClientHello {
 
         Ok(Some(extension_data))
     }
+
+    fn get_extension_length(&self, extension_id: u16) -> Result<usize, s2n_tls::error::Error> {
+        // we are depending on an internal implementation detail, where ClientHello
+        // is aliased to the raw s2n_tls_sys type. Below is a compile-time assertion
+        // that this hasn't changed. Even if the change isn't run through
+        // s2n-tls CI, customers will fail to build instead of
+        // encountering a runtime error.
+        static_assertions::assert_eq_size!(
+            s2n_tls::client_hello::ClientHello,
+            s2n_tls_sys::s2n_client_hello
+        );
+
+        let raw_ch =
+            self as *const s2n_tls::client_hello::ClientHello as *mut s2n_tls_sys::s2n_client_hello;
+        unsafe {
+            s2n_client_hello_get_extension_length(raw_ch, extension_id as c_uint).into_result()
+        }
+    }
 }
 
 #[cfg(test)]
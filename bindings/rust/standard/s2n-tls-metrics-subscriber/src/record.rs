@@ -3,25 +3,36 @@
 
 use std::{
     sync::atomic::{AtomicU64, Ordering},
-    time::SystemTime,
+    time::{Duration, SystemTime},
 };
 
-use s2n_tls::{connection::Connection, error::Error as S2NError, events::HandshakeSuccess};
+use s2n_tls::{
+    client_hello::ClientHello as S2NClientHello,
+    connection::Connection,
+    enums::EarlyDataStatus,
+    error::{Error as S2NError, ErrorType as S2NErrorType},
+    events::HandshakeSuccess,
+    fingerprint::FingerprintType,
+};
 use s2n_tls_metrics_schema::{
     record::FrozenHandshakeRecord,
     static_lists::{
         Alert, CERT_KEY_COUNT, CERT_SIG_COUNT, CIPHER_COUNT, CertKeyType, CertSignatureAlgorithm,
-        Cipher, ClientIssue, DEFINED_ALERTS_COUNT, GROUP_COUNT, Group, PROTOCOL_COUNT,
-        SIGNATURE_COUNT, Signature, Version,
+        Cipher, ClientIssue, DEFINED_ALERTS_COUNT, EarlyData, ErrorType, GROUP_COUNT, Group,
+        HandshakeMessage, PROTOCOL_COUNT, Resumption, SIGNATURE_COUNT, Signature, Version,
     },
 };
 
 use crate::{
-    bounded_set::BoundedStringSet,
+    bounded_set::{BoundedStringCounter, BoundedStringSet, TopKStringCounter},
     client_issue::has_issue,
-    compatibility::{Cnsa1, Cnsa2, Fips20251201, General20251201, TlsProfile},
+    compatibility::{
+        Cnsa1, Cnsa2, CompatibilityProfile, Fips20251201, General20251201, TlsProfile,
+    },
     counter::Counter,
     detector::SyntheticTrafficDetector,
+    fingerprint::with_fingerprint,
+    histogram::{Histogram, Histograms},
     parsing::{self, ClientHelloSupportedParameters},
 };
 
@@ -37,6 +48,60 @@ fn protocol_version_to_iana(v: s2n_tls::enums::Version) -> Option<Version> {
     Some(Version(s2n_codec::zerocopy::U16::new(iana)))
 }
 
+fn error_type_to_schema(error_type: S2NErrorType) -> ErrorType {
+    match error_type {
+        S2NErrorType::IOError => ErrorType::IOError,
+        S2NErrorType::ConnectionClosed => ErrorType::ConnectionClosed,
+        S2NErrorType::Blocked => ErrorType::Blocked,
+        S2NErrorType::Alert => ErrorType::Alert,
+        S2NErrorType::ProtocolError => ErrorType::ProtocolError,
+        S2NErrorType::InternalError => ErrorType::InternalError,
+        S2NErrorType::UsageError => ErrorType::UsageError,
+        S2NErrorType::Application => ErrorType::Application,
+        _ => ErrorType::Unknown,
+    }
+}
+
+/// Determine how the keys for a successful handshake were established.
+fn resumption(
+    conn: &Connection,
+    handshake_type: &str,
+    client_hello: Option<&S2NClientHello>,
+) -> Resumption {
+    if handshake_type.contains("FULL_HANDSHAKE") {
+        return Resumption::Full;
+    }
+    // an abbreviated handshake which didn't resume a session used an external PSK
+    if !conn.resumed() {
+        return match conn.negotiated_psk_identity_length() {
+            Ok(length) if length > 0 => Resumption::ExternalPsk,
+            _ => Resumption::Unknown,
+        };
+    }
+    // TLS 1.3 sessions can only be resumed with session tickets
+    if matches!(
+        conn.actual_protocol_version(),
+        Ok(s2n_tls::enums::Version::TLS13)
+    ) {
+        return Resumption::SessionTicket;
+    }
+    // A TLS 1.2 server resumes using the session ticket if the client offered
+    // one, and otherwise uses the session ID.
+    match client_hello.map(parsing::offered_session_ticket) {
+        Some(Ok(true)) => Resumption::SessionTicket,
+        Some(Ok(false)) => Resumption::SessionId,
+        _ => Resumption::Unknown,
+    }
+}
+
+fn early_data_to_schema(status: EarlyDataStatus) -> EarlyData {
+    match status {
+        EarlyDataStatus::Rejected => EarlyData::Rejected,
+        EarlyDataStatus::InProgress | EarlyDataStatus::End => EarlyData::Accepted,
+        _ => EarlyData::NotRequested,
+    }
+}
+
 pub(crate) struct NegotiatedParameters {
     pub version: Version,
     pub cipher: Cipher,
@@ -90,6 +155,10 @@ pub(crate) struct HandshakeRecordInProgress {
     /// the total number of failed handshakes
     handshake_failure_count: AtomicU64,
     alerts: Counter<DEFINED_ALERTS_COUNT, Alert>,
+    failure_errors: BoundedStringCounter,
+    failure_error_types: Counter<{ ErrorType::COUNT }, ErrorType>,
+    /// the handshake message being processed when the handshake failed
+    failure_messages: Counter<{ HandshakeMessage::COUNT }, HandshakeMessage>,
 
     negotiated_protocols: Counter<PROTOCOL_COUNT, Version>,
     negotiated_ciphers: Counter<CIPHER_COUNT, Cipher>,
@@ -121,9 +190,18 @@ pub(crate) struct HandshakeRecordInProgress {
     compatibility_fips20251201: AtomicU64,
     compatibility_cnsa1: AtomicU64,
     compatibility_cnsa2: AtomicU64,
+    /// compatibility with the profiles registered on the subscriber, by name
+    compatibility_profiles: BoundedStringCounter,
 
     client_issue: Counter<{ ClientIssue::COUNT }, ClientIssue>,
 
+    resumption: Counter<{ Resumption::COUNT }, Resumption>,
+    hello_retry_request: AtomicU64,
+    early_data: Counter<{ EarlyData::COUNT }, EarlyData>,
+    /// The most frequent JA4 fingerprints. Only populated if fingerprinting is
+    /// enabled on the subscriber, and includes failed handshakes.
+    client_hello_ja4: TopKStringCounter,
+
     /// sum of handshake duration, including network latency and waiting
     ///
     /// To get the average, divide this by handshake_success_count.
@@ -132,6 +210,16 @@ pub(crate) struct HandshakeRecordInProgress {
     ///
     /// To get the average, divide this by handshake_success_count.
     handshake_compute_us: AtomicU64,
+    /// distribution of handshake duration, for percentiles
+    handshake_duration_histogram: Histogram,
+    /// distribution of handshake compute, for percentiles
+    handshake_compute_histogram: Histogram,
+    /// time spent processing each handshake message.
+    ///
+    /// Unlike the other fields, this is populated from timing checkpoints
+    /// which arrive before the handshake is complete, so it includes failed
+    /// and synthetic handshakes.
+    handshake_message_histograms: Histograms<{ HandshakeMessage::COUNT }, HandshakeMessage>,
 
     /// Number of handshakes flagged by the configured
     /// [`SyntheticTrafficDetector`]. Synthetic handshakes are excluded from
@@ -150,6 +238,9 @@ impl HandshakeRecordInProgress {
             handshake_success_count: Default::default(),
             handshake_failure_count: Default::default(),
             alerts: Counter::new(),
+            failure_errors: Default::default(),
+            failure_error_types: Counter::new(),
+            failure_messages: Counter::new(),
 
             negotiated_groups: Counter::new(),
             negotiated_ciphers: Counter::new(),
@@ -177,11 +268,20 @@ impl HandshakeRecordInProgress {
             compatibility_fips20251201: AtomicU64::default(),
             compatibility_cnsa1: AtomicU64::default(),
             compatibility_cnsa2: AtomicU64::default(),
+            compatibility_profiles: Default::default(),
 
             client_issue: Counter::new(),
 
+            resumption: Counter::new(),
+            hello_retry_request: Default::default(),
+            early_data: Counter::new(),
+            client_hello_ja4: Default::default(),
+
             handshake_duration_us: Default::default(),
             handshake_compute_us: Default::default(),
+            handshake_duration_histogram: Histogram::new(),
+            handshake_compute_histogram: Histogram::new(),
+            handshake_message_histograms: Histograms::new(),
             synthetic_traffic_count: Default::default(),
             internal_failure: Default::default(),
             exporter,
@@ -196,6 +296,8 @@ impl HandshakeRecordInProgress {
         conn: &s2n_tls::connection::Connection,
         event: &s2n_tls::events::HandshakeEvent,
         detector: Option<&dyn SyntheticTrafficDetector>,
+        fingerprint_client_hello: bool,
+        compatibility_profiles: &[CompatibilityProfile],
     ) {
         // client_hello is only available on the server side of the connection.
         // Even on the server side, the client hello may not be populated if the
@@ -220,13 +322,38 @@ impl HandshakeRecordInProgress {
             }
         }
 
+        // s2n-tls can't fingerprint SSLv2 client hellos
+        if fingerprint_client_hello
+            && let (Some(client_hello), Ok(false)) = (client_hello, conn.client_hello_is_sslv2())
+            && let Err(e) = with_fingerprint(FingerprintType::JA4, client_hello, |ja4| {
+                self.client_hello_ja4.increment(ja4)
+            })
+        {
+            tracing::error!("failed to fingerprint client hello: {e}");
+            self.internal_failure.fetch_add(1, Ordering::Relaxed);
+        }
+
         let success = match event.result() {
-            s2n_tls::events::HandshakeResult::Failure(_) => {
+            s2n_tls::events::HandshakeResult::Failure(failure) => {
                 self.handshake_failure_count.fetch_add(1, Ordering::Relaxed);
                 let alert = conn.alert().map(Alert);
                 if let Some(alert) = alert {
                     self.alerts.increment(&alert);
                 }
+
+                let error = failure.error();
+                self.failure_errors.increment(error.name());
+                self.failure_error_types
+                    .increment(&error_type_to_schema(error.kind()));
+                // The last message may not be a handshake message, e.g. if the
+                // connection was closed before anything was received.
+                if let Some(message) = conn
+                    .last_message_name()
+                    .ok()
+                    .and_then(HandshakeMessage::from_name)
+                {
+                    self.failure_messages.increment(&message);
+                }
                 return;
             }
             s2n_tls::events::HandshakeResult::Success(s) => {
@@ -268,6 +395,29 @@ impl HandshakeRecordInProgress {
             self.negotiated_signatures.increment(&sig);
         }
 
+        // populate resumption metrics
+        let handshake_type = match conn.handshake_type() {
+            Ok(handshake_type) => handshake_type,
+            Err(e) => {
+                tracing::error!("failed to retrieve handshake type: {e}");
+                self.internal_failure.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+        };
+        self.resumption
+            .increment(&resumption(conn, handshake_type, client_hello));
+        if handshake_type.contains("HELLO_RETRY_REQUEST") {
+            self.hello_retry_request.fetch_add(1, Ordering::Relaxed);
+        }
+        match conn.early_data_status() {
+            Ok(status) => self.early_data.increment(&early_data_to_schema(status)),
+            Err(e) => {
+                tracing::error!("failed to retrieve early data status: {e}");
+                self.internal_failure.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+        }
+
         let supported_parameters = if let Some(client_hello) = client_hello {
             match (
                 conn.client_hello_is_sslv2(),
@@ -316,6 +466,10 @@ impl HandshakeRecordInProgress {
                     if Cnsa2::supported(&supported_parameter) {
                         self.compatibility_cnsa2.fetch_add(1, Ordering::Relaxed);
                     }
+                    compatibility_profiles
+                        .iter()
+                        .filter(|profile| profile.supported(&supported_parameter))
+                        .for_each(|profile| self.compatibility_profiles.increment(profile.name()));
                     Some(supported_parameter)
                 }
                 (Err(e), _) => {
@@ -426,6 +580,14 @@ impl HandshakeRecordInProgress {
         );
         self.handshake_duration_us
             .fetch_add(event.duration().as_micros() as u64, Ordering::Relaxed);
+        self.handshake_compute_histogram
+            .record(event.synchronous_time());
+        self.handshake_duration_histogram.record(event.duration());
+    }
+
+    /// Record the time spent processing a single handshake message.
+    pub fn record_message(&self, message: &HandshakeMessage, duration: Duration) {
+        self.handshake_message_histograms.record(message, duration);
     }
 
     /// make a copy of this record to be exported.
@@ -444,6 +606,9 @@ impl HandshakeRecordInProgress {
             handshake_success_count: self.handshake_success_count.load(Ordering::Relaxed),
             handshake_failure_count: self.handshake_failure_count.load(Ordering::Relaxed),
             alerts: self.alerts.freeze(),
+            failure_errors: self.failure_errors.freeze(),
+            failure_error_types: self.failure_error_types.freeze(),
+            failure_messages: self.failure_messages.freeze(),
             negotiated_protocols: self.negotiated_protocols.freeze(),
             negotiated_ciphers: self.negotiated_ciphers.freeze(),
             negotiated_groups: self.negotiated_groups.freeze(),
@@ -472,11 +637,20 @@ impl HandshakeRecordInProgress {
             compatibility_fips20251201: self.compatibility_fips20251201.load(Ordering::Relaxed),
             compatibility_cnsa1: self.compatibility_cnsa1.load(Ordering::Relaxed),
             compatibility_cnsa2: self.compatibility_cnsa2.load(Ordering::Relaxed),
+            compatibility_profiles: self.compatibility_profiles.freeze(),
 
             client_issues: self.client_issue.freeze(),
 
+            resumption: self.resumption.freeze(),
+            hello_retry_request: self.hello_retry_request.load(Ordering::Relaxed),
+            early_data: self.early_data.freeze(),
+            client_hello_ja4: self.client_hello_ja4.freeze(),
+
             handshake_duration_us: self.handshake_duration_us.load(Ordering::Relaxed),
             handshake_compute_us: self.handshake_compute_us.load(Ordering::Relaxed),
+            handshake_duration_histogram: self.handshake_duration_histogram.freeze(),
+            handshake_compute_histogram: self.handshake_compute_histogram.freeze(),
+            handshake_message_histograms: self.handshake_message_histograms.freeze(),
             synthetic_traffic_count: self.synthetic_traffic_count.load(Ordering::Relaxed),
             internal_failure: self.internal_failure.load(Ordering::Relaxed),
             security_policies: self.security_policies.freeze(),
@@ -842,6 +1016,155 @@ mod tests {
         assert!(single_handshake.handshake_duration_us < multiple_handshakes.handshake_duration_us);
     }
 
+    /// Client hello fingerprints are only counted when enabled.
+    #[test]
+    fn record_contents_ja4() {
+        let endpoint = TestEndpoint::new();
+        endpoint.client_handshake(&ARBITRARY_POLICY_1);
+        endpoint.subscriber.finish_record();
+        let records = endpoint.sink.records.lock().unwrap();
+        assert_eq!(records[0].as_schema().handshake.client_hello_ja4.total(), 0);
+
+        let sink = crate::test_utils::VecSink::new();
+        let subscriber =
+            crate::AggregatedMetricsSubscriber::new(sink.clone(), crate::Attribution::default())
+                .with_client_hello_fingerprints();
+        let server_config = {
+            let mut config =
+                s2n_tls::testing::config_builder(&s2n_tls::security::DEFAULT_TLS13).unwrap();
+            config.set_event_subscriber(subscriber.clone()).unwrap();
+            config.build().unwrap()
+        };
+        let client_config =
+            s2n_tls::testing::build_config(&s2n_tls::security::DEFAULT_TLS13).unwrap();
+        for _ in 0..2 {
+            let mut pair = s2n_tls::testing::TestPair::from_configs(&client_config, &server_config);
+            pair.handshake().unwrap();
+        }
+        subscriber.finish_record();
+
+        let records = sink.records.lock().unwrap();
+        let ja4 = &records[0].as_schema().handshake.client_hello_ja4;
+        // both handshakes used the same client config
+        assert_eq!(ja4.entries.len(), 1);
+        assert_eq!(ja4.total(), 2);
+        let (fingerprint, _) = ja4.entries.first_key_value().unwrap();
+        // TCP, TLS 1.3
+        assert!(fingerprint.starts_with("t13"), "{fingerprint}");
+    }
+
+    /// Registered compatibility profiles are counted by name.
+    #[test]
+    fn record_contents_compatibility_profiles() {
+        let profile = |name: &str| {
+            let policy = s2n_tls::security::Policy::from_version(name).unwrap();
+            CompatibilityProfile::from_policy(name, &policy).unwrap()
+        };
+
+        let sink = crate::test_utils::VecSink::new();
+        let subscriber =
+            crate::AggregatedMetricsSubscriber::new(sink.clone(), crate::Attribution::default())
+                .with_compatibility_profiles(vec![profile("default_tls13"), profile("cnsa_2")]);
+        let server_config = {
+            let mut config = s2n_tls::testing::config_builder(&ARBITRARY_POLICY_1).unwrap();
+            config.set_event_subscriber(subscriber.clone()).unwrap();
+            config.build().unwrap()
+        };
+        let client_config = s2n_tls::testing::build_config(&ARBITRARY_POLICY_1).unwrap();
+        let mut pair = s2n_tls::testing::TestPair::from_configs(&client_config, &server_config);
+        pair.handshake().unwrap();
+        subscriber.finish_record();
+
+        let records = sink.records.lock().unwrap();
+        let profiles = &records[0].as_schema().handshake.compatibility_profiles;
+        assert_eq!(profiles.get("default_tls13"), 1);
+        // a client without ML-KEM-1024 and ML-DSA-87 isn't compatible
+        assert_eq!(profiles.get("cnsa_2"), 0);
+        assert_eq!(profiles.total(), 1);
+    }
+
+    /// Full and resumed handshakes are counted by resumption mechanism.
+    #[test]
+    fn record_contents_resumption() -> Result<(), Box<dyn std::error::Error>> {
+        use s2n_tls::{
+            security::{DEFAULT_TLS13, Policy},
+            testing::{LIFOSessionResumption, TestPair, config_builder},
+        };
+        use std::{task::Waker, time::SystemTime};
+
+        for policy in [&DEFAULT_TLS13, &Policy::from_version("20170210")?] {
+            let sink = crate::test_utils::VecSink::new();
+            let attribution = crate::Attribution {
+                service: "test".to_owned(),
+                resource: "test".to_owned(),
+                component: "test".to_owned(),
+            };
+            let subscriber = crate::AggregatedMetricsSubscriber::new(sink.clone(), attribution);
+
+            let server_config = {
+                let mut builder = config_builder(policy)?;
+                builder.set_event_subscriber(subscriber.clone())?;
+                builder.add_session_ticket_key(
+                    b"a key name",
+                    b"good enough bytes for test",
+                    SystemTime::UNIX_EPOCH,
+                )?;
+                builder.build()?
+            };
+            let client_config = {
+                let session_tickets = LIFOSessionResumption::default();
+                let mut builder = config_builder(policy)?;
+                builder.enable_session_tickets(true)?;
+                builder.set_session_ticket_callback(session_tickets.clone())?;
+                builder.set_connection_initializer(session_tickets)?;
+                builder.build()?
+            };
+
+            for resumed in [false, true] {
+                let mut pair = TestPair::from_configs(&client_config, &server_config);
+                pair.client.set_waker(Some(Waker::noop()))?;
+                pair.handshake()?;
+                // read in the TLS 1.3 session ticket
+                assert!(pair.client.poll_recv(&mut [0]).is_pending());
+                assert_eq!(pair.server.resumed(), resumed);
+            }
+            subscriber.finish_record();
+            let records = sink.records.lock().unwrap();
+            let record = &records[0].as_schema().handshake;
+
+            assert_eq!(record.handshake_success_count, 2);
+            assert_eq!(record.resumption.get(&Resumption::Full), 1);
+            assert_eq!(record.resumption.get(&Resumption::SessionTicket), 1);
+            assert_eq!(record.resumption.total(), 2);
+            assert_eq!(record.hello_retry_request, 0);
+            assert_eq!(record.early_data.get(&EarlyData::NotRequested), 2);
+            assert_eq!(record.early_data.total(), 2);
+        }
+        Ok(())
+    }
+
+    /// Each successful handshake is counted once in the latency histograms, and
+    /// compute percentiles can't exceed duration percentiles.
+    #[test]
+    fn latency_histograms() {
+        let endpoint = TestEndpoint::new();
+
+        for _ in 0..3 {
+            endpoint.client_handshake(&ARBITRARY_POLICY_1);
+        }
+        endpoint.subscriber.finish_record();
+        let records = endpoint.sink.records.lock().unwrap();
+        let record = &records[0].as_schema().handshake;
+
+        let duration = &record.handshake_duration_histogram;
+        let compute = &record.handshake_compute_histogram;
+        assert_eq!(duration.count(), 3);
+        assert_eq!(compute.count(), 3);
+        for quantile in [0.5, 0.99] {
+            assert!(compute.quantile(quantile).unwrap() <= duration.quantile(quantile).unwrap());
+        }
+    }
+
     /// Helper: performs an mTLS handshake using distinct cert types for server
     /// (RSA 4096 / SHA384) and client (ECDSA P384 / SHA384) so the test can
     /// distinguish which cert ended up in which record field.
@@ -1141,6 +1464,58 @@ mod tests {
         assert_eq!(record.internal_failure, 0);
     }
 
+    /// Failed handshakes are broken down by error name, error type, and the
+    /// message being processed when the handshake failed.
+    #[test]
+    fn failure_breakdown() {
+        use std::task::Poll;
+
+        let sink = crate::test_utils::VecSink::new();
+        let attribution = crate::Attribution {
+            service: "test".to_owned(),
+            resource: "test".to_owned(),
+            component: "test".to_owned(),
+        };
+        let subscriber = crate::AggregatedMetricsSubscriber::new(sink.clone(), attribution);
+
+        let server_config = {
+            // doesn't allow TLS 1.3
+            let policy = s2n_tls::security::Policy::from_version("20141001").unwrap();
+            let mut config = s2n_tls::testing::config_builder(&policy).unwrap();
+            config.set_event_subscriber(subscriber.clone()).unwrap();
+            config.set_max_blinding_delay(0).unwrap();
+            config.build().unwrap()
+        };
+        // only allows TLS 1.3
+        let client_config =
+            s2n_tls::testing::build_config(&s2n_tls::security::DEFAULT_TLS13).unwrap();
+
+        let mut pair = s2n_tls::testing::TestPair::from_configs(&client_config, &server_config);
+        // Drive the client first to send the ClientHello, then the server
+        // to process it and fail.
+        let _ = pair.client.poll_negotiate();
+        let server_error = match pair.server.poll_negotiate() {
+            Poll::Ready(Err(e)) => e,
+            other => panic!("expected server error, got {:?}", other),
+        };
+
+        subscriber.finish_record();
+        let records = sink.records.lock().unwrap();
+        let record = &records[0].as_schema().handshake;
+
+        assert_eq!(record.handshake_failure_count, 1);
+        assert_eq!(record.failure_errors.get(server_error.name()), 1);
+        assert_eq!(record.failure_errors.total(), 1);
+        let error_type = error_type_to_schema(server_error.kind());
+        assert_eq!(record.failure_error_types.get(&error_type), 1);
+        assert_eq!(record.failure_error_types.total(), 1);
+        assert_eq!(
+            record.failure_messages.get(&HandshakeMessage::ClientHello),
+            1
+        );
+        assert_eq!(record.failure_messages.total(), 1);
+    }
+
     /// Make sure that the client issues are properly plumbed in, and show up in
     /// the record. We have to use OpenSSL to get a poorly configured client.
     #[test]
@@ -1,10 +1,11 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-use s2n_tls_metrics_schema::record::FrozenHandshakeRecord;
+use s2n_tls_metrics_schema::{record::FrozenHandshakeRecord, static_lists::HandshakeMessage};
 
 use crate::{
-    Attribution, MetricRecord, detector::SyntheticTrafficDetector,
+    Attribution, MetricRecord, checkpoints::HandshakeCheckpoints,
+    compatibility::CompatibilityProfile, detector::SyntheticTrafficDetector,
     record::HandshakeRecordInProgress, telemetry_sink::TelemetrySink,
 };
 use arc_swap::ArcSwap;
@@ -12,7 +13,7 @@ use s2n_tls::events::EventSubscriber;
 use std::{
     sync::{
         Arc, Mutex, OnceLock,
-        atomic::{AtomicU64, Ordering},
+        atomic::{AtomicBool, AtomicU64, Ordering},
         mpsc::{self, Receiver, Sender},
     },
     time::{Duration, SystemTime},
@@ -67,6 +68,18 @@ struct MetricSubscriberInner<S: TelemetrySink> {
     /// Installed once via `with_synthetic_traffic_detector`; the hot path
     /// reads it lock-free.
     synthetic_detector: OnceLock<Box<dyn SyntheticTrafficDetector>>,
+
+    /// Whether to record the JA4 fingerprint of each client hello. Enabled via
+    /// `with_client_hello_fingerprints`.
+    fingerprint_client_hello: AtomicBool,
+
+    /// Additional compatibility profiles to check each client hello against.
+    /// Installed once via `with_compatibility_profiles`.
+    compatibility_profiles: OnceLock<Vec<CompatibilityProfile>>,
+
+    /// The timestamp of the latest timing checkpoint of each connection's
+    /// handshake, which each handshake message is measured from.
+    checkpoints: HandshakeCheckpoints<Option<u64>>,
 }
 
 fn epoch_ms_now() -> u64 {
@@ -109,6 +122,9 @@ impl<S: TelemetrySink> AggregatedMetricsSubscriber<S> {
             export_interval,
             last_export_epoch_ms: AtomicU64::new(epoch_ms_now()),
             synthetic_detector: OnceLock::new(),
+            fingerprint_client_hello: AtomicBool::new(false),
+            compatibility_profiles: OnceLock::new(),
+            checkpoints: HandshakeCheckpoints::default(),
         };
         Self {
             inner: Arc::new(inner),
@@ -130,6 +146,33 @@ impl<S: TelemetrySink> AggregatedMetricsSubscriber<S> {
         self
     }
 
+    /// Count the JA4 fingerprints of client hellos, to help identify client
+    /// populations such as bots.
+    ///
+    /// Only the 16 most frequent fingerprints of each record are kept, each
+    /// with the handshakes it is guaranteed to account for. The remaining
+    /// handshakes are counted as `client_hello.ja4.OTHER`.
+    /// Fingerprinting isn't free, so this is disabled by default.
+    pub fn with_client_hello_fingerprints(self) -> Self {
+        self.inner
+            .fingerprint_client_hello
+            .store(true, Ordering::Relaxed);
+        self
+    }
+
+    /// Check each client hello against `profiles`, in addition to the built-in
+    /// compatibility profiles. See [`CompatibilityProfile`].
+    ///
+    /// Up to 32 profiles are reported individually. Compatible handshakes for
+    /// any further profiles are counted as `compatibility.profile.TOO_MANY`.
+    ///
+    /// Must be called before any handshake traffic begins. Subsequent calls
+    /// are silently ignored.
+    pub fn with_compatibility_profiles(self, profiles: Vec<CompatibilityProfile>) -> Self {
+        let _ = self.inner.compatibility_profiles.set(profiles);
+        self
+    }
+
     /// Finish aggregation of the record and export it.
     ///
     /// Note that this method will block until all other in-flight updates of the
@@ -236,19 +279,72 @@ impl<S: TelemetrySink> EventSubscriber for AggregatedMetricsSubscriber<S> {
         connection: &s2n_tls::connection::Connection,
         event: &s2n_tls::events::HandshakeEvent,
     ) {
+        self.inner.checkpoints.take(connection);
+
         let current_record = self.inner.current_record.load_full();
         let detector = self
             .inner
             .synthetic_detector
             .get()
             .map(|boxed| boxed.as_ref());
-        current_record.update(connection, event, detector);
+        let fingerprint_client_hello = self.inner.fingerprint_client_hello.load(Ordering::Relaxed);
+        let compatibility_profiles = self
+            .inner
+            .compatibility_profiles
+            .get()
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        current_record.update(
+            connection,
+            event,
+            detector,
+            fingerprint_client_hello,
+            compatibility_profiles,
+        );
         // Drop the Arc before attempting export so that finish_record can
         // observe the final reference count drop.
         drop(current_record);
 
         self.try_periodic_export();
     }
+
+    fn on_timing_checkpoint(
+        &self,
+        connection: &s2n_tls::connection::Connection,
+        checkpoint: &s2n_tls::events::TimingCheckpoint,
+    ) {
+        // Every checkpoint advances the clock, but only handshake messages
+        // are recorded. The others (e.g. NEGOTIATE_START, RECORD_READ) only
+        // mark the beginning of the next message.
+        let timestamp_ns = checkpoint.timestamp_ns();
+        let previous = self
+            .inner
+            .checkpoints
+            .record(connection, checkpoint, |latest| {
+                latest.replace(timestamp_ns)
+            })
+            .flatten();
+        let Some(elapsed) = previous
+            .and_then(|previous| timestamp_ns.checked_sub(previous))
+            .map(Duration::from_nanos)
+        else {
+            return;
+        };
+        let Some(message) = HandshakeMessage::from_name(checkpoint.name()) else {
+            return;
+        };
+        let current_record = self.inner.current_record.load_full();
+        current_record.record_message(&message, elapsed);
+    }
+
+    fn on_connection_close(
+        &self,
+        connection: &s2n_tls::connection::Connection,
+        _event: &s2n_tls::events::ConnectionCloseEvent,
+    ) {
+        // The handshake may not have finished.
+        self.inner.checkpoints.take(connection);
+    }
 }
 
 #[cfg(test)]
@@ -523,4 +619,41 @@ mod tests {
         assert_eq!(records[0].as_schema().handshake.handshake_success_count, 2);
         assert_eq!(records[0].as_schema().handshake.synthetic_traffic_count, 0);
     }
+
+    /// Every message the server processes during a TLS 1.3 handshake is
+    /// recorded in the message histograms.
+    #[test]
+    fn message_histograms() {
+        use s2n_tls_metrics_schema::static_lists::HandshakeMessage;
+
+        let endpoint = TestEndpoint::new();
+        for _ in 0..3 {
+            endpoint.client_handshake(&ARBITRARY_POLICY_1);
+        }
+        endpoint.subscriber.finish_record();
+        // The checkpoints of each connection are discarded after its handshake.
+        assert!(endpoint.subscriber.inner.checkpoints.is_empty());
+
+        let records = endpoint.sink.records.lock().unwrap();
+        let record = &records[0].as_schema().handshake;
+        let histograms = &record.handshake_message_histograms;
+        for message in [
+            HandshakeMessage::ClientHello,
+            HandshakeMessage::ServerHello,
+            HandshakeMessage::EncryptedExtensions,
+            HandshakeMessage::ServerCert,
+            HandshakeMessage::ServerCertVerify,
+            HandshakeMessage::ServerFinished,
+            HandshakeMessage::ClientFinished,
+        ] {
+            assert_eq!(histograms.get(&message).unwrap().count(), 3, "{message}");
+        }
+        // TLS 1.2 messages are not part of the handshake
+        assert!(
+            histograms
+                .get(&HandshakeMessage::ServerHelloDone)
+                .unwrap()
+                .is_empty()
+        );
+    }
 }
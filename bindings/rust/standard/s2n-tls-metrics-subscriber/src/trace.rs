@@ -0,0 +1,386 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Per-connection handshake telemetry through the [`tracing`] ecosystem.
+//!
+//! Unlike [`AggregatedMetricsSubscriber`](crate::AggregatedMetricsSubscriber),
+//! which only reports aggregated counts, [`TracingSubscriber`] emits one
+//! `tracing` event for every handshake it observes. This is useful for
+//! debugging individual connections, or for feeding an existing log pipeline.
+
+use std::{
+    fmt::Write as _,
+    num::NonZeroU64,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use s2n_tls::{
+    connection::Connection,
+    events::{
+        ConnectionCloseEvent, EventSubscriber, HandshakeEvent, HandshakeResult, TimingCheckpoint,
+    },
+};
+
+use crate::checkpoints::HandshakeCheckpoints;
+
+/// The `tracing` target of every event emitted by [`TracingSubscriber`].
+pub const TARGET: &str = "s2n_tls::handshake";
+
+/// Value recorded in place of a redacted field.
+const REDACTED: &str = "<redacted>";
+
+/// Fields which may identify the peer or the application, and can therefore
+/// be withheld from the emitted events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedactedField {
+    /// The `server_name` field, from the SNI extension.
+    ServerName,
+    /// The `application_protocol` field, from ALPN.
+    ApplicationProtocol,
+}
+
+/// An [`EventSubscriber`] which emits a `tracing` event for each handshake.
+///
+/// Successful handshakes are emitted at `INFO` and failed handshakes at `WARN`,
+/// both with the target [`TARGET`]. Each event has the fields
+/// - `security_policy`, `server_name`, `application_protocol`
+/// - `duration_us` and `synchronous_us`, see [`HandshakeEvent::duration`] and
+///   [`HandshakeEvent::synchronous_time`]
+/// - `checkpoints`: each timing checkpoint of the handshake as `NAME=offset_us`,
+///   measured from the start of the handshake
+/// - on success: `protocol`, `cipher`, and `group`
+/// - on failure: `error`, `error_type`, `error_message`, and `last_message`
+#[derive(Debug)]
+pub struct TracingSubscriber {
+    success_sample_rate: NonZeroU64,
+    successes_seen: AtomicU64,
+    redact_server_name: bool,
+    redact_application_protocol: bool,
+    /// Timing checkpoints of each handshake in progress, as
+    /// `(name, timestamp_ns)`.
+    checkpoints: HandshakeCheckpoints<Vec<(String, u64)>>,
+}
+
+impl Default for TracingSubscriber {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TracingSubscriber {
+    /// Create a subscriber which emits an event for every handshake, with no
+    /// redaction.
+    pub fn new() -> Self {
+        Self {
+            success_sample_rate: NonZeroU64::MIN,
+            successes_seen: AtomicU64::new(0),
+            redact_server_name: false,
+            redact_application_protocol: false,
+            checkpoints: HandshakeCheckpoints::default(),
+        }
+    }
+
+    /// Only emit an event for one in every `one_in` successful handshakes.
+    ///
+    /// Failed handshakes are always emitted, since they are both rarer and
+    /// more interesting.
+    pub fn with_success_sampling(mut self, one_in: NonZeroU64) -> Self {
+        self.success_sample_rate = one_in;
+        self
+    }
+
+    /// Record `<redacted>` instead of the value of `field`.
+    pub fn with_redacted_field(mut self, field: RedactedField) -> Self {
+        match field {
+            RedactedField::ServerName => self.redact_server_name = true,
+            RedactedField::ApplicationProtocol => self.redact_application_protocol = true,
+        }
+        self
+    }
+
+    fn sample_success(&self) -> bool {
+        let seen = self.successes_seen.fetch_add(1, Ordering::Relaxed);
+        seen.is_multiple_of(self.success_sample_rate.get())
+    }
+
+    fn server_name<'a>(&self, connection: &'a Connection) -> Option<&'a str> {
+        if self.redact_server_name {
+            connection.server_name().map(|_| REDACTED)
+        } else {
+            connection.server_name()
+        }
+    }
+
+    fn application_protocol<'a>(&self, connection: &'a Connection) -> Option<&'a str> {
+        let protocol = connection.application_protocol()?;
+        if self.redact_application_protocol {
+            Some(REDACTED)
+        } else {
+            // ALPN identifiers are usually, but not necessarily, printable
+            Some(std::str::from_utf8(protocol).unwrap_or("<non-utf8>"))
+        }
+    }
+}
+
+/// Format `checkpoints` as offsets in microseconds from the first of them.
+fn format_checkpoints(checkpoints: &[(String, u64)]) -> String {
+    let start = checkpoints.first().map_or(0, |(_, timestamp)| *timestamp);
+    let mut formatted = String::new();
+    for (name, timestamp) in checkpoints {
+        let offset_us = timestamp.saturating_sub(start) / 1_000;
+        if !formatted.is_empty() {
+            formatted.push(' ');
+        }
+        // writing to a String is infallible
+        let _ = write!(formatted, "{name}={offset_us}");
+    }
+    formatted
+}
+
+impl EventSubscriber for TracingSubscriber {
+    fn on_handshake_event(&self, connection: &Connection, event: &HandshakeEvent) {
+        // Always take the checkpoints, even if nothing will be emitted, so
+        // that they aren't retained after the handshake.
+        let checkpoints = self.checkpoints.take(connection).unwrap_or_default();
+
+        match event.result() {
+            HandshakeResult::Success(success) => {
+                if !tracing::enabled!(target: TARGET, tracing::Level::INFO)
+                    || !self.sample_success()
+                {
+                    return;
+                }
+                tracing::info!(
+                    target: TARGET,
+                    security_policy = event.security_policy_label(),
+                    server_name = self.server_name(connection),
+                    application_protocol = self.application_protocol(connection),
+                    duration_us = event.duration().as_micros() as u64,
+                    synchronous_us = event.synchronous_time().as_micros() as u64,
+                    checkpoints = format_checkpoints(&checkpoints),
+                    protocol = ?success.protocol_version(),
+                    cipher = success.cipher(),
+                    group = success.group(),
+                    "TLS handshake succeeded"
+                );
+            }
+            HandshakeResult::Failure(failure) => {
+                if !tracing::enabled!(target: TARGET, tracing::Level::WARN) {
+                    return;
+                }
+                let error = failure.error();
+                tracing::warn!(
+                    target: TARGET,
+                    security_policy = event.security_policy_label(),
+                    server_name = self.server_name(connection),
+                    application_protocol = self.application_protocol(connection),
+                    duration_us = event.duration().as_micros() as u64,
+                    synchronous_us = event.synchronous_time().as_micros() as u64,
+                    checkpoints = format_checkpoints(&checkpoints),
+                    error = error.name(),
+                    error_type = ?error.kind(),
+                    error_message = error.message(),
+                    last_message = connection.last_message_name().ok(),
+                    "TLS handshake failed"
+                );
+            }
+        }
+    }
+
+    fn on_timing_checkpoint(&self, connection: &Connection, checkpoint: &TimingCheckpoint) {
+        if !tracing::enabled!(target: TARGET, tracing::Level::WARN) {
+            return;
+        }
+        self.checkpoints
+            .record(connection, checkpoint, |checkpoints| {
+                checkpoints.push((checkpoint.name().to_owned(), checkpoint.timestamp_ns()));
+            });
+    }
+
+    fn on_connection_close(&self, connection: &Connection, _event: &ConnectionCloseEvent) {
+        // The handshake may not have finished.
+        self.checkpoints.take(connection);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    use s2n_tls::{
+        security::{DEFAULT_TLS13, Policy},
+        testing::{TestPair, build_config, config_builder},
+    };
+    use tracing::{
+        Event, Metadata,
+        field::{Field, Visit},
+        span,
+    };
+
+    use super::*;
+
+    #[derive(Default, Clone)]
+    struct CapturedEvent(HashMap<String, String>);
+
+    impl std::ops::Deref for CapturedEvent {
+        type Target = HashMap<String, String>;
+
+        fn deref(&self) -> &Self::Target {
+            &self.0
+        }
+    }
+
+    /// A minimal [`tracing::Subscriber`] which captures the fields of every
+    /// event.
+    #[derive(Default, Clone)]
+    struct Capture(Arc<Mutex<Vec<CapturedEvent>>>);
+
+    impl Visit for CapturedEvent {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0.insert(field.name().to_owned(), format!("{value:?}"));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_owned(), value.to_owned());
+        }
+    }
+
+    impl tracing::Subscriber for Capture {
+        fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+            metadata.target() == TARGET
+        }
+
+        fn new_span(&self, _span: &span::Attributes<'_>) -> span::Id {
+            span::Id::from_u64(1)
+        }
+
+        fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = CapturedEvent::default();
+            fields
+                .0
+                .insert("level".to_owned(), event.metadata().level().to_string());
+            event.record(&mut fields);
+            self.0.lock().unwrap().push(fields);
+        }
+
+        fn enter(&self, _span: &span::Id) {}
+
+        fn exit(&self, _span: &span::Id) {}
+    }
+
+    fn handshakes(
+        subscriber: TracingSubscriber,
+        server_policy: &Policy,
+        count: usize,
+    ) -> Vec<CapturedEvent> {
+        let capture = Capture::default();
+        tracing::subscriber::with_default(capture.clone(), || {
+            let server_config = {
+                let mut config = config_builder(server_policy).unwrap();
+                config.set_event_subscriber(subscriber).unwrap();
+                config.set_max_blinding_delay(0).unwrap();
+                config.build().unwrap()
+            };
+            let client_config = build_config(&DEFAULT_TLS13).unwrap();
+            for _ in 0..count {
+                let mut pair = TestPair::from_configs(&client_config, &server_config);
+                pair.client.set_server_name("localhost").unwrap();
+                let _ = pair.handshake();
+            }
+        });
+        let events = capture.0.lock().unwrap();
+        events.clone()
+    }
+
+    #[test]
+    fn success_event() {
+        let events = handshakes(TracingSubscriber::new(), &DEFAULT_TLS13, 1);
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+
+        assert_eq!(event["level"], "INFO");
+        assert_eq!(event["protocol"], "TLS13");
+        assert_eq!(event["cipher"], "TLS_AES_128_GCM_SHA256");
+        assert_eq!(event["server_name"], "localhost");
+        let checkpoints = &event["checkpoints"];
+        assert!(checkpoints.starts_with("NEGOTIATE_START=0 "));
+        assert!(checkpoints.contains(" CLIENT_HELLO="));
+        assert!(!event.contains_key("error"));
+    }
+
+    #[test]
+    fn failure_event() {
+        // doesn't allow TLS 1.3, which is all the client supports
+        let policy = Policy::from_version("20141001").unwrap();
+        let events = handshakes(TracingSubscriber::new(), &policy, 1);
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+
+        assert_eq!(event["level"], "WARN");
+        assert!(event.contains_key("error"));
+        assert!(event.contains_key("error_type"));
+        assert_eq!(event["last_message"], "CLIENT_HELLO");
+        assert!(!event.contains_key("cipher"));
+    }
+
+    /// The checkpoints of handshakes interleaved on one thread aren't mixed up.
+    #[test]
+    fn interleaved_handshakes() {
+        let capture = Capture::default();
+        tracing::subscriber::with_default(capture.clone(), || {
+            let server_config = {
+                let mut config = config_builder(&DEFAULT_TLS13).unwrap();
+                config
+                    .set_event_subscriber(TracingSubscriber::new())
+                    .unwrap();
+                config.set_max_blinding_delay(0).unwrap();
+                config.build().unwrap()
+            };
+            let client_config = build_config(&DEFAULT_TLS13).unwrap();
+            let mut pairs = [
+                TestPair::from_configs(&client_config, &server_config),
+                TestPair::from_configs(&client_config, &server_config),
+            ];
+            // step each handshake in turn, so that their checkpoints interleave
+            let mut finished = [false; 2];
+            while !finished.iter().all(|finished| *finished) {
+                for (pair, finished) in pairs.iter_mut().zip(&mut finished) {
+                    let client = pair.client.poll_negotiate();
+                    let server = pair.server.poll_negotiate();
+                    *finished = client.is_ready() && server.is_ready();
+                }
+            }
+        });
+
+        let events = capture.0.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        for event in events.iter() {
+            let checkpoints = &event["checkpoints"];
+            assert_eq!(checkpoints.matches("NEGOTIATE_START").count(), 1);
+            assert_eq!(checkpoints.matches("CLIENT_HELLO").count(), 1);
+        }
+    }
+
+    #[test]
+    fn success_sampling() {
+        let subscriber =
+            TracingSubscriber::new().with_success_sampling(NonZeroU64::new(3).unwrap());
+        let events = handshakes(subscriber, &DEFAULT_TLS13, 7);
+        // handshakes 1, 4, and 7
+        assert_eq!(events.len(), 3);
+    }
+
+    #[test]
+    fn redaction() {
+        let subscriber = TracingSubscriber::new().with_redacted_field(RedactedField::ServerName);
+        let events = handshakes(subscriber, &DEFAULT_TLS13, 1);
+        assert_eq!(events[0]["server_name"], "<redacted>");
+    }
+}
@@ -21,16 +21,29 @@ impl TelemetrySink for CaptureSink {
     }
 }
 
+/// Which handshake messages are timed depends on the s2n-tls state machine
+/// (e.g. middlebox compatibility), so per-message percentiles are left out of
+/// the snapshot. They're covered by the subscriber's unit tests.
+const MESSAGE_PERCENTILE_PREFIX: &str = "handshake_message_us.";
+
 /// Normalize non-deterministic values (timestamps, timings) so the snapshot
 /// is stable across runs.
 fn normalize(value: &mut serde_json::Value) {
     if let Some(obj) = value.as_object_mut() {
         // Zero out timing fields
-        for key in ["handshake_duration_us", "handshake_compute_us"] {
+        for key in [
+            "handshake_duration_us",
+            "handshake_duration_us.p50",
+            "handshake_duration_us.p99",
+            "handshake_compute_us",
+            "handshake_compute_us.p50",
+            "handshake_compute_us.p99",
+        ] {
             if obj.contains_key(key) {
                 obj.insert(key.to_owned(), serde_json::json!("<DURATION>"));
             }
         }
+        obj.retain(|key, _| !key.starts_with(MESSAGE_PERCENTILE_PREFIX));
         // Zero out the EMF timestamp
         if let Some(aws) = obj.get_mut("_aws").and_then(|v| v.as_object_mut()) {
             if aws.contains_key("Timestamp") {
@@ -42,6 +55,12 @@ fn normalize(value: &mut serde_json::Value) {
             normalize(v);
         }
     } else if let Some(arr) = value.as_array_mut() {
+        // Drop per-message percentiles from the EMF "Metrics" declarations
+        arr.retain(|v| {
+            !v.get("Name")
+                .and_then(|name| name.as_str())
+                .is_some_and(|name| name.starts_with(MESSAGE_PERCENTILE_PREFIX))
+        });
         for v in arr {
             normalize(v);
         }
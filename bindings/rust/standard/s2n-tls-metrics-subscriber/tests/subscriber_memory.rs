@@ -159,7 +159,7 @@ fn subscriber_allocation_budget() {
     // the mpsc channel allocs in chunks.
     const EXPORT: AllocDelta = AllocDelta {
         blocks: 5,
-        bytes: 4966,
+        bytes: 41100,
     };
 
     // Subscriber state is fixed-size, so nothing should be retained across
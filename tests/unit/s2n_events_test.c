@@ -21,6 +21,7 @@
 #include "error/s2n_errno.h"
 #include "s2n_test.h"
 #include "testlib/s2n_testlib.h"
+#include "tls/s2n_alerts.h"
 #include "tls/s2n_connection.h"
 #include "tls/s2n_post_handshake.h"
 #include "tls/s2n_tls.h"
@@ -28,6 +29,14 @@
 
 struct event_subscriber {
     uint64_t invoked;
+    uint64_t closes;
+    struct s2n_event_connection_close last_close;
+    uint64_t alerts;
+    struct s2n_event_alert last_alert;
+    uint64_t key_updates;
+    struct s2n_event_key_update last_key_update;
+    uint64_t session_tickets;
+    struct s2n_event_session_ticket last_session_ticket;
 };
 
 void subscriber_on_handshake_complete(
@@ -39,6 +48,46 @@ void subscriber_on_handshake_complete(
     sub->invoked++;
 }
 
+void subscriber_on_connection_close(
+        struct s2n_connection *conn,
+        void *subscriber,
+        struct s2n_event_connection_close *event)
+{
+    struct event_subscriber *sub = (struct event_subscriber *) subscriber;
+    sub->closes++;
+    sub->last_close = *event;
+}
+
+void subscriber_on_alert(
+        struct s2n_connection *conn,
+        void *subscriber,
+        struct s2n_event_alert *event)
+{
+    struct event_subscriber *sub = (struct event_subscriber *) subscriber;
+    sub->alerts++;
+    sub->last_alert = *event;
+}
+
+void subscriber_on_key_update(
+        struct s2n_connection *conn,
+        void *subscriber,
+        struct s2n_event_key_update *event)
+{
+    struct event_subscriber *sub = (struct event_subscriber *) subscriber;
+    sub->key_updates++;
+    sub->last_key_update = *event;
+}
+
+void subscriber_on_session_ticket(
+        struct s2n_connection *conn,
+        void *subscriber,
+        struct s2n_event_session_ticket *event)
+{
+    struct event_subscriber *sub = (struct event_subscriber *) subscriber;
+    sub->session_tickets++;
+    sub->last_session_ticket = *event;
+}
+
 int main(int argc, char **argv)
 {
     BEGIN_TEST();
@@ -85,5 +134,95 @@ int main(int argc, char **argv)
         EXPECT_EQUAL(subscriber.invoked, 1);
     }
 
+    /* lifecycle event callbacks can't be NULL */
+    {
+        EXPECT_FAILURE_WITH_ERRNO(s2n_config_set_connection_close_event(config, NULL), S2N_ERR_NULL);
+        EXPECT_FAILURE_WITH_ERRNO(s2n_config_set_alert_event(config, NULL), S2N_ERR_NULL);
+        EXPECT_FAILURE_WITH_ERRNO(s2n_config_set_key_update_event(config, NULL), S2N_ERR_NULL);
+        EXPECT_FAILURE_WITH_ERRNO(s2n_config_set_session_ticket_event(config, NULL), S2N_ERR_NULL);
+    };
+
+    /* lifecycle events are no-ops without a callback */
+    {
+        EXPECT_OK(s2n_event_alert_send(conn, S2N_TLS_ALERT_LEVEL_FATAL, S2N_TLS_ALERT_HANDSHAKE_FAILURE, true));
+        EXPECT_OK(s2n_event_key_update_send(conn, true, false));
+        EXPECT_OK(s2n_event_session_ticket_send(conn, true, 100));
+        conn->connection_start_ns = 1;
+        EXPECT_OK(s2n_event_connection_close_send(conn));
+        EXPECT_EQUAL(conn->connection_start_ns, 0);
+    };
+
+    EXPECT_SUCCESS(s2n_config_set_connection_close_event(config, subscriber_on_connection_close));
+    EXPECT_SUCCESS(s2n_config_set_alert_event(config, subscriber_on_alert));
+    EXPECT_SUCCESS(s2n_config_set_key_update_event(config, subscriber_on_key_update));
+    EXPECT_SUCCESS(s2n_config_set_session_ticket_event(config, subscriber_on_session_ticket));
+
+    /* s2n_event_connection_close_send: callback is invoked once */
+    {
+        /* no event for a connection that never negotiated */
+        EXPECT_OK(s2n_event_connection_close_send(conn));
+        EXPECT_EQUAL(subscriber.closes, 0);
+
+        conn->connection_start_ns = 1;
+        conn->wire_bytes_in = 10;
+        conn->wire_bytes_out = 20;
+        EXPECT_OK(s2n_event_connection_close_send(conn));
+        EXPECT_EQUAL(subscriber.closes, 1);
+        EXPECT_EQUAL(subscriber.last_close.wire_bytes_in, 10);
+        EXPECT_EQUAL(subscriber.last_close.wire_bytes_out, 20);
+        EXPECT_EQUAL(subscriber.last_close.connection_start_ns, 1);
+        EXPECT_TRUE(subscriber.last_close.connection_end_ns > 1);
+
+        /* idempotency: the event is only sent once */
+        EXPECT_OK(s2n_event_connection_close_send(conn));
+        EXPECT_EQUAL(subscriber.closes, 1);
+    };
+
+    /* s2n_connection_wipe and s2n_connection_free send the connection close event */
+    {
+        subscriber.closes = 0;
+
+        struct s2n_connection *closing = s2n_connection_new(S2N_CLIENT);
+        EXPECT_NOT_NULL(closing);
+        EXPECT_SUCCESS(s2n_connection_set_config(closing, config));
+
+        closing->connection_start_ns = 1;
+        EXPECT_SUCCESS(s2n_connection_wipe(closing));
+        EXPECT_EQUAL(subscriber.closes, 1);
+
+        /* the wiped connection hasn't negotiated, so it isn't reported */
+        EXPECT_SUCCESS(s2n_connection_wipe(closing));
+        EXPECT_EQUAL(subscriber.closes, 1);
+
+        closing->connection_start_ns = 1;
+        EXPECT_SUCCESS(s2n_connection_free(closing));
+        EXPECT_EQUAL(subscriber.closes, 2);
+    };
+
+    /* s2n_event_alert_send: callback is invoked */
+    {
+        EXPECT_OK(s2n_event_alert_send(conn, S2N_TLS_ALERT_LEVEL_FATAL, S2N_TLS_ALERT_HANDSHAKE_FAILURE, false));
+        EXPECT_EQUAL(subscriber.alerts, 1);
+        EXPECT_EQUAL(subscriber.last_alert.level, S2N_TLS_ALERT_LEVEL_FATAL);
+        EXPECT_EQUAL(subscriber.last_alert.description, S2N_TLS_ALERT_HANDSHAKE_FAILURE);
+        EXPECT_EQUAL(subscriber.last_alert.sent, 0);
+    };
+
+    /* s2n_event_key_update_send: callback is invoked */
+    {
+        EXPECT_OK(s2n_event_key_update_send(conn, true, true));
+        EXPECT_EQUAL(subscriber.key_updates, 1);
+        EXPECT_EQUAL(subscriber.last_key_update.sent, 1);
+        EXPECT_EQUAL(subscriber.last_key_update.update_requested, 1);
+    };
+
+    /* s2n_event_session_ticket_send: callback is invoked */
+    {
+        EXPECT_OK(s2n_event_session_ticket_send(conn, false, 3600));
+        EXPECT_EQUAL(subscriber.session_tickets, 1);
+        EXPECT_EQUAL(subscriber.last_session_ticket.sent, 0);
+        EXPECT_EQUAL(subscriber.last_session_ticket.lifetime_secs, 3600);
+    };
+
     END_TEST();
 }
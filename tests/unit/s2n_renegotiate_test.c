@@ -219,6 +219,31 @@ int main(int argc, char *argv[])
             EXPECT_EQUAL(original_out_size, wiped_out_size);
         };
 
+        /* Connection start time unaffected by wipe */
+        {
+            DEFER_CLEANUP(struct s2n_connection *server_conn = s2n_connection_new(S2N_SERVER), s2n_connection_ptr_free);
+            EXPECT_NOT_NULL(server_conn);
+            EXPECT_SUCCESS(s2n_connection_set_config(server_conn, config));
+
+            DEFER_CLEANUP(struct s2n_connection *client_conn = s2n_connection_new(S2N_CLIENT), s2n_connection_ptr_free);
+            EXPECT_NOT_NULL(client_conn);
+            EXPECT_SUCCESS(s2n_connection_set_config(client_conn, config));
+
+            DEFER_CLEANUP(struct s2n_stuffer in = { 0 }, s2n_stuffer_free);
+            DEFER_CLEANUP(struct s2n_stuffer out = { 0 }, s2n_stuffer_free);
+            EXPECT_SUCCESS(s2n_stuffer_growable_alloc(&in, 0));
+            EXPECT_SUCCESS(s2n_stuffer_growable_alloc(&out, 0));
+            EXPECT_SUCCESS(s2n_connection_set_io_stuffers(&in, &out, client_conn));
+            EXPECT_SUCCESS(s2n_connection_set_io_stuffers(&out, &in, server_conn));
+
+            EXPECT_SUCCESS(s2n_negotiate_test_server_and_client(server_conn, client_conn));
+            uint64_t connection_start_ns = client_conn->connection_start_ns;
+            EXPECT_NOT_EQUAL(connection_start_ns, 0);
+
+            EXPECT_SUCCESS(s2n_renegotiate_wipe(client_conn));
+            EXPECT_EQUAL(client_conn->connection_start_ns, connection_start_ns);
+        };
+
         /* Handshake succeeds after wipe */
         {
             DEFER_CLEANUP(struct s2n_connection *server_conn = s2n_connection_new(S2N_SERVER), s2n_connection_ptr_free);
@@ -24,6 +24,7 @@
 #include "tls/s2n_tls_parameters.h"
 #include "utils/s2n_atomic.h"
 #include "utils/s2n_blob.h"
+#include "utils/s2n_events.h"
 #include "utils/s2n_safety.h"
 
 #define S2N_ALERT_CASE(error, alert_code) \
@@ -275,6 +276,7 @@ int s2n_process_alert_fragment(struct s2n_connection *conn)
     POSIX_ENSURE(s2n_alerts_supported(conn), S2N_ERR_BAD_MESSAGE);
 
     POSIX_GUARD(s2n_stuffer_copy(&conn->in, &conn->alert_in, 2));
+    POSIX_GUARD_RESULT(s2n_event_alert_send(conn, conn->alert_in_data[0], conn->alert_in_data[1], false));
 
     /* Close notifications are handled as shutdowns */
     if (conn->alert_in_data[1] == S2N_TLS_ALERT_CLOSE_NOTIFY) {
@@ -387,6 +389,7 @@ S2N_RESULT s2n_alerts_write_error_or_close_notify(struct s2n_connection *conn)
 
     RESULT_GUARD(s2n_record_write(conn, TLS_ALERT, &alert));
     conn->alert_sent = true;
+    RESULT_GUARD(s2n_event_alert_send(conn, level, code, true));
     return S2N_RESULT_OK;
 }
 
@@ -404,5 +407,6 @@ S2N_RESULT s2n_alerts_write_warning(struct s2n_connection *conn)
     RESULT_GUARD_POSIX(s2n_blob_init(&alert, alert_bytes, sizeof(alert_bytes)));
 
     RESULT_GUARD(s2n_record_write(conn, TLS_ALERT, &alert));
+    RESULT_GUARD(s2n_event_alert_send(conn, level, code, true));
     return S2N_RESULT_OK;
 }
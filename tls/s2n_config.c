@@ -1374,3 +1374,35 @@ int s2n_config_set_timing_checkpoint_cb(struct s2n_config *config, s2n_event_on_
     config->on_timing_checkpoint_cb = callback;
     return S2N_SUCCESS;
 }
+
+int s2n_config_set_connection_close_event(struct s2n_config *config, s2n_event_on_connection_close_cb callback)
+{
+    POSIX_ENSURE_REF(config);
+    POSIX_ENSURE_REF(callback);
+    config->on_connection_close_event = callback;
+    return S2N_SUCCESS;
+}
+
+int s2n_config_set_alert_event(struct s2n_config *config, s2n_event_on_alert_cb callback)
+{
+    POSIX_ENSURE_REF(config);
+    POSIX_ENSURE_REF(callback);
+    config->on_alert_event = callback;
+    return S2N_SUCCESS;
+}
+
+int s2n_config_set_key_update_event(struct s2n_config *config, s2n_event_on_key_update_cb callback)
+{
+    POSIX_ENSURE_REF(config);
+    POSIX_ENSURE_REF(callback);
+    config->on_key_update_event = callback;
+    return S2N_SUCCESS;
+}
+
+int s2n_config_set_session_ticket_event(struct s2n_config *config, s2n_event_on_session_ticket_cb callback)
+{
+    POSIX_ENSURE_REF(config);
+    POSIX_ENSURE_REF(callback);
+    config->on_session_ticket_event = callback;
+    return S2N_SUCCESS;
+}
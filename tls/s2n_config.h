@@ -217,6 +217,10 @@ struct s2n_config {
     void *subscriber;
     s2n_event_on_handshake_cb on_handshake_event;
     s2n_event_on_timing_checkpoint_cb on_timing_checkpoint_cb;
+    s2n_event_on_connection_close_cb on_connection_close_event;
+    s2n_event_on_alert_cb on_alert_event;
+    s2n_event_on_key_update_cb on_key_update_event;
+    s2n_event_on_session_ticket_cb on_session_ticket_event;
 
     /* The user defined context associated with config */
     void *context;
@@ -49,6 +49,7 @@
 #include "utils/s2n_atomic.h"
 #include "utils/s2n_blob.h"
 #include "utils/s2n_compiler.h"
+#include "utils/s2n_events.h"
 #include "utils/s2n_io.h"
 #include "utils/s2n_mem.h"
 #include "utils/s2n_random.h"
@@ -261,6 +262,9 @@ S2N_CLEANUP_RESULT s2n_connection_ptr_free(struct s2n_connection **conn)
 
 int s2n_connection_free(struct s2n_connection *conn)
 {
+    /* Best effort: failing to report the close must not leak the connection */
+    s2n_result_ignore(s2n_event_connection_close_send(conn));
+
     POSIX_GUARD(s2n_connection_wipe_keys(conn));
     POSIX_GUARD_RESULT(s2n_psk_parameters_wipe(&conn->psk_params));
 
@@ -500,6 +504,10 @@ int s2n_connection_wipe(struct s2n_connection *conn)
 {
     POSIX_ENSURE_REF(conn);
 
+    /* Report the close of the connection being wiped before its state is lost.
+     * Best effort: failing to report the close must not fail the wipe. */
+    s2n_result_ignore(s2n_event_connection_close_send(conn));
+
     /* First make a copy of everything we'd like to save, which isn't very much. */
     int mode = conn->mode;
     struct s2n_config *config = conn->config;
@@ -406,6 +406,9 @@ struct s2n_connection {
     uint8_t recv_key_updated;
 
     struct s2n_event_handshake handshake_event;
+    /* The first call to s2n_negotiate, for the connection close event.
+     * Reset to 0 once the event has been sent. */
+    uint64_t connection_start_ns;
 };
 
 S2N_CLEANUP_RESULT s2n_connection_ptr_free(struct s2n_connection **s2n_connection);
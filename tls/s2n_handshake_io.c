@@ -1753,6 +1753,10 @@ int s2n_negotiate(struct s2n_connection *conn, s2n_blocked_status *blocked)
     POSIX_GUARD(s2n_default_monotonic_clock(NULL, &negotiate_start));
     if (conn->handshake_event.handshake_start_ns == 0) {
         conn->handshake_event.handshake_start_ns = negotiate_start;
+        /* A renegotiation handshake doesn't start a new connection */
+        if (conn->connection_start_ns == 0) {
+            conn->connection_start_ns = negotiate_start;
+        }
         POSIX_GUARD_RESULT(s2n_event_checkpoint_send(
                 conn, "NEGOTIATE_START", (uint8_t) conn->mode));
     }
@@ -23,6 +23,7 @@
 #include "tls/s2n_tls.h"
 #include "tls/s2n_tls13_handshake.h"
 #include "utils/s2n_atomic.h"
+#include "utils/s2n_events.h"
 #include "utils/s2n_safety.h"
 
 static s2n_peer_key_update key_update_request_val = S2N_KEY_UPDATE_NOT_REQUESTED;
@@ -64,6 +65,7 @@ int s2n_key_update_recv(struct s2n_connection *conn, struct s2n_stuffer *request
         POSIX_GUARD_RESULT(s2n_ktls_key_update_process(conn));
     }
 
+    POSIX_GUARD_RESULT(s2n_event_key_update_send(conn, false, key_update_request == S2N_KEY_UPDATE_REQUESTED));
     return S2N_SUCCESS;
 }
 
@@ -103,6 +105,7 @@ int s2n_key_update_send(struct s2n_connection *conn, s2n_blocked_status *blocked
         POSIX_GUARD(s2n_update_application_traffic_keys(conn, conn->mode, SENDING));
 
         s2n_atomic_flag_clear(&conn->key_update_pending);
+        POSIX_GUARD_RESULT(s2n_event_key_update_send(conn, true, key_update_request_val == S2N_KEY_UPDATE_REQUESTED));
         POSIX_GUARD(s2n_flush(conn, blocked));
     }
 
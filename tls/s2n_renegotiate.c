@@ -101,6 +101,11 @@ int s2n_renegotiate_wipe(struct s2n_connection *conn)
     uint64_t wire_bytes_in = conn->wire_bytes_in;
     uint64_t wire_bytes_out = conn->wire_bytes_out;
 
+    /* Save the connection start time.
+     * Renegotiation doesn't close the connection, so the wipe must not report a close event. */
+    uint64_t connection_start_ns = conn->connection_start_ns;
+    conn->connection_start_ns = 0;
+
     /* Save io settings */
     bool send_managed = conn->managed_send_io;
     s2n_send_fn *send_fn = conn->send;
@@ -150,6 +155,7 @@ int s2n_renegotiate_wipe(struct s2n_connection *conn)
     conn->client_protocol_version = client_protocol_version;
     conn->wire_bytes_in = wire_bytes_in;
     conn->wire_bytes_out = wire_bytes_out;
+    conn->connection_start_ns = connection_start_ns;
     conn->managed_send_io = send_managed;
     conn->send = send_fn;
     conn->send_io_context = send_ctx;
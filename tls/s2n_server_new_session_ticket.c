@@ -24,6 +24,7 @@
 #include "tls/s2n_resume.h"
 #include "tls/s2n_tls.h"
 #include "tls/s2n_tls13_handshake.h"
+#include "utils/s2n_events.h"
 #include "utils/s2n_random.h"
 #include "utils/s2n_safety.h"
 
@@ -50,6 +51,8 @@ int s2n_server_nst_recv(struct s2n_connection *conn)
 
         POSIX_GUARD(s2n_stuffer_read(&conn->handshake.io, &conn->client_ticket));
 
+        POSIX_GUARD_RESULT(s2n_event_session_ticket_send(conn, false, conn->ticket_lifetime_hint));
+
         if (conn->config->session_ticket_cb != NULL) {
             size_t session_len = s2n_connection_get_session_length(conn);
 
@@ -150,6 +153,7 @@ int s2n_server_nst_send(struct s2n_connection *conn)
      * This simplifies s2n_connection_get_tickets_sent.
      */
     conn->tickets_sent++;
+    POSIX_GUARD_RESULT(s2n_event_session_ticket_send(conn, true, lifetime_hint_in_secs));
     return S2N_SUCCESS;
 }
 
@@ -355,6 +359,7 @@ S2N_RESULT s2n_tls13_server_nst_write(struct s2n_connection *conn, struct s2n_st
 
     RESULT_ENSURE(conn->tickets_sent < UINT16_MAX, S2N_ERR_INTEGER_OVERFLOW);
     conn->tickets_sent++;
+    RESULT_GUARD(s2n_event_session_ticket_send(conn, true, ticket_lifetime_in_secs));
 
     return S2N_RESULT_OK;
 }
@@ -424,6 +429,8 @@ S2N_RESULT s2n_tls13_server_nst_recv(struct s2n_connection *conn, struct s2n_stu
     /* Handle `extensions` field */
     RESULT_GUARD_POSIX(s2n_extension_list_recv(S2N_EXTENSION_LIST_NST, conn, input));
 
+    RESULT_GUARD(s2n_event_session_ticket_send(conn, false, ticket_lifetime));
+
     if (conn->config->session_ticket_cb != NULL) {
         /* Retrieve serialized session data */
         const uint16_t session_state_size = s2n_connection_get_session_length(conn);
@@ -94,3 +94,104 @@ S2N_RESULT s2n_event_checkpoint_send(struct s2n_connection *conn, const char *na
     conn->config->on_timing_checkpoint_cb(conn, conn->config->subscriber, &checkpoint);
     return S2N_RESULT_OK;
 }
+
+/**
+ * Emit the connection close event, if s2n_negotiate was called on the
+ * connection and the event hasn't already been sent.
+ *
+ * No-op if no callback is registered or config is NULL.
+ */
+S2N_RESULT s2n_event_connection_close_send(struct s2n_connection *conn)
+{
+    RESULT_ENSURE_REF(conn);
+
+    if (conn->connection_start_ns == 0) {
+        return S2N_RESULT_OK;
+    }
+    uint64_t connection_start_ns = conn->connection_start_ns;
+    conn->connection_start_ns = 0;
+
+    if (conn->config == NULL || conn->config->on_connection_close_event == NULL) {
+        return S2N_RESULT_OK;
+    }
+
+    uint64_t connection_end_ns = 0;
+    RESULT_GUARD_POSIX(s2n_default_monotonic_clock(NULL, &connection_end_ns));
+
+    struct s2n_event_connection_close event = {
+        .wire_bytes_in = conn->wire_bytes_in,
+        .wire_bytes_out = conn->wire_bytes_out,
+        .connection_start_ns = connection_start_ns,
+        .connection_end_ns = connection_end_ns,
+    };
+
+    conn->config->on_connection_close_event(conn, conn->config->subscriber, &event);
+    return S2N_RESULT_OK;
+}
+
+/**
+ * Emit an alert event.
+ *
+ * No-op if no callback is registered or config is NULL.
+ */
+S2N_RESULT s2n_event_alert_send(struct s2n_connection *conn, uint8_t level, uint8_t description, bool sent)
+{
+    RESULT_ENSURE_REF(conn);
+
+    if (conn->config == NULL || conn->config->on_alert_event == NULL) {
+        return S2N_RESULT_OK;
+    }
+
+    struct s2n_event_alert event = {
+        .level = level,
+        .description = description,
+        .sent = sent,
+    };
+
+    conn->config->on_alert_event(conn, conn->config->subscriber, &event);
+    return S2N_RESULT_OK;
+}
+
+/**
+ * Emit a KeyUpdate event.
+ *
+ * No-op if no callback is registered or config is NULL.
+ */
+S2N_RESULT s2n_event_key_update_send(struct s2n_connection *conn, bool sent, bool update_requested)
+{
+    RESULT_ENSURE_REF(conn);
+
+    if (conn->config == NULL || conn->config->on_key_update_event == NULL) {
+        return S2N_RESULT_OK;
+    }
+
+    struct s2n_event_key_update event = {
+        .sent = sent,
+        .update_requested = update_requested,
+    };
+
+    conn->config->on_key_update_event(conn, conn->config->subscriber, &event);
+    return S2N_RESULT_OK;
+}
+
+/**
+ * Emit a session ticket event.
+ *
+ * No-op if no callback is registered or config is NULL.
+ */
+S2N_RESULT s2n_event_session_ticket_send(struct s2n_connection *conn, bool sent, uint32_t lifetime_secs)
+{
+    RESULT_ENSURE_REF(conn);
+
+    if (conn->config == NULL || conn->config->on_session_ticket_event == NULL) {
+        return S2N_RESULT_OK;
+    }
+
+    struct s2n_event_session_ticket event = {
+        .sent = sent,
+        .lifetime_secs = lifetime_secs,
+    };
+
+    conn->config->on_session_ticket_event(conn, conn->config->subscriber, &event);
+    return S2N_RESULT_OK;
+}
@@ -26,3 +26,8 @@ S2N_RESULT s2n_event_handshake_populate(struct s2n_connection* conn, struct s2n_
 S2N_RESULT s2n_event_handshake_send(struct s2n_connection* conn, struct s2n_event_handshake* event);
 
 S2N_RESULT s2n_event_checkpoint_send(struct s2n_connection* conn, const char* name, uint8_t role);
+
+S2N_RESULT s2n_event_connection_close_send(struct s2n_connection* conn);
+S2N_RESULT s2n_event_alert_send(struct s2n_connection* conn, uint8_t level, uint8_t description, bool sent);
+S2N_RESULT s2n_event_key_update_send(struct s2n_connection* conn, bool sent, bool update_requested);
+S2N_RESULT s2n_event_session_ticket_send(struct s2n_connection* conn, bool sent, uint32_t lifetime_secs);